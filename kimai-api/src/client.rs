@@ -0,0 +1,826 @@
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{ring, verify_tls12_signature, verify_tls13_signature, CryptoProvider};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+
+use crate::error::{ApiError, ConnectionError, DryRun};
+use crate::models::*;
+
+/// Where the server is and how to talk to it, usually read from a
+/// configuration file.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Settings {
+    pub endpoint: String,
+    /// May be left out when the token is kept elsewhere, e.g. in a keyring.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub token: String,
+    /// How the token is sent.
+    #[serde(default, skip_serializing_if = "Auth::is_bearer")]
+    pub auth: Auth,
+    /// The user the token belongs to, for [`Auth::Legacy`].
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub username: String,
+    /// Number of items to request per page from paginated endpoints.
+    #[serde(default = "default_page_size")]
+    pub page_size: usize,
+    /// Upper bound on the number of items fetched from paginated endpoints.
+    #[serde(default = "default_max_results")]
+    pub max_results: usize,
+    /// Seconds to wait for a connection to the server.
+    #[serde(default = "default_connect_timeout")]
+    pub connect_timeout: u64,
+    /// Seconds to wait for the server to answer.
+    #[serde(default = "default_read_timeout")]
+    pub read_timeout: u64,
+    /// How often to try a request before giving up on network errors and
+    /// server errors (5xx).
+    #[serde(default = "default_attempts")]
+    pub attempts: u32,
+    /// PEM file with the certificate of a private CA that signed the
+    /// server's certificate, trusted besides the usual public ones.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_cert: Option<PathBuf>,
+    /// Accept any certificate the server presents. Anyone in between can
+    /// then read the token, so this is only for trying things out.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub insecure: bool,
+    /// HTTP proxy for requests to the server, e.g. `http://proxy:3128`.
+    /// Without it `HTTPS_PROXY`, `HTTP_PROXY` and `NO_PROXY` are honored.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+}
+
+/// How requests are authenticated.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Auth {
+    /// An API token sent as `Authorization: Bearer`, as in Kimai 2.
+    #[default]
+    Bearer,
+    /// The username and API password as `X-AUTH-USER` and `X-AUTH-TOKEN`,
+    /// as in Kimai 1.x and still accepted by early 2.x releases.
+    Legacy,
+}
+
+impl Auth {
+    fn is_bearer(&self) -> bool {
+        *self == Auth::Bearer
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            endpoint: String::new(),
+            token: String::new(),
+            auth: Auth::default(),
+            username: String::new(),
+            page_size: default_page_size(),
+            max_results: default_max_results(),
+            connect_timeout: default_connect_timeout(),
+            read_timeout: default_read_timeout(),
+            attempts: default_attempts(),
+            ca_cert: None,
+            insecure: false,
+            proxy: None,
+        }
+    }
+}
+
+fn default_page_size() -> usize {
+    100
+}
+
+fn default_max_results() -> usize {
+    10_000
+}
+
+fn default_connect_timeout() -> u64 {
+    10
+}
+
+fn default_read_timeout() -> u64 {
+    30
+}
+
+fn default_attempts() -> u32 {
+    3
+}
+
+/// A connection to one Kimai instance, authenticated with an API token.
+/// Clones share their HTTP agent, and with it open connections, so only the
+/// first request pays for the TLS handshake.
+#[derive(Debug, Clone)]
+pub struct Client {
+    settings: Settings,
+    agent: Arc<OnceLock<ureq::Agent>>,
+    dry_run: bool,
+    /// Whether the server is Kimai 1.x, whose API differs in a few places.
+    legacy: bool,
+}
+
+/// Accepts every certificate, for [`Settings::insecure`]. Signatures are
+/// still checked, so the connection is at least encrypted.
+#[derive(Debug)]
+struct AnyCertificate(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for AnyCertificate {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// The TLS setup for `settings`, or `None` for ureq's default.
+fn tls_config(settings: &Settings) -> Result<Option<Arc<ClientConfig>>> {
+    if settings.ca_cert.is_none() && !settings.insecure {
+        return Ok(None);
+    }
+    let provider = Arc::new(ring::default_provider());
+    let builder = ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()?;
+    let config = if settings.insecure {
+        builder
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(AnyCertificate(provider)))
+            .with_no_client_auth()
+    } else {
+        let mut roots = RootCertStore {
+            roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+        };
+        if let Some(path) = &settings.ca_cert {
+            let certs = CertificateDer::pem_file_iter(path)
+                .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+                .with_context(|| format!("Could not read ca_cert {}", path.display()))?;
+            let (added, _) = roots.add_parsable_certificates(certs);
+            if added == 0 {
+                anyhow::bail!("ca_cert {} contains no certificate", path.display());
+            }
+        }
+        builder.with_root_certificates(roots).with_no_client_auth()
+    };
+    Ok(Some(Arc::new(config)))
+}
+
+/// The first of the environment variables `names` that is set, in upper or
+/// lower case.
+fn env(names: &[&str]) -> Option<String> {
+    names
+        .iter()
+        .flat_map(|name| [name.to_string(), name.to_lowercase()])
+        .find_map(|name| std::env::var(name).ok())
+        .filter(|value| !value.trim().is_empty())
+}
+
+/// Whether `NO_PROXY`, a comma separated list of hosts and domains like
+/// `.example.com`, says `host` is reached without a proxy.
+fn bypasses_proxy(no_proxy: &str, host: &str) -> bool {
+    no_proxy
+        .split(',')
+        .map(|entry| entry.trim().trim_start_matches('.'))
+        .filter(|entry| !entry.is_empty())
+        .any(|entry| {
+            // Without the port; IPv6 addresses have colons of their own
+            let entry = match entry.strip_prefix('[') {
+                Some(ipv6) => ipv6.split(']').next().unwrap_or_default(),
+                None if entry.matches(':').count() == 1 => {
+                    entry.split(':').next().unwrap_or_default()
+                }
+                None => entry,
+            };
+            entry == "*"
+                || host.eq_ignore_ascii_case(entry)
+                || host
+                    .to_lowercase()
+                    .ends_with(&format!(".{}", entry.to_lowercase()))
+        })
+}
+
+/// The proxy to reach the server through: `proxy` from the settings, or else
+/// the one the environment names for the endpoint's scheme.
+fn proxy(settings: &Settings) -> Result<Option<ureq::Proxy>> {
+    let url = match &settings.proxy {
+        Some(proxy) => proxy.clone(),
+        None => {
+            let (scheme, rest) = settings
+                .endpoint
+                .split_once("://")
+                .unwrap_or(("http", &settings.endpoint));
+            let authority = rest.split('/').next().unwrap_or_default();
+            let authority = authority.rsplit('@').next().unwrap_or_default();
+            let host = match authority.strip_prefix('[') {
+                Some(ipv6) => ipv6.split(']').next().unwrap_or_default(),
+                None => authority.split(':').next().unwrap_or_default(),
+            };
+            if env(&["NO_PROXY"]).is_some_and(|no_proxy| bypasses_proxy(&no_proxy, host)) {
+                return Ok(None);
+            }
+            let names: &[&str] = if scheme == "https" {
+                &["HTTPS_PROXY", "ALL_PROXY"]
+            } else {
+                &["HTTP_PROXY", "ALL_PROXY"]
+            };
+            match env(names) {
+                Some(proxy) => proxy,
+                None => return Ok(None),
+            }
+        }
+    };
+    let proxy = ureq::Proxy::new(&url).with_context(|| format!("Invalid proxy '{}'", url))?;
+    Ok(Some(proxy))
+}
+
+/// The TLS problem a transport error comes from, if any. Those won't go away
+/// by trying again.
+fn tls_error(transport: &ureq::Transport) -> Option<&rustls::Error> {
+    let mut source = std::error::Error::source(transport);
+    while let Some(error) = source {
+        if let Some(tls) = error.downcast_ref::<rustls::Error>() {
+            return Some(tls);
+        }
+        source = match error.downcast_ref::<std::io::Error>() {
+            Some(io) => io
+                .get_ref()
+                .map(|inner| inner as &(dyn std::error::Error + 'static)),
+            None => error.source(),
+        };
+    }
+    None
+}
+
+/// Whether a failed attempt is worth repeating. Requests that change data are
+/// only repeated if they never reached the server, so they can't be applied twice.
+fn is_retryable(method: &str, error: &ureq::Error) -> bool {
+    let idempotent = matches!(method, "GET" | "PUT" | "DELETE");
+    match error {
+        ureq::Error::Status(code, _) => *code >= 500 && idempotent,
+        ureq::Error::Transport(transport) if tls_error(transport).is_some() => false,
+        ureq::Error::Transport(transport) => match transport.kind() {
+            ureq::ErrorKind::Dns | ureq::ErrorKind::ConnectionFailed => true,
+            ureq::ErrorKind::Io => idempotent,
+            _ => false,
+        },
+    }
+}
+
+/// `value` with whatever looks like a secret blanked out, for the logs.
+fn redacted(mut value: serde_json::Value) -> serde_json::Value {
+    match &mut value {
+        serde_json::Value::Object(fields) => {
+            for (name, field) in fields.iter_mut() {
+                let name = name.to_lowercase();
+                *field = if name.contains("token") || name.contains("password") {
+                    serde_json::Value::String("********".to_string())
+                } else {
+                    redacted(field.take())
+                };
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items.iter_mut() {
+                *item = redacted(item.take());
+            }
+        }
+        _ => {}
+    }
+    value
+}
+
+/// Log a request or response body at trace level.
+fn trace_body(what: &str, body: &str) {
+    if log::log_enabled!(log::Level::Trace) {
+        match serde_json::from_str(body) {
+            Ok(value) => log::trace!("{} {}", what, redacted(value)),
+            Err(_) => log::trace!("{} {}", what, body),
+        }
+    }
+}
+
+fn parse_response<T: DeserializeOwned>(response: ureq::Response) -> Result<T> {
+    // Endpoints like DELETE answer with an empty body
+    let body = response.into_string()?;
+    trace_body("Response:", &body);
+    let body = if body.trim().is_empty() {
+        "null"
+    } else {
+        &body
+    };
+    let data: T = serde_json::from_str(body)?;
+    Ok(data)
+}
+
+#[derive(Serialize, Debug)]
+struct RestartForm {
+    /// `all` also copies the description and tags
+    copy: &'static str,
+}
+
+impl Client {
+    pub fn new(settings: Settings) -> Client {
+        Client {
+            settings,
+            agent: Arc::default(),
+            dry_run: false,
+            legacy: false,
+        }
+    }
+
+    /// Adapt requests to the server's version, as told by
+    /// [`Client::get_version`]. Without it the client speaks Kimai 2.
+    pub fn server_version(mut self, version: &Version) -> Result<Client> {
+        match version.major() {
+            Some(1) => self.legacy = true,
+            Some(major) if major >= 2 => self.legacy = false,
+            _ => anyhow::bail!(
+                "Kimai {} is not supported, only Kimai 1.x and 2.x are",
+                version.version
+            ),
+        }
+        Ok(self)
+    }
+
+    /// Print requests that would change data instead of sending them; they
+    /// fail with [`DryRun`].
+    pub fn dry_run(mut self, dry_run: bool) -> Client {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    /// The HTTP agent with the configured timeouts, certificates and proxy,
+    /// also for requests the client has no method for. Built on first use,
+    /// as reading `ca_cert` may fail.
+    pub fn agent(&self) -> Result<ureq::Agent> {
+        if let Some(agent) = self.agent.get() {
+            return Ok(agent.clone());
+        }
+        let mut builder = ureq::AgentBuilder::new()
+            .timeout_connect(std::time::Duration::from_secs(
+                self.settings.connect_timeout,
+            ))
+            .timeout_read(std::time::Duration::from_secs(self.settings.read_timeout));
+        if let Some(tls) = tls_config(&self.settings)? {
+            builder = builder.tls_config(tls);
+        }
+        if self.settings.insecure {
+            // Once per run, however many clients send requests
+            static WARNED: std::sync::Once = std::sync::Once::new();
+            WARNED.call_once(|| {
+                eprintln!(
+                    "WARNING: insecure = true, the certificate of {} is not checked and \
+                     the token can be intercepted",
+                    self.settings.endpoint
+                )
+            });
+        }
+        if let Some(proxy) = proxy(&self.settings)? {
+            builder = builder.proxy(proxy);
+        }
+        Ok(self.agent.get_or_init(|| builder.build()).clone())
+    }
+
+    fn send_request<B: Serialize>(
+        &self,
+        method: &str,
+        url: &str,
+        body: Option<&B>,
+    ) -> Result<ureq::Response> {
+        let settings = &self.settings;
+        if settings.token.is_empty() {
+            anyhow::bail!("No API token configured");
+        }
+        if settings.auth == Auth::Legacy && settings.username.is_empty() {
+            anyhow::bail!("No username configured, legacy authentication needs one");
+        }
+
+        let url = settings.endpoint.to_owned() + url;
+
+        if self.dry_run && method != "GET" {
+            println!("Would {} {}", method, url);
+            if let Some(data) = body {
+                println!("{}", serde_json::to_string_pretty(data)?);
+            }
+            return Err(anyhow::Error::new(DryRun));
+        }
+
+        if let Some(data) = body {
+            trace_body("Request:", &serde_json::to_string(data)?);
+        }
+
+        let agent = self.agent()?;
+        let attempts = settings.attempts.max(1);
+        let mut backoff = std::time::Duration::from_millis(500);
+
+        let mut attempt = 1;
+        let response = loop {
+            let request = agent
+                .request(method, &url)
+                .set("Accept", "application/json");
+            let request = match settings.auth {
+                Auth::Bearer => request.set("Authorization", &format!("Bearer {}", settings.token)),
+                Auth::Legacy => request
+                    .set("X-AUTH-USER", &settings.username)
+                    .set("X-AUTH-TOKEN", &settings.token),
+            };
+
+            let started = std::time::Instant::now();
+            let response = match body {
+                Some(data) => request.send_json(data),
+                None => request.call(),
+            };
+            let outcome = match &response {
+                Ok(response) => response.status().to_string(),
+                Err(ureq::Error::Status(code, _)) => code.to_string(),
+                Err(ureq::Error::Transport(transport)) => transport.to_string(),
+            };
+            log::debug!(
+                "{} {} {} ({} ms)",
+                method,
+                url,
+                outcome,
+                started.elapsed().as_millis()
+            );
+
+            match response {
+                Err(e) if attempt < attempts && is_retryable(method, &e) => {
+                    std::thread::sleep(backoff);
+                    backoff *= 2;
+                    attempt += 1;
+                }
+                response => break response,
+            }
+        };
+
+        let response = match response {
+            Ok(response) => Ok(response),
+            Err(ureq::Error::Status(code, response)) => {
+                /* the server returned an unexpected status
+                code (such as 400, 500 etc) */
+                let body = response.into_string().unwrap_or_default();
+                trace_body("Response:", &body);
+                Err(anyhow::Error::new(ApiError::from_response(code, &body)))
+            }
+            Err(ureq::Error::Transport(transport)) if tls_error(&transport).is_some() => {
+                let tls = tls_error(&transport).map(ToString::to_string);
+                anyhow::bail!(
+                    "Could not connect securely to {}: {}. If the server's certificate \
+                     is signed by a private CA, set `ca_cert` to the CA's PEM file",
+                    settings.endpoint,
+                    tls.unwrap_or_default()
+                )
+            }
+            Err(ureq::Error::Transport(transport))
+                if matches!(
+                    transport.kind(),
+                    ureq::ErrorKind::Dns | ureq::ErrorKind::ConnectionFailed | ureq::ErrorKind::Io
+                ) =>
+            {
+                Err(anyhow::Error::new(ConnectionError {
+                    attempts: attempt,
+                    transport,
+                }))
+            }
+            Err(e) => {
+                anyhow::bail!("Request failed: {:?}", e)
+            }
+        }?;
+        Ok(response)
+    }
+
+    fn request<T: DeserializeOwned, B: Serialize>(
+        &self,
+        method: &str,
+        url: &str,
+        body: Option<&B>,
+    ) -> Result<T> {
+        parse_response(self.send_request(method, url, body)?)
+    }
+
+    fn get<T: DeserializeOwned>(&self, url: &str) -> Result<T> {
+        self.request("GET", url, None::<&()>)
+    }
+
+    /// GET a paginated collection, following pages until everything (or at
+    /// most `limit` items, or `max_results`) has been fetched. Endpoints that
+    /// don't send `X-Total-Count` are treated as unpaginated.
+    fn get_paged<T: DeserializeOwned>(&self, url: &str, limit: Option<usize>) -> Result<Vec<T>> {
+        let max_results = self.settings.max_results;
+        let max = limit.unwrap_or(max_results).min(max_results);
+        let size = self.settings.page_size.min(max).max(1);
+        let separator = if url.contains('?') { '&' } else { '?' };
+
+        let mut items: Vec<T> = Vec::new();
+        for page in 1.. {
+            let page_url = format!("{}{}page={}&size={}", url, separator, page, size);
+            let response = self.send_request("GET", &page_url, None::<&()>)?;
+            let total: Option<usize> = response
+                .header("X-Total-Count")
+                .and_then(|total| total.trim().parse().ok());
+            let batch: Vec<T> = parse_response(response)?;
+            let batch_len = batch.len();
+            items.extend(batch);
+
+            let Some(total) = total else {
+                break;
+            };
+            if items.len() >= max {
+                if total > max && limit.is_none() {
+                    eprintln!(
+                        "Warning: only showing {} of {} results, raise `max_results` to see more",
+                        max, total
+                    );
+                }
+                break;
+            }
+            if items.len() >= total || batch_len < size {
+                break;
+            }
+        }
+
+        items.truncate(max);
+        Ok(items)
+    }
+
+    pub fn get_version(&self) -> Result<Version> {
+        self.get("/api/version")
+    }
+
+    /// The user the API token belongs to.
+    pub fn get_current_user(&self) -> Result<User> {
+        self.get("/api/users/me")
+    }
+
+    /// Users the token may see, which needs the permission to view users.
+    pub fn get_users(&self) -> Result<Vec<User>> {
+        self.get_paged("/api/users?visible=1", None)
+    }
+
+    pub fn get_customers(&self) -> Result<Vec<Customer>> {
+        self.get_paged("/api/customers?visible=1", None)
+    }
+
+    pub fn get_tags(&self) -> Result<Vec<Tag>> {
+        if self.legacy {
+            // Kimai 1 has no tags/find, and lists tags by name only
+            let names: Vec<String> = self.get("/api/tags")?;
+            return Ok(names.into_iter().map(|name| Tag { name }).collect());
+        }
+        self.get("/api/tags/find?visible=1")
+    }
+
+    /// Visible projects, of one customer or of all.
+    pub fn get_projects(&self, customer: Option<i32>) -> Result<Vec<Project>> {
+        let url = match customer {
+            Some(customer) => format!("/api/projects?visible=1&customer={}", customer),
+            None => "/api/projects?visible=1".to_string(),
+        };
+        self.get_paged(&url, None)
+    }
+
+    pub fn create_project(&self, form: &ProjectForm) -> Result<Project> {
+        self.request("POST", "/api/projects", Some(form))
+    }
+
+    pub fn create_activity(&self, form: &ActivityForm) -> Result<Activity> {
+        self.request("POST", "/api/activities", Some(form))
+    }
+
+    pub fn get_project(&self, id: i32) -> Result<ProjectDetails> {
+        self.get(&format!("/api/projects/{}", id))
+    }
+
+    /// Visible activities, of one project (including global activities) or
+    /// of all.
+    pub fn get_activities(&self, project: Option<i32>) -> Result<Vec<Activity>> {
+        let url = match project {
+            Some(project) => format!("/api/activities?visible=1&projects[]={}", project),
+            None => "/api/activities?visible=1".to_string(),
+        };
+        self.get_paged(&url, None)
+    }
+
+    /// Custom fields of timesheets. Needs the custom fields plugin.
+    pub fn get_meta_fields(&self) -> Result<Vec<MetaField>> {
+        self.get("/api/metafields?entity=timesheet")
+    }
+
+    pub fn get_invoices(&self, customer: Option<i32>) -> Result<Vec<Invoice>> {
+        let url = match customer {
+            Some(customer) => format!("/api/invoices?customers[]={}", customer),
+            None => "/api/invoices".to_string(),
+        };
+        self.get_paged(&url, None)
+    }
+
+    /// Needs the work contract plugin.
+    pub fn get_absences(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<Absence>> {
+        self.get_paged(&format!("/api/absences?begin={}&end={}", from, to), None)
+    }
+
+    /// Needs the work contract plugin.
+    pub fn get_public_holidays(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<PublicHoliday>> {
+        self.get_paged(
+            &format!("/api/public-holidays?begin={}&end={}", from, to),
+            None,
+        )
+    }
+
+    /// Installed plugins, which needs the permission to see them.
+    pub fn get_plugins(&self) -> Result<Vec<Plugin>> {
+        self.get("/api/plugins")
+    }
+
+    /// Expenses from `from` to `to`, of one project or of all. Needs the
+    /// expenses plugin.
+    pub fn get_expenses(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+        project: Option<i32>,
+    ) -> Result<Vec<Expense>> {
+        let mut url = format!("/api/expenses?begin={}T00:00:00&end={}T23:59:59", from, to);
+        if let Some(project) = project {
+            url.push_str(&format!("&projects[]={}", project));
+        }
+        self.get_paged(&url, None)
+    }
+
+    /// Needs the expenses plugin.
+    pub fn create_expense(&self, form: &ExpenseForm) -> Result<Expense> {
+        self.request("POST", "/api/expenses", Some(form))
+    }
+
+    /// Submit the week starting on `monday` for approval. Needs the approval
+    /// plugin.
+    pub fn submit_week(&self, user: i32, monday: NaiveDate) -> Result<()> {
+        let url = format!(
+            "/api/approval-bundle/add_to_approve?user={}&date={}",
+            user, monday
+        );
+        // The answer is a link to the approval in the web UI
+        self.request::<serde_json::Value, _>("POST", &url, None::<&()>)?;
+        Ok(())
+    }
+
+    /// Needs the approval plugin.
+    pub fn get_week_status(&self, user: i32, monday: NaiveDate) -> Result<WeekStatus> {
+        self.get(&format!(
+            "/api/approval-bundle/week_status?user={}&date={}",
+            user, monday
+        ))
+    }
+
+    pub fn insert_timesheet_entry(&self, form: &TimesheetEditForm) -> Result<Timesheet> {
+        self.request("POST", "/api/timesheets", Some(&self.compatible(form)))
+    }
+
+    /// `form` without the fields the server would reject as extra: Kimai 1
+    /// only learned about `billable` late, so it's left for the server to
+    /// decide.
+    fn compatible(&self, form: &TimesheetEditForm) -> TimesheetEditForm {
+        let mut form = form.clone();
+        if self.legacy {
+            form.billable = None;
+        }
+        form
+    }
+
+    pub fn get_timesheets(&self, query: &TimesheetQuery) -> Result<Vec<TimesheetExpanded>> {
+        let url = format!("/api/timesheets?{}", query.to_query_string());
+        self.get_paged(&url, query.limit)
+    }
+
+    pub fn get_timesheet(&self, id: i32) -> Result<Timesheet> {
+        self.get(&format!("/api/timesheets/{}", id))
+    }
+
+    pub fn update_timesheet(&self, id: i32, form: &TimesheetEditForm) -> Result<Timesheet> {
+        let url = format!("/api/timesheets/{}", id);
+        self.request("PATCH", &url, Some(&self.compatible(form)))
+    }
+
+    pub fn delete_timesheet(&self, id: i32) -> Result<()> {
+        let url = format!("/api/timesheets/{}", id);
+        self.request("DELETE", &url, None::<&()>)
+    }
+
+    pub fn get_active_timesheets(&self) -> Result<Vec<TimesheetExpanded>> {
+        if self.legacy {
+            // Older 1.x releases lack timesheets/active, but all can filter
+            return self.get_paged("/api/timesheets?active=1&full=true", None);
+        }
+        self.get("/api/timesheets/active")
+    }
+
+    /// Recently used project/activity/description combinations, newest first.
+    pub fn get_recent_timesheets(&self, size: usize) -> Result<Vec<TimesheetExpanded>> {
+        self.get(&format!("/api/timesheets/recent?size={}", size))
+    }
+
+    /// Start a new running timer copying an existing entry.
+    pub fn restart_timesheet(&self, id: i32) -> Result<Timesheet> {
+        let url = format!("/api/timesheets/{}/restart", id);
+        self.request("PATCH", &url, Some(&RestartForm { copy: "all" }))
+    }
+
+    pub fn stop_timesheet(&self, id: i32) -> Result<Timesheet> {
+        let url = format!("/api/timesheets/{}/stop", id);
+        self.request("PATCH", &url, None::<&()>)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn no_proxy_by_host_or_domain() {
+        let no_proxy = "localhost, .internal.example.com,10.0.0.1:8080,[::1]:80";
+        assert!(bypasses_proxy(no_proxy, "localhost"));
+        assert!(bypasses_proxy(no_proxy, "LOCALHOST"));
+        assert!(bypasses_proxy(no_proxy, "internal.example.com"));
+        assert!(bypasses_proxy(no_proxy, "kimai.Internal.example.com"));
+        assert!(bypasses_proxy(no_proxy, "10.0.0.1"));
+        assert!(bypasses_proxy(no_proxy, "::1"));
+        assert!(!bypasses_proxy(no_proxy, "example.com"));
+        assert!(!bypasses_proxy(no_proxy, "notinternal.example.com"));
+        assert!(!bypasses_proxy(no_proxy, "10.0.0.12"));
+
+        assert!(bypasses_proxy("::1", "::1"));
+        assert!(bypasses_proxy("*", "kimai.example.com"));
+        assert!(!bypasses_proxy(" , ", "kimai.example.com"));
+    }
+
+    #[test]
+    fn secrets_blanked_at_any_depth() {
+        let body = json!({
+            "username": "anna",
+            "apiToken": "abc",
+            "plainPassword": "secret",
+            "preferences": [{ "name": "timezone", "value": "Europe/Berlin" }],
+            "user": { "token": "def", "alias": "Anna" },
+        });
+        assert_eq!(
+            redacted(body),
+            json!({
+                "username": "anna",
+                "apiToken": "********",
+                "plainPassword": "********",
+                "preferences": [{ "name": "timezone", "value": "Europe/Berlin" }],
+                "user": { "token": "********", "alias": "Anna" },
+            })
+        );
+    }
+}
@@ -0,0 +1,214 @@
+//! Errors besides the usual `anyhow` ones, so callers can tell them apart.
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+/// Error payload Kimai sends for failed requests. Validation failures list
+/// messages per form field, possibly nested.
+#[derive(Debug, Deserialize)]
+struct ErrorBody {
+    message: Option<String>,
+    errors: Option<FormErrors>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct FormErrors {
+    #[serde(default)]
+    errors: Vec<String>,
+    #[serde(default)]
+    children: BTreeMap<String, FormErrors>,
+}
+
+impl FormErrors {
+    /// Collect `(field, message)` pairs; errors on the form itself have no field.
+    fn flatten(&self, path: &str, out: &mut Vec<(String, String)>) {
+        for error in &self.errors {
+            out.push((path.to_string(), error.clone()));
+        }
+        for (field, child) in &self.children {
+            let path = if path.is_empty() {
+                field.clone()
+            } else {
+                format!("{}.{}", path, field)
+            };
+            child.flatten(&path, out);
+        }
+    }
+}
+
+/// The server rejected a request.
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: u16,
+    pub message: String,
+    /// Validation messages as `(field, message)`.
+    pub fields: Vec<(String, String)>,
+}
+
+impl ApiError {
+    pub(crate) fn from_response(status: u16, body: &str) -> ApiError {
+        match serde_json::from_str::<ErrorBody>(body) {
+            Ok(decoded) => {
+                let mut fields = Vec::new();
+                if let Some(errors) = decoded.errors {
+                    errors.flatten("", &mut fields);
+                }
+                ApiError {
+                    status,
+                    message: decoded
+                        .message
+                        .unwrap_or_else(|| "Request failed".to_string()),
+                    fields,
+                }
+            }
+            // Not a Kimai error, e.g. an HTML page from a proxy
+            Err(_) => ApiError {
+                status,
+                message: body.trim().to_string(),
+                fields: Vec::new(),
+            },
+        }
+    }
+}
+
+impl Display for ApiError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "Server returned status code {}: {}",
+            self.status, self.message
+        )?;
+        for (field, message) in &self.fields {
+            if field.is_empty() {
+                write!(f, "\n  {}", message)?;
+            } else {
+                write!(f, "\n  {}: {}", field, message)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+/// The server could not be reached, e.g. because we are offline.
+#[derive(Debug)]
+pub struct ConnectionError {
+    pub(crate) attempts: u32,
+    pub(crate) transport: ureq::Transport,
+}
+
+impl Display for ConnectionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        if self.attempts > 1 {
+            write!(
+                f,
+                "Request failed after {} attempts: {}",
+                self.attempts, self.transport
+            )
+        } else {
+            write!(f, "Request failed: {}", self.transport)
+        }
+    }
+}
+
+impl std::error::Error for ConnectionError {}
+
+/// A request that would change data was skipped because of `--dry-run`.
+#[derive(Debug)]
+pub struct DryRun;
+
+impl Display for DryRun {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "Dry run, nothing was sent")
+    }
+}
+
+impl std::error::Error for DryRun {}
+
+pub fn is_dry_run(error: &anyhow::Error) -> bool {
+    error.downcast_ref::<DryRun>().is_some()
+}
+
+/// Whether `error` is the server saying the URL doesn't exist, e.g. because a
+/// plugin isn't installed.
+pub fn is_not_found(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<ApiError>()
+        .is_some_and(|e| e.status == 404)
+}
+
+/// Whether the server refused the API token.
+pub fn is_unauthorized(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<ApiError>()
+        .is_some_and(|e| e.status == 401 || e.status == 403)
+}
+
+/// Whether the server rejected what was sent, e.g. a form field.
+pub fn is_invalid(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<ApiError>()
+        .is_some_and(|e| e.status == 400 || e.status == 422)
+}
+
+/// Whether `error` means the server could not be reached at all.
+pub fn is_unreachable(error: &anyhow::Error) -> bool {
+    error.downcast_ref::<ConnectionError>().is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn form_errors_flattened_by_field() {
+        // As Kimai 2 answers a timesheet with an end before its start
+        let body = r#"{
+            "code": 400,
+            "message": "Validation Failed",
+            "errors": {
+                "errors": ["This form should not contain extra fields."],
+                "children": {
+                    "begin": {},
+                    "end": {"errors": ["End date must not be earlier then start date."]},
+                    "project": {"errors": ["This value should not be blank."]},
+                    "metaFields": {"children": {"0": {"children": {
+                        "value": {"errors": ["This value is not valid."]}
+                    }}}}
+                }
+            }
+        }"#;
+        let error = ApiError::from_response(400, body);
+        assert_eq!(error.message, "Validation Failed");
+        assert_eq!(
+            error.fields,
+            [
+                ("", "This form should not contain extra fields."),
+                ("end", "End date must not be earlier then start date."),
+                ("metaFields.0.value", "This value is not valid."),
+                ("project", "This value should not be blank."),
+            ]
+            .map(|(field, message)| (field.to_string(), message.to_string()))
+        );
+        assert_eq!(
+            error.to_string(),
+            "Server returned status code 400: Validation Failed\n  \
+             This form should not contain extra fields.\n  \
+             end: End date must not be earlier then start date.\n  \
+             metaFields.0.value: This value is not valid.\n  \
+             project: This value should not be blank."
+        );
+    }
+
+    #[test]
+    fn other_bodies_kept_as_message() {
+        let error = ApiError::from_response(400, r#"{"code": 400}"#);
+        assert_eq!(error.message, "Request failed");
+        assert!(error.fields.is_empty());
+
+        let error = ApiError::from_response(502, "<html>Bad Gateway</html>\n");
+        assert_eq!(error.message, "<html>Bad Gateway</html>");
+        assert!(error.fields.is_empty());
+    }
+}
@@ -0,0 +1,26 @@
+//! Client for the [Kimai](https://www.kimai.org) time tracking API, as used
+//! by the `kimai` command line tool.
+//!
+//! ```no_run
+//! use kimai_api::{Client, Settings};
+//!
+//! let client = Client::new(Settings {
+//!     endpoint: "https://kimai.example.com".to_string(),
+//!     token: "...".to_string(),
+//!     ..Settings::default()
+//! });
+//! for project in client.get_projects(None)? {
+//!     println!("{}", project);
+//! }
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+mod client;
+mod error;
+mod models;
+
+pub use client::{Auth, Client, Settings};
+pub use error::{
+    is_dry_run, is_invalid, is_not_found, is_unauthorized, is_unreachable, ApiError,
+    ConnectionError, DryRun,
+};
+pub use models::*;
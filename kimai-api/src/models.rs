@@ -0,0 +1,460 @@
+//! The records the Kimai API sends and accepts.
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Activity {
+    pub id: i32,
+    pub parent_title: Option<String>,
+    pub name: String,
+    /// Whether time on the activity is billed, by default.
+    #[serde(default)]
+    pub billable: Option<bool>,
+}
+
+impl Display for Activity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        if let Some(parent_title) = &self.parent_title {
+            write!(f, "{} | {}", parent_title, self.name)
+        } else {
+            write!(f, "{}", self.name)
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Project {
+    pub id: i32,
+    /// Name of the customer
+    pub parent_title: Option<String>,
+    pub name: String,
+    /// Whether time on the project is billed, by default.
+    #[serde(default)]
+    pub billable: Option<bool>,
+}
+
+impl Display for Project {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        if let Some(parent_title) = &self.parent_title {
+            write!(f, "{} | {}", parent_title, self.name)
+        } else {
+            write!(f, "{}", self.name)
+        }
+    }
+}
+
+/// A new project, for `POST /api/projects`.
+#[derive(Debug, Serialize)]
+pub struct ProjectForm {
+    pub name: String,
+    pub customer: i32,
+    pub visible: bool,
+}
+
+/// A new activity, for `POST /api/activities`.
+#[derive(Debug, Serialize)]
+pub struct ActivityForm {
+    pub name: String,
+    /// Without a project the activity is global, usable in every project.
+    pub project: Option<i32>,
+    pub visible: bool,
+}
+
+/// The budgets of a project, from `GET /api/projects/{id}`.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectDetails {
+    pub id: i32,
+    /// Money budget; zero when there is none.
+    #[serde(default)]
+    pub budget: f64,
+    /// Time budget in seconds; zero when there is none.
+    #[serde(default)]
+    pub time_budget: i64,
+    /// `month` when the budgets apply to each month rather than the whole
+    /// project.
+    pub budget_type: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Customer {
+    pub id: i32,
+    pub name: String,
+}
+
+impl Display for Customer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// An invoice created in Kimai, from `GET /api/invoices`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Invoice {
+    pub invoice_number: String,
+    pub customer: Option<Customer>,
+    #[serde(with = "kimai_datetime")]
+    pub created_at: DateTime<FixedOffset>,
+    #[serde(default)]
+    pub total: f64,
+    #[serde(default)]
+    pub currency: String,
+    /// `new`, `pending`, `paid` or `canceled`
+    pub status: String,
+}
+
+/// Time off recorded with Kimai's work contract plugin.
+#[derive(Debug, Deserialize)]
+pub struct Absence {
+    #[serde(deserialize_with = "kimai_date")]
+    pub date: NaiveDate,
+    /// E.g. `holiday` (vacation), `sickness` or `time_off`
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PublicHoliday {
+    #[serde(deserialize_with = "kimai_date")]
+    pub date: NaiveDate,
+}
+
+/// An installed plugin, from `GET /api/plugins`.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Plugin {
+    /// E.g. `ExpensesBundle`
+    pub name: String,
+    #[serde(default)]
+    pub version: Option<String>,
+}
+
+/// An expense recorded with Kimai's expenses plugin.
+#[derive(Debug, Deserialize)]
+pub struct Expense {
+    pub id: i32,
+    #[serde(with = "kimai_datetime")]
+    pub begin: DateTime<FixedOffset>,
+    pub project: i32,
+    #[serde(default)]
+    pub activity: Option<i32>,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub cost: f64,
+    /// Paid by the user, who gets it back.
+    #[serde(default)]
+    pub refundable: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ExpenseForm {
+    pub begin: DateTime<Utc>,
+    pub project: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub activity: Option<i32>,
+    /// An expense category of the plugin, by id.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<i32>,
+    pub description: String,
+    pub cost: f64,
+    pub refundable: bool,
+}
+
+/// Where a week is in the approval plugin's workflow.
+#[derive(Debug, Deserialize)]
+pub struct WeekStatus {
+    /// E.g. `not_submitted`, `submitted`, `approved` or `denied`
+    pub status: String,
+}
+
+/// A date, possibly given as the start of a Kimai date-time.
+fn kimai_date<'de, D>(deserializer: D) -> Result<NaiveDate, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    NaiveDate::parse_from_str(s.get(..10).unwrap_or(&s), "%Y-%m-%d")
+        .map_err(serde::de::Error::custom)
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Tag {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Version {
+    pub version: String,
+}
+
+impl Version {
+    /// The major version, e.g. 1 for Kimai 1.30.
+    pub fn major(&self) -> Option<u32> {
+        self.version.split('.').next()?.trim().parse().ok()
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct User {
+    #[serde(default)]
+    pub id: i32,
+    pub username: String,
+    pub alias: Option<String>,
+    /// From the user's preferences, e.g. `Europe/Berlin`.
+    #[serde(default)]
+    pub timezone: Option<String>,
+    /// From the user's preferences, e.g. `de`; the web UI's URLs start with it.
+    #[serde(default)]
+    pub language: Option<String>,
+}
+
+impl Display for User {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self.alias.as_deref().filter(|a| !a.is_empty()) {
+            Some(alias) => write!(f, "{} ({})", alias, self.username),
+            None => write!(f, "{}", self.username),
+        }
+    }
+}
+
+/// Kimai formats dates as `2024-05-01T09:00:00+0200`, which is not quite RFC 3339.
+mod kimai_datetime {
+    use chrono::{DateTime, FixedOffset};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    const FORMAT: &str = "%Y-%m-%dT%H:%M:%S%z";
+
+    pub fn serialize<S>(value: &DateTime<FixedOffset>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(&value.format(FORMAT))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<DateTime<FixedOffset>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        DateTime::parse_from_str(&s, FORMAT).map_err(serde::de::Error::custom)
+    }
+
+    pub mod option {
+        use super::*;
+
+        pub fn serialize<S>(
+            value: &Option<DateTime<FixedOffset>>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            match value {
+                Some(value) => super::serialize(value, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D>(
+            deserializer: D,
+        ) -> Result<Option<DateTime<FixedOffset>>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            match Option::<String>::deserialize(deserializer)? {
+                Some(s) => DateTime::parse_from_str(&s, FORMAT)
+                    .map(Some)
+                    .map_err(serde::de::Error::custom),
+                None => Ok(None),
+            }
+        }
+    }
+}
+
+/// A timesheet record with its project and activity expanded, as returned by
+/// `/api/timesheets/active` and `/api/timesheets?full=true`.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TimesheetExpanded {
+    pub id: i32,
+    #[serde(with = "kimai_datetime")]
+    pub begin: DateTime<FixedOffset>,
+    #[serde(default, with = "kimai_datetime::option")]
+    pub end: Option<DateTime<FixedOffset>>,
+    /// Duration in seconds; zero for running timers.
+    #[serde(default)]
+    pub duration: i64,
+    pub description: Option<String>,
+    pub project: Project,
+    pub activity: Activity,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// What the entry is billed at; only included for users allowed to see
+    /// rates.
+    #[serde(default)]
+    pub rate: Option<f64>,
+    #[serde(default, rename = "hourlyRate")]
+    pub hourly_rate: Option<f64>,
+    #[serde(default = "default_true")]
+    pub billable: bool,
+    /// Exported entries, e.g. to an invoice, can't be changed anymore.
+    #[serde(default)]
+    pub exported: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Timesheet {
+    pub id: i32,
+    #[serde(with = "kimai_datetime")]
+    pub begin: DateTime<FixedOffset>,
+    #[serde(default, with = "kimai_datetime::option")]
+    pub end: Option<DateTime<FixedOffset>>,
+    /// Duration in seconds, as the server counts it; none while running.
+    #[serde(default)]
+    pub duration: Option<i64>,
+    pub description: Option<String>,
+    pub project: i32,
+    pub activity: i32,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub exported: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct TimesheetEditForm {
+    pub begin: DateTime<Utc>,
+    pub project: i32,
+    pub activity: i32,
+    /// Leaving out the end starts a running timer.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Kimai expects tags as a single comma separated string.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_tags",
+        deserialize_with = "deserialize_tags"
+    )]
+    pub tags: Option<Vec<String>>,
+    /// Left out, Kimai decides from the customer, project and activity.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub billable: Option<bool>,
+    /// Values for custom fields, only the ones being set.
+    #[serde(default, rename = "metaFields", skip_serializing_if = "Vec::is_empty")]
+    pub meta_fields: Vec<MetaValue>,
+    /// Log for another user, which needs the permission to edit their
+    /// timesheets. Left out, the entry is the token's own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<i32>,
+}
+
+/// The value of a custom field on a record.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct MetaValue {
+    pub name: String,
+    #[serde(default)]
+    pub value: Option<String>,
+}
+
+/// A custom field timesheets can have, defined with Kimai's custom fields
+/// plugin.
+#[derive(Deserialize, Serialize, Debug)]
+pub struct MetaField {
+    pub name: String,
+    pub label: Option<String>,
+    /// E.g. `string`, `integer` or `boolean`
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+    /// The value new entries get.
+    #[serde(default, alias = "default")]
+    pub value: Option<serde_json::Value>,
+}
+
+fn serialize_tags<S: serde::Serializer>(
+    tags: &Option<Vec<String>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match tags {
+        Some(tags) => serializer.serialize_str(&tags.join(",")),
+        None => serializer.serialize_none(),
+    }
+}
+
+fn deserialize_tags<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Vec<String>>, D::Error> {
+    Ok(Option::<String>::deserialize(deserializer)?.map(|tags| {
+        tags.split(',')
+            .filter(|tag| !tag.is_empty())
+            .map(String::from)
+            .collect()
+    }))
+}
+
+/// Filters for `GET /api/timesheets`. Dates are in the user's timezone.
+#[derive(Debug, Default)]
+pub struct TimesheetQuery {
+    pub begin: Option<NaiveDateTime>,
+    pub end: Option<NaiveDateTime>,
+    pub project: Option<i32>,
+    pub activity: Option<i32>,
+    pub customer: Option<i32>,
+    /// Only billable (`Some(true)`) or non-billable entries.
+    pub billable: Option<bool>,
+    /// Only entries that were (`Some(true)`) or weren't exported yet, e.g.
+    /// to an invoice.
+    pub exported: Option<bool>,
+    /// Include the entries of other users, as far as the token may see them.
+    pub all_users: bool,
+    /// Only the entries of this user instead of the token's own.
+    pub user: Option<i32>,
+    /// Maximum number of entries to fetch, across all pages.
+    pub limit: Option<usize>,
+}
+
+impl TimesheetQuery {
+    pub(crate) fn to_query_string(&self) -> String {
+        let mut params = vec!["full=true".to_string()];
+        if let Some(begin) = self.begin {
+            params.push(format!("begin={}", begin.format(HTML5_FORMAT)));
+        }
+        if let Some(end) = self.end {
+            params.push(format!("end={}", end.format(HTML5_FORMAT)));
+        }
+        if let Some(project) = self.project {
+            params.push(format!("projects[]={}", project));
+        }
+        if let Some(activity) = self.activity {
+            params.push(format!("activities[]={}", activity));
+        }
+        if let Some(customer) = self.customer {
+            params.push(format!("customers[]={}", customer));
+        }
+        if let Some(billable) = self.billable {
+            params.push(format!("billable={}", u8::from(billable)));
+        }
+        if let Some(exported) = self.exported {
+            params.push(format!("exported={}", u8::from(exported)));
+        }
+        if let Some(user) = self.user {
+            params.push(format!("user={}", user));
+        } else if self.all_users {
+            params.push("user=all".to_string());
+        }
+        params.join("&")
+    }
+}
+
+/// Format for local date-time query parameters.
+const HTML5_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
@@ -0,0 +1,335 @@
+//! The Kimai API as the commands use it: the `kimai-api` client configured
+//! from the loaded config, with lists that rarely change kept in the cache.
+use anyhow::Result;
+use chrono::NaiveDate;
+use std::io::IsTerminal;
+use std::sync::Mutex;
+
+pub use kimai_api::{
+    is_dry_run, is_invalid, is_not_found, is_unauthorized, is_unreachable, Absence, Activity,
+    ActivityForm, ApiError, Auth, Client, Customer, Expense, ExpenseForm, Invoice, MetaField,
+    MetaValue, Project, ProjectDetails, ProjectForm, PublicHoliday, Tag, Timesheet,
+    TimesheetEditForm, TimesheetExpanded, TimesheetQuery, User, Version, WeekStatus,
+};
+
+use crate::{cache, config, descriptions, hooks, rounding};
+
+/// The client made for the last settings used, with the settings as JSON.
+static SHARED: Mutex<Option<(String, Client)>> = Mutex::new(None);
+
+/// A client for `server`, sharing its connections with every other one for
+/// the same settings. `kimai config init` tries several, hence the check.
+fn shared(server: &kimai_api::Settings) -> Client {
+    let key = serde_json::to_string(server).unwrap_or_default();
+    let mut shared = SHARED.lock().unwrap();
+    match shared.as_ref() {
+        Some((settings, client)) if *settings == key => client.clone(),
+        _ => {
+            let client = Client::new(server.clone());
+            *shared = Some((key, client.clone()));
+            client
+        }
+    }
+}
+
+/// A client for the configured server. Fails without a token, with a hint
+/// on how to set one up.
+pub fn client(config: &config::Config) -> Result<Client> {
+    let mut server = config.server.clone();
+    server.token = config::token(config)?;
+    if server.token.is_empty() {
+        anyhow::bail!(
+            "No API token configured, set `token` or `token_cmd`, or run `kimai auth login`"
+        );
+    }
+    let shared = shared(&server);
+    let client = shared.clone().dry_run(config.dry_run);
+    match server_version(config, &shared) {
+        Some(version) => client.server_version(&version),
+        None => Ok(client),
+    }
+}
+
+/// The server's version, asked once per cache TTL. Not knowing it is no
+/// reason to fail, as the requests that follow report any real problem.
+fn server_version(config: &config::Config, client: &Client) -> Option<Version> {
+    cache::get_or_fetch(config, "version", || client.get_version()).ok()
+}
+
+/// The client's HTTP agent, for requests without a token.
+pub fn agent(config: &config::Config) -> Result<ureq::Agent> {
+    shared(&config.server).agent()
+}
+
+/// The running timers fetched by [`prefetch`], until they are used.
+static PREFETCHED_ACTIVE: Mutex<Option<Vec<TimesheetExpanded>>> = Mutex::new(None);
+
+/// Fill the cache with the lists the pickers may need, and fetch the running
+/// timers a new one has to deal with, all at once rather than one request
+/// after the other as the prompts get to them. Failures are left for the
+/// lookups that use the lists to report.
+pub fn prefetch(config: &config::Config) {
+    std::thread::scope(|scope| {
+        if config.select_customer {
+            scope.spawn(|| get_customers(config));
+        }
+        scope.spawn(|| get_projects(config));
+        if std::io::stdin().is_terminal() {
+            scope.spawn(|| get_tags(config));
+        }
+        scope.spawn(|| {
+            if let Ok(active) = get_active_timesheets(config) {
+                *PREFETCHED_ACTIVE.lock().unwrap() = Some(active);
+            }
+        });
+    });
+}
+
+/// The running timers as [`prefetch`] found them, or else fetched now. The
+/// prefetched ones are given out once, so anything asking after a change gets
+/// the server's current state.
+pub fn take_active_timesheets(config: &config::Config) -> Result<Vec<TimesheetExpanded>> {
+    match PREFETCHED_ACTIVE.lock().unwrap().take() {
+        Some(active) => Ok(active),
+        None => get_active_timesheets(config),
+    }
+}
+
+pub fn get_version(config: &config::Config) -> Result<Version> {
+    client(config)?.get_version()
+}
+
+/// The user the API token belongs to.
+pub fn get_current_user(config: &config::Config) -> Result<User> {
+    client(config)?.get_current_user()
+}
+
+/// Users the token may act for, e.g. as a team lead.
+pub fn get_users(config: &config::Config) -> Result<Vec<User>> {
+    cache::get_or_fetch(config, "users", || client(config)?.get_users()).map_err(|e| {
+        if is_unauthorized(&e) {
+            e.context("Kimai refused to list users, the token needs the permission to view them")
+        } else {
+            e
+        }
+    })
+}
+
+pub fn get_customers(config: &config::Config) -> Result<Vec<Customer>> {
+    cache::get_or_fetch(config, "customers", || client(config)?.get_customers())
+}
+
+pub fn get_tags(config: &config::Config) -> Result<Vec<Tag>> {
+    cache::get_or_fetch(config, "tags", || client(config)?.get_tags())
+}
+
+pub fn get_projects(config: &config::Config) -> Result<Vec<Project>> {
+    cache::get_or_fetch(config, "projects", || client(config)?.get_projects(None))
+}
+
+pub fn get_projects_by_customer(config: &config::Config, customer_id: i32) -> Result<Vec<Project>> {
+    let key = format!("projects-customer-{}", customer_id);
+    cache::get_or_fetch(config, &key, || {
+        client(config)?.get_projects(Some(customer_id))
+    })
+}
+
+/// Create a project; cached project lists are dropped to show it.
+pub fn create_project(config: &config::Config, form: &ProjectForm) -> Result<Project> {
+    let project = client(config)?.create_project(form)?;
+    cache::forget(config, "projects");
+    Ok(project)
+}
+
+/// Create an activity; cached activity lists are dropped to show it.
+pub fn create_activity(config: &config::Config, form: &ActivityForm) -> Result<Activity> {
+    let activity = client(config)?.create_activity(form)?;
+    cache::forget(config, "activities");
+    Ok(activity)
+}
+
+pub fn get_project(config: &config::Config, id: i32) -> Result<ProjectDetails> {
+    let key = format!("project-{}", id);
+    cache::get_or_fetch(config, &key, || client(config)?.get_project(id))
+}
+
+pub fn get_activities(config: &config::Config) -> Result<Vec<Activity>> {
+    cache::get_or_fetch(config, "activities", || {
+        client(config)?.get_activities(None)
+    })
+}
+
+pub fn get_activities_by_project(
+    config: &config::Config,
+    project_id: i32,
+) -> Result<Vec<Activity>> {
+    let key = format!("activities-project-{}", project_id);
+    cache::get_or_fetch(config, &key, || {
+        client(config)?.get_activities(Some(project_id))
+    })
+}
+
+/// The custom fields timesheets have, none without the custom fields plugin.
+pub fn get_meta_fields(config: &config::Config) -> Result<Vec<MetaField>> {
+    cache::get_or_fetch(config, "meta-fields", || {
+        match client(config)?.get_meta_fields() {
+            Err(e) if is_not_found(&e) => Ok(Vec::new()),
+            result => result,
+        }
+    })
+}
+
+pub fn get_invoices(config: &config::Config, customer: Option<i32>) -> Result<Vec<Invoice>> {
+    client(config)?.get_invoices(customer)
+}
+
+pub fn get_absences(
+    config: &config::Config,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<Absence>> {
+    client(config)?.get_absences(from, to)
+}
+
+pub fn get_public_holidays(
+    config: &config::Config,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<PublicHoliday>> {
+    client(config)?.get_public_holidays(from, to)
+}
+
+/// Whether the server has the plugin `name`, e.g. `ExpensesBundle`. Tokens
+/// that may not list plugins find out from the plugin's endpoints instead.
+/// Not cached, so a plugin shows up as soon as it's installed.
+pub fn has_plugin(config: &config::Config, name: &str) -> Result<bool> {
+    match client(config)?.get_plugins() {
+        Ok(plugins) => Ok(plugins.iter().any(|plugin| plugin.name == name)),
+        Err(e)
+            if e.downcast_ref::<ApiError>()
+                .is_some_and(|e| e.status == 403) =>
+        {
+            Ok(true)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+pub fn get_expenses(
+    config: &config::Config,
+    from: NaiveDate,
+    to: NaiveDate,
+    project: Option<i32>,
+) -> Result<Vec<Expense>> {
+    client(config)?.get_expenses(from, to, project)
+}
+
+pub fn create_expense(config: &config::Config, form: &ExpenseForm) -> Result<Expense> {
+    client(config)?.create_expense(form)
+}
+
+pub fn submit_week(config: &config::Config, user: i32, monday: NaiveDate) -> Result<()> {
+    client(config)?.submit_week(user, monday)
+}
+
+pub fn get_week_status(
+    config: &config::Config,
+    user: i32,
+    monday: NaiveDate,
+) -> Result<WeekStatus> {
+    client(config)?.get_week_status(user, monday)
+}
+
+/// Create an entry, if its description matches the project's pattern;
+/// cached descriptions are dropped to offer its own, and the hooks run.
+pub fn insert_timesheet_entry(
+    config: &config::Config,
+    form: &TimesheetEditForm,
+) -> Result<Timesheet> {
+    descriptions::check(config, form.project, form.description.as_deref())?;
+    let timesheet = client(config)?.insert_timesheet_entry(form)?;
+    let differences = rounding::differences(form, &timesheet);
+    if !differences.is_empty() {
+        let changes: Vec<String> = differences
+            .into_iter()
+            .map(|(what, stored, sent)| format!("{} {} instead of {}", what, stored, sent))
+            .collect();
+        eprintln!(
+            "Kimai stored entry #{} with {}",
+            timesheet.id,
+            changes.join(", ")
+        );
+    }
+    cache::forget(config, "descriptions");
+    hooks::entry_created(config, &timesheet);
+    Ok(timesheet)
+}
+
+/// Descriptions of the recent entries on `project` and `activity`, the most
+/// recent first and each only once.
+pub fn get_descriptions(
+    config: &config::Config,
+    project: i32,
+    activity: i32,
+) -> Result<Vec<String>> {
+    let key = format!("descriptions-{}-{}", project, activity);
+    cache::get_or_fetch(config, &key, || {
+        let entries = client(config)?.get_timesheets(&TimesheetQuery {
+            project: Some(project),
+            activity: Some(activity),
+            limit: Some(100),
+            ..Default::default()
+        })?;
+        let mut descriptions: Vec<String> = Vec::new();
+        for description in entries.into_iter().filter_map(|e| e.description) {
+            if !description.is_empty() && !descriptions.contains(&description) {
+                descriptions.push(description);
+            }
+        }
+        Ok(descriptions)
+    })
+}
+
+pub fn get_timesheets(
+    config: &config::Config,
+    query: &TimesheetQuery,
+) -> Result<Vec<TimesheetExpanded>> {
+    client(config)?.get_timesheets(query)
+}
+
+pub fn get_timesheet(config: &config::Config, id: i32) -> Result<Timesheet> {
+    client(config)?.get_timesheet(id)
+}
+
+pub fn update_timesheet(
+    config: &config::Config,
+    id: i32,
+    form: &TimesheetEditForm,
+) -> Result<Timesheet> {
+    client(config)?.update_timesheet(id, form)
+}
+
+pub fn delete_timesheet(config: &config::Config, id: i32) -> Result<()> {
+    client(config)?.delete_timesheet(id)
+}
+
+pub fn get_active_timesheets(config: &config::Config) -> Result<Vec<TimesheetExpanded>> {
+    client(config)?.get_active_timesheets()
+}
+
+/// Recently used project/activity/description combinations, newest first.
+pub fn get_recent_timesheets(
+    config: &config::Config,
+    size: usize,
+) -> Result<Vec<TimesheetExpanded>> {
+    client(config)?.get_recent_timesheets(size)
+}
+
+/// Start a new running timer copying an existing entry.
+pub fn restart_timesheet(config: &config::Config, id: i32) -> Result<Timesheet> {
+    client(config)?.restart_timesheet(id)
+}
+
+pub fn stop_timesheet(config: &config::Config, id: i32) -> Result<Timesheet> {
+    client(config)?.stop_timesheet(id)
+}
@@ -0,0 +1,149 @@
+use anyhow::Result;
+use chrono::{Duration, NaiveTime};
+use inquire::Confirm;
+use std::fmt::{Display, Formatter};
+use std::io::IsTerminal;
+
+use crate::api::{self, Project, TimesheetQuery};
+use crate::{clock, config, format, report};
+
+/// How much of a project's budgets has been used.
+#[derive(Debug)]
+pub struct Budget {
+    /// Money used and available.
+    pub money: Option<(f64, f64)>,
+    /// Time used and available.
+    pub time: Option<(Duration, Duration)>,
+    /// Whether the budgets are for the current month only.
+    pub monthly: bool,
+}
+
+impl Budget {
+    pub fn is_exceeded(&self) -> bool {
+        self.money.is_some_and(|(used, budget)| used >= budget)
+            || self.time.is_some_and(|(used, budget)| used >= budget)
+    }
+}
+
+fn percent(used: f64, budget: f64) -> f64 {
+    used / budget * 100.0
+}
+
+impl Display for Budget {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let mut parts = Vec::new();
+        if let Some((used, budget)) = self.money {
+            parts.push(if used > budget {
+                format!(
+                    "{} over the budget of {}",
+                    format::money(used - budget),
+                    format::money(budget)
+                )
+            } else {
+                format!(
+                    "{} of {} budget left ({:.0}% used)",
+                    format::money(budget - used),
+                    format::money(budget),
+                    percent(used, budget)
+                )
+            });
+        }
+        if let Some((used, budget)) = self.time {
+            parts.push(if used > budget {
+                format!(
+                    "{} over the time budget of {} hours",
+                    format::duration(used - budget),
+                    format::duration(budget)
+                )
+            } else {
+                format!(
+                    "{} of {} hours left ({:.0}% used)",
+                    format::duration(budget - used),
+                    format::duration(budget),
+                    percent(used.num_seconds() as f64, budget.num_seconds() as f64)
+                )
+            });
+        }
+        write!(f, "{}", parts.join(", "))?;
+        if self.monthly {
+            write!(f, " this month")?;
+        }
+        Ok(())
+    }
+}
+
+/// The budgets of a project and how much of them is used, or `None` if the
+/// project has no budget.
+pub fn fetch(config: &config::Config, project: i32) -> Result<Option<Budget>> {
+    let details = api::get_project(config, project)?;
+    if details.budget <= 0.0 && details.time_budget <= 0 {
+        return Ok(None);
+    }
+
+    let monthly = details.budget_type.as_deref() == Some("month");
+    let (begin, end) = if monthly {
+        let (from, to) = report::month(clock::today());
+        (
+            Some(from.and_time(NaiveTime::MIN)),
+            Some(to.and_hms_opt(23, 59, 59).unwrap()),
+        )
+    } else {
+        (None, None)
+    };
+    let entries = api::get_timesheets(
+        config,
+        &TimesheetQuery {
+            begin,
+            end,
+            project: Some(project),
+            all_users: true,
+            ..Default::default()
+        },
+    )?;
+
+    let money = (details.budget > 0.0)
+        .then(|| (entries.iter().filter_map(|e| e.rate).sum(), details.budget));
+    let time = (details.time_budget > 0).then(|| {
+        (
+            entries.iter().map(report::entry_duration).sum(),
+            Duration::seconds(details.time_budget),
+        )
+    });
+    Ok(Some(Budget {
+        money,
+        time,
+        monthly,
+    }))
+}
+
+/// Show the remaining budget of `project` and make the user confirm logging
+/// time once it is used up. Any trouble determining the budget is reported
+/// but doesn't keep anyone from logging.
+pub fn check(config: &config::Config, project: &Project) -> Result<()> {
+    if !config.show_budget {
+        return Ok(());
+    }
+    let budget = match fetch(config, project.id) {
+        Ok(Some(budget)) => budget,
+        Ok(None) => return Ok(()),
+        // The entry is queued in this case, reporting it is enough
+        Err(e) if api::is_unreachable(&e) => return Ok(()),
+        Err(e) => {
+            eprintln!("Could not determine the budget of {}: {}", project, e);
+            return Ok(());
+        }
+    };
+
+    format::note!("{}: {}", project, budget);
+    if budget.is_exceeded() {
+        eprintln!("Warning: {} is over budget", project);
+        if std::io::stdin().is_terminal()
+            && !Confirm::new("Log time anyway?")
+                .with_default(false)
+                .prompt()?
+        {
+            anyhow::bail!("Not logging time on {}", project);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::{api, config};
+
+#[derive(Deserialize, Serialize)]
+struct Entry<T> {
+    fetched_at: DateTime<Utc>,
+    data: T,
+}
+
+/// Values already fetched by this process, so a session logging several
+/// entries doesn't ask the server for the same lists again.
+static FETCHED: Mutex<BTreeMap<String, serde_json::Value>> = Mutex::new(BTreeMap::new());
+
+/// Directory for cached data of the configured Kimai instance.
+pub fn dir(config: &config::Config) -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("kimai").join(config.instance_id()))
+}
+
+fn path(config: &config::Config, key: &str) -> Option<PathBuf> {
+    dir(config).map(|dir| dir.join(format!("{}.json", key)))
+}
+
+/// Read `key` from the cache, regardless of its age.
+pub fn read<T: DeserializeOwned>(config: &config::Config, key: &str) -> Option<(T, DateTime<Utc>)> {
+    let contents = std::fs::read_to_string(path(config, key)?).ok()?;
+    let entry: Entry<T> = serde_json::from_str(&contents).ok()?;
+    Some((entry.data, entry.fetched_at))
+}
+
+/// Store `data` under `key`. The cache is only an optimization, so failing
+/// to write it is not an error.
+pub fn write<T: Serialize>(config: &config::Config, key: &str, data: &T) {
+    let Some(path) = path(config, key) else {
+        return;
+    };
+    let entry = Entry {
+        fetched_at: Utc::now(),
+        data,
+    };
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Ok(contents) = serde_json::to_string(&entry) {
+        let _ = std::fs::write(path, contents);
+    }
+}
+
+/// Drop every cached value whose key starts with `prefix`, e.g. all
+/// project lists after creating a project.
+pub fn forget(config: &config::Config, prefix: &str) {
+    FETCHED
+        .lock()
+        .unwrap()
+        .retain(|key, _| !key.starts_with(prefix));
+    let Some(entries) = dir(config).and_then(|dir| std::fs::read_dir(dir).ok()) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if entry.file_name().to_string_lossy().starts_with(prefix) {
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+}
+
+/// Return the cached value for `key` if it is younger than the configured
+/// TTL, otherwise call `fetch` and cache its result. When the server can't be
+/// reached, stale data is better than nothing.
+pub fn get_or_fetch<T, F>(config: &config::Config, key: &str, fetch: F) -> Result<T>
+where
+    T: Serialize + DeserializeOwned,
+    F: FnOnce() -> Result<T>,
+{
+    get_or_fetch_within(config, key, config.cache_ttl, fetch)
+}
+
+/// [`get_or_fetch`] for data that changes more often than the configured TTL
+/// allows for, taking the cached value only if it is younger than `ttl`
+/// seconds as well.
+pub fn get_or_fetch_within<T, F>(
+    config: &config::Config,
+    key: &str,
+    ttl: u64,
+    fetch: F,
+) -> Result<T>
+where
+    T: Serialize + DeserializeOwned,
+    F: FnOnce() -> Result<T>,
+{
+    let ttl = ttl.min(config.cache_ttl);
+    if let Some(value) = FETCHED.lock().unwrap().get(key) {
+        if let Ok(data) = serde_json::from_value(value.clone()) {
+            return Ok(data);
+        }
+    }
+
+    if ttl > 0 && !config.refresh {
+        if let Some((data, fetched_at)) = read(config, key) {
+            let age = Utc::now() - fetched_at;
+            if age.num_seconds() >= 0 && (age.num_seconds() as u64) < ttl {
+                return Ok(data);
+            }
+        }
+    }
+
+    let data = match fetch() {
+        Ok(data) => data,
+        Err(e) if api::is_unreachable(&e) => match read(config, key) {
+            Some((data, _)) => return Ok(data),
+            None => return Err(e),
+        },
+        Err(e) => return Err(e),
+    };
+    if config.cache_ttl > 0 {
+        write(config, key, &data);
+    }
+    if let Ok(value) = serde_json::to_value(&data) {
+        FETCHED.lock().unwrap().insert(key.to_string(), value);
+    }
+    Ok(data)
+}
@@ -0,0 +1,110 @@
+//! Monthly caps on the hours logged for a customer or project, e.g. when a
+//! contract covers a fixed number of hours each month.
+use anyhow::Result;
+use chrono::Duration;
+use inquire::Confirm;
+use std::io::IsTerminal;
+
+use crate::api::{self, Project, TimesheetEditForm, TimesheetExpanded};
+use crate::{clock, config, format, report};
+
+/// A cap that applies to an entry.
+struct Cap {
+    /// The customer's or project's name.
+    name: String,
+    hours: f64,
+    /// Whether an entry counts towards the cap.
+    counts: Box<dyn Fn(&TimesheetExpanded) -> bool>,
+}
+
+/// The caps configured for entries on `project`.
+fn caps(config: &config::Config, project: &Project) -> Result<Vec<Cap>> {
+    let mut caps = Vec::new();
+    if let Some(hours) = config.project_defaults(project).and_then(|p| p.monthly_cap) {
+        let id = project.id;
+        caps.push(Cap {
+            name: project.to_string(),
+            hours,
+            counts: Box::new(move |e| e.project.id == id),
+        });
+    }
+    let customers_capped = config.customers.values().any(|c| c.monthly_cap.is_some());
+    if let (true, Some(name)) = (customers_capped, &project.parent_title) {
+        let customer = api::get_customers(config)?
+            .into_iter()
+            .find(|c| &c.name == name);
+        let hours = customer
+            .as_ref()
+            .and_then(|c| config.customer_settings(c))
+            .and_then(|c| c.monthly_cap);
+        if let Some(hours) = hours {
+            let name = name.clone();
+            caps.push(Cap {
+                name: name.clone(),
+                hours,
+                counts: Box::new(move |e| e.project.parent_title.as_ref() == Some(&name)),
+            });
+        }
+    }
+    Ok(caps)
+}
+
+/// Warn if `form` makes its customer or project go over a monthly cap, and
+/// unless `force` is given, ask whether to log it anyway.
+pub fn check(config: &config::Config, form: &TimesheetEditForm, force: bool) -> Result<()> {
+    let capped = config.projects.values().any(|p| p.monthly_cap.is_some())
+        || config.customers.values().any(|c| c.monthly_cap.is_some());
+    // Running timers have no length to count yet
+    let (true, Some(end)) = (capped, form.end) else {
+        return Ok(());
+    };
+    let Some(project) = api::get_projects(config)?
+        .into_iter()
+        .find(|p| p.id == form.project)
+    else {
+        return Ok(());
+    };
+    let caps = caps(config, &project)?;
+    if caps.is_empty() {
+        return Ok(());
+    }
+
+    let date = form.begin.with_timezone(&clock::zone()).date_naive();
+    let (from, to) = report::month(date);
+    let entries = report::fetch_for(config, from, to, form.user)?;
+    for cap in caps {
+        let logged: Duration = entries
+            .iter()
+            .filter(|e| (cap.counts)(e))
+            .map(report::entry_duration)
+            .sum();
+        let total = logged + (end - form.begin);
+        let limit = Duration::minutes((cap.hours * 60.0) as i64);
+        if total <= limit {
+            continue;
+        }
+        eprintln!(
+            "Warning: this brings {} to {} in {}, over the monthly cap of {}",
+            cap.name,
+            format::duration(total),
+            date.format("%B %Y"),
+            format::duration(limit)
+        );
+        if force {
+            continue;
+        }
+        if !std::io::stdin().is_terminal() {
+            anyhow::bail!(
+                "Not logging over the cap of {}, pass --force to log it anyway",
+                cap.name
+            );
+        }
+        if !Confirm::new("Log it anyway?")
+            .with_default(false)
+            .prompt()?
+        {
+            anyhow::bail!("Nothing was logged");
+        }
+    }
+    Ok(())
+}
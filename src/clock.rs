@@ -0,0 +1,165 @@
+//! The timezone times are entered and shown in. Kimai interprets times in each
+//! user's own timezone, which need not be the one of the machine running the
+//! CLI, so this is `timezone` from the config, or else the timezone set in the
+//! user's Kimai profile, or else the system timezone.
+use anyhow::Result;
+use chrono::{
+    DateTime, Duration, FixedOffset, Local, LocalResult, NaiveDate, NaiveDateTime, NaiveTime,
+    Offset, TimeZone, Utc, Weekday,
+};
+use inquire::{Confirm, Select};
+use std::io::IsTerminal;
+use std::sync::OnceLock;
+
+use crate::{api, cache, config, format};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Zone {
+    System,
+    Named(chrono_tz::Tz),
+}
+
+/// An offset that remembers its zone, so times stay in the zone through
+/// arithmetic, even across a change of the clocks.
+#[derive(Clone, Copy, Debug)]
+pub struct ZoneOffset {
+    zone: Zone,
+    offset: FixedOffset,
+}
+
+impl Offset for ZoneOffset {
+    fn fix(&self) -> FixedOffset {
+        self.offset
+    }
+}
+
+impl std::fmt::Display for ZoneOffset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.offset.fmt(f)
+    }
+}
+
+impl TimeZone for Zone {
+    type Offset = ZoneOffset;
+
+    fn from_offset(offset: &ZoneOffset) -> Zone {
+        offset.zone
+    }
+
+    fn offset_from_local_date(&self, local: &NaiveDate) -> LocalResult<ZoneOffset> {
+        self.offset_from_local_datetime(&local.and_time(NaiveTime::MIN))
+    }
+
+    fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> LocalResult<ZoneOffset> {
+        let offset = match self {
+            Zone::System => Local.offset_from_local_datetime(local),
+            Zone::Named(tz) => tz.offset_from_local_datetime(local).map(|o| o.fix()),
+        };
+        offset.map(|offset| ZoneOffset {
+            zone: *self,
+            offset,
+        })
+    }
+
+    fn offset_from_utc_date(&self, utc: &NaiveDate) -> ZoneOffset {
+        self.offset_from_utc_datetime(&utc.and_time(NaiveTime::MIN))
+    }
+
+    fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> ZoneOffset {
+        let offset = match self {
+            Zone::System => Local.offset_from_utc_datetime(utc),
+            Zone::Named(tz) => tz.offset_from_utc_datetime(utc).fix(),
+        };
+        ZoneOffset {
+            zone: *self,
+            offset,
+        }
+    }
+}
+
+static ZONE: OnceLock<Zone> = OnceLock::new();
+static WEEK_START: OnceLock<Weekday> = OnceLock::new();
+
+fn parse_zone(name: &str) -> Option<Zone> {
+    name.parse().ok().map(Zone::Named)
+}
+
+/// Settle on the zone for this run. The profile's timezone is cached like
+/// other data from the server; if it can't be had, the system timezone is the
+/// best guess.
+pub fn init(config: &config::Config) -> Result<()> {
+    let zone = match &config.timezone {
+        Some(name) => parse_zone(name).ok_or_else(|| {
+            anyhow::anyhow!("Unknown timezone '{}', expected e.g. Europe/Berlin", name)
+        })?,
+        None => cache::get_or_fetch(config, "me", || api::get_current_user(config))
+            .ok()
+            .and_then(|user| user.timezone)
+            .and_then(|name| parse_zone(&name))
+            .unwrap_or(Zone::System),
+    };
+    let _ = ZONE.set(zone);
+    let _ = WEEK_START.set(config.week_start);
+    Ok(())
+}
+
+/// The configured first day of the week.
+pub fn week_start() -> Weekday {
+    WEEK_START.get().copied().unwrap_or(Weekday::Mon)
+}
+
+pub fn zone() -> Zone {
+    ZONE.get().copied().unwrap_or(Zone::System)
+}
+
+pub fn now() -> DateTime<Zone> {
+    Utc::now().with_timezone(&zone())
+}
+
+pub fn today() -> NaiveDate {
+    now().date_naive()
+}
+
+/// The point in time a wall clock time stands for. Around a change of the
+/// clocks a time can happen twice, or not at all; then the user gets to
+/// choose, and without a terminal to ask on it's an error.
+pub fn resolve(time: NaiveDateTime) -> Result<DateTime<Zone>> {
+    let label = format!(
+        "{} {}",
+        format::date(time.date()),
+        format::time(time.time())
+    );
+    match zone().from_local_datetime(&time) {
+        LocalResult::Single(resolved) => Ok(resolved),
+        LocalResult::Ambiguous(first, second) => {
+            if !std::io::stdin().is_terminal() {
+                anyhow::bail!("{} happens twice as the clocks go back", label);
+            }
+            let options = vec![
+                format!("{} {} (before the clocks go back)", label, first.offset()),
+                format!("{} {} (after the clocks go back)", label, second.offset()),
+            ];
+            let picked = Select::new(&format!("{} happens twice:", label), options).raw_prompt()?;
+            Ok(if picked.index == 0 { first } else { second })
+        }
+        LocalResult::None => {
+            // Clocks skip at most a couple of hours
+            let later = (1..=8)
+                .map(|n| time + Duration::minutes(15 * n))
+                .find_map(|t| zone().from_local_datetime(&t).earliest())
+                .ok_or_else(|| anyhow::anyhow!("{} does not exist", label))?;
+            let message = format!(
+                "{} does not exist as the clocks go forward. Use {} instead?",
+                label,
+                format::time(later.time())
+            );
+            if !std::io::stdin().is_terminal() {
+                anyhow::bail!("{} does not exist as the clocks go forward", label);
+            }
+            if !Confirm::new(&message).with_default(true).prompt()? {
+                anyhow::bail!("{} does not exist", label);
+            }
+            Ok(later)
+        }
+    }
+}
@@ -0,0 +1,142 @@
+//! Weeks submitted for approval with Kimai's approval plugin, where a team
+//! lead approves or denies them.
+use anyhow::Result;
+use chrono::{Days, NaiveDate, Weekday};
+use clap::{Args, Subcommand};
+
+use crate::api::{self, TimesheetExpanded};
+use crate::format::{self, Align};
+use crate::{clock, config, parse, prompts, report};
+
+#[derive(Subcommand, Debug)]
+pub enum ApproveCommand {
+    /// Submit a week for approval
+    Submit(SubmitArgs),
+    /// Show whether recent weeks are submitted and approved
+    Status(StatusArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct SubmitArgs {
+    /// Any day of the week to submit, e.g. -7 for last week, defaults to this
+    /// week
+    #[arg(
+        long,
+        value_parser = parse::date_arg,
+        allow_hyphen_values = true,
+        num_args = 0..=1,
+        default_missing_value = "today"
+    )]
+    pub week: Option<NaiveDate>,
+    /// Submit the week of another user (id, username or alias)
+    #[arg(long)]
+    pub user: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct StatusArgs {
+    /// Any day of the last week to show, defaults to this week
+    #[arg(long, value_parser = parse::date_arg, allow_hyphen_values = true)]
+    pub week: Option<NaiveDate>,
+    /// How many weeks to show
+    #[arg(long, default_value_t = 4)]
+    pub weeks: u32,
+    /// Show the weeks of another user (id, username or alias)
+    #[arg(long)]
+    pub user: Option<String>,
+}
+
+/// The Monday of the week of `date`. The approval plugin's weeks start on
+/// Monday, whatever `week_start` says.
+fn monday(date: NaiveDate) -> NaiveDate {
+    date.week(Weekday::Mon).first_day()
+}
+
+/// The id of the user given with `--user`, or the token's own.
+fn user_id(config: &config::Config, needle: Option<&str>) -> Result<i32> {
+    Ok(match needle {
+        Some(needle) => prompts::user(config, needle)?.id,
+        None => api::get_current_user(config)?.id,
+    })
+}
+
+/// Entries of `user` from `monday` for `weeks` weeks.
+fn entries(
+    config: &config::Config,
+    user: Option<i32>,
+    monday: NaiveDate,
+    weeks: u32,
+) -> Result<Vec<TimesheetExpanded>> {
+    let to = monday + Days::new(u64::from(weeks) * 7 - 1);
+    report::fetch_for(config, monday, to, user)
+}
+
+/// `result` of a plugin request, explaining a missing plugin.
+fn plugin<T>(result: Result<T>) -> Result<T> {
+    result.map_err(|e| {
+        if api::is_not_found(&e) {
+            e.context("Kimai doesn't know about approvals, is the approval plugin installed?")
+        } else {
+            e
+        }
+    })
+}
+
+fn submit(config: &config::Config, args: SubmitArgs) -> Result<()> {
+    let monday = monday(args.week.unwrap_or_else(clock::today));
+    let user = user_id(config, args.user.as_deref())?;
+    let entries = entries(config, args.user.is_some().then_some(user), monday, 1)?;
+    if entries.iter().any(|e| e.end.is_none()) {
+        anyhow::bail!(
+            "A timer of the week of {} is still running, stop it first",
+            format::date(monday)
+        );
+    }
+    let logged = entries.iter().map(report::entry_duration).sum();
+
+    plugin(api::submit_week(config, user, monday))?;
+    format::note!(
+        "Submitted the week of {} with {} logged for approval",
+        format::date(monday),
+        format::duration(logged)
+    );
+    Ok(())
+}
+
+fn status(config: &config::Config, args: StatusArgs) -> Result<()> {
+    let weeks = args.weeks.max(1);
+    let last = monday(args.week.unwrap_or_else(clock::today));
+    let first = last - Days::new(u64::from(weeks - 1) * 7);
+    let user = user_id(config, args.user.as_deref())?;
+    let entries = entries(config, args.user.is_some().then_some(user), first, weeks)?;
+
+    let headers = [
+        ("Week", Align::Left),
+        ("Logged", Align::Right),
+        ("Status", Align::Left),
+    ];
+    let mut rows = Vec::new();
+    for week in 0..weeks {
+        let monday = first + Days::new(u64::from(week) * 7);
+        let logged = entries
+            .iter()
+            .filter(|e| self::monday(e.begin.with_timezone(&clock::zone()).date_naive()) == monday)
+            .map(report::entry_duration)
+            .sum();
+        let status = plugin(api::get_week_status(config, user, monday))?.status;
+        rows.push(vec![
+            format::date(monday),
+            format::duration(logged),
+            status.replace('_', " "),
+        ]);
+    }
+    format::table(&headers, &rows);
+    Ok(())
+}
+
+pub fn run(config: &config::Config, command: ApproveCommand) -> Result<()> {
+    match command {
+        ApproveCommand::Submit(args) => submit(config, args),
+        ApproveCommand::Status(args) => status(config, args),
+    }
+}
@@ -0,0 +1,37 @@
+use anyhow::Result;
+use clap::Subcommand;
+use inquire::{required, Password, PasswordDisplayMode};
+
+use crate::{config, format};
+
+#[derive(Subcommand, Debug)]
+pub enum AuthCommand {
+    /// Store an API token in the OS keyring
+    Login,
+    /// Remove the stored API token from the OS keyring
+    Logout,
+}
+
+pub fn run(config: &config::Config, command: &AuthCommand) -> Result<()> {
+    let entry = config::keyring_entry(config.profile.as_deref())?;
+    match command {
+        AuthCommand::Login => {
+            let token = Password::new("API token:")
+                .with_display_mode(PasswordDisplayMode::Masked)
+                .without_confirmation()
+                .with_validator(required!("This field is required"))
+                .prompt()?;
+            entry.set_password(token.trim())?;
+            format::note!("Token stored in the keyring.");
+            if !config.use_keyring {
+                format::note!("Set `use_keyring = true` in your config file to use it.");
+            }
+        }
+        AuthCommand::Logout => match entry.delete_credential() {
+            Ok(()) => format::note!("Token removed from the keyring."),
+            Err(keyring::Error::NoEntry) => format::note!("No token stored in the keyring."),
+            Err(e) => return Err(e.into()),
+        },
+    }
+    Ok(())
+}
@@ -0,0 +1,136 @@
+//! The working-time account: overtime and hours owed since a start date,
+//! from the expected hours, days off and the time logged.
+use anyhow::Result;
+use chrono::{Duration, NaiveDate};
+use clap::Args;
+use std::collections::BTreeMap;
+
+use crate::format::{self, Align, Style};
+use crate::report::{self, Grid};
+use crate::{clock, config, parse, prompts};
+
+#[derive(Args, Debug)]
+pub struct BalanceArgs {
+    /// First day to count, instead of `start` from `[balance]` and without
+    /// its carry-over
+    #[arg(long, value_parser = parse::date_arg, allow_hyphen_values = true)]
+    pub from: Option<NaiveDate>,
+    /// Last day to count, defaults to today
+    #[arg(long, value_parser = parse::date_arg, allow_hyphen_values = true)]
+    pub to: Option<NaiveDate>,
+    /// Show the balance per week instead of per month
+    #[arg(long)]
+    pub week: bool,
+    /// Show the balance of another user (id, username or alias)
+    #[arg(long)]
+    pub user: Option<String>,
+}
+
+/// A difference with its sign, e.g. `+1:30` or `-0:45`.
+fn signed(duration: Duration) -> String {
+    if duration > Duration::zero() {
+        format!("+{}", format::duration(duration))
+    } else {
+        format::duration(duration)
+    }
+}
+
+pub fn run(config: &config::Config, args: BalanceArgs) -> Result<()> {
+    let (from, carry_over) = match (args.from, &config.balance) {
+        (Some(from), _) => (from, Duration::zero()),
+        (None, Some(balance)) => (
+            balance.start,
+            Duration::minutes((balance.carry_over * 60.0) as i64),
+        ),
+        (None, None) => {
+            anyhow::bail!("Set the first day to count with `start` in `[balance]`, or pass --from")
+        }
+    };
+    let today = clock::today();
+    let to = args.to.unwrap_or(today);
+    if to < from {
+        anyhow::bail!(
+            "The balance starts on {}, after {}",
+            format::date(from),
+            format::date(to)
+        );
+    }
+
+    let user = match &args.user {
+        Some(needle) => Some(prompts::user(config, needle)?.id),
+        None => None,
+    };
+    let entries = report::fetch_for(config, from, to, user)?;
+    let grid = Grid::new(&entries, from, to);
+    let days_off = report::days_off(config, from, to)?;
+
+    // Expected and logged time per week or month, keyed by its first day
+    let mut periods: BTreeMap<NaiveDate, (Duration, Duration)> = BTreeMap::new();
+    for (day, logged) in grid.days.iter().zip(grid.day_totals()) {
+        let mut expected = report::expected(config, *day, &days_off);
+        // Today isn't over, so it only counts once it has overtime
+        if *day == today {
+            expected = expected.min(logged);
+        }
+        let start = if args.week {
+            report::week(*day).0
+        } else {
+            report::month(*day).0
+        };
+        let period = periods.entry(start).or_default();
+        period.0 += expected;
+        period.1 += logged;
+    }
+
+    let mut balance = carry_over;
+    let mut rows = Vec::new();
+    if !carry_over.is_zero() {
+        rows.push(vec![
+            "Carried over".to_string(),
+            String::new(),
+            String::new(),
+            String::new(),
+            signed(carry_over),
+        ]);
+    }
+    for (start, (expected, logged)) in &periods {
+        balance += *logged - *expected;
+        let label = if args.week {
+            format::date(*start)
+        } else {
+            start.format("%B %Y").to_string()
+        };
+        rows.push(vec![
+            label,
+            format::duration(*expected),
+            format::duration(*logged),
+            signed(*logged - *expected),
+            signed(balance),
+        ]);
+    }
+    format::table(
+        &[
+            (if args.week { "Week" } else { "Month" }, Align::Left),
+            ("Expected", Align::Right),
+            ("Logged", Align::Right),
+            ("Difference", Align::Right),
+            ("Balance", Align::Right),
+        ],
+        &rows,
+    );
+
+    let (text, style) = if balance < Duration::zero() {
+        (format!("{} owed", format::duration(-balance)), Style::Red)
+    } else {
+        (
+            format!("{} overtime", format::duration(balance)),
+            Style::Green,
+        )
+    };
+    println!(
+        "Balance on {}: {}",
+        format::date(to),
+        format::paint(&text, style)
+    );
+    Ok(())
+}
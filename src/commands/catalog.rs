@@ -0,0 +1,141 @@
+//! Read-only listings of what the token can see, for scripts and for
+//! checking why a project doesn't show up in the pickers.
+use anyhow::Result;
+use clap::Args;
+use clap_complete::ArgValueCandidates;
+use serde::Serialize;
+
+use crate::format::{self, Align};
+use crate::{api, complete, config, prompts};
+
+#[derive(Args, Debug)]
+pub struct CatalogArgs {
+    /// Print JSON instead of a table
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ProjectsArgs {
+    /// Only list the projects of this customer (name or id)
+    #[arg(long)]
+    pub customer: Option<String>,
+    #[command(flatten)]
+    pub output: CatalogArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct ActivitiesArgs {
+    /// Only list the activities usable on this project (name or id),
+    /// including global ones
+    #[arg(long, add = ArgValueCandidates::new(complete::projects))]
+    pub project: Option<String>,
+    #[command(flatten)]
+    pub output: CatalogArgs,
+}
+
+/// Print `items` as JSON or as a table of `headers` and `row`s.
+fn print<T: Serialize>(
+    args: &CatalogArgs,
+    items: &[T],
+    headers: &[(&str, Align)],
+    row: impl Fn(&T) -> Vec<String>,
+) -> Result<()> {
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(items)?);
+    } else {
+        let rows: Vec<Vec<String>> = items.iter().map(row).collect();
+        format::table(headers, &rows);
+    }
+    Ok(())
+}
+
+pub fn projects(config: &config::Config, args: ProjectsArgs) -> Result<()> {
+    let projects = match &args.customer {
+        Some(needle) => {
+            let customer = prompts::customer(config, Some(needle))?;
+            api::get_projects_by_customer(config, customer.id)?
+        }
+        None => api::get_projects(config)?,
+    };
+    print(
+        &args.output,
+        &projects,
+        &[
+            ("ID", Align::Right),
+            ("Customer", Align::Left),
+            ("Project", Align::Left),
+        ],
+        |p| {
+            vec![
+                p.id.to_string(),
+                p.parent_title.clone().unwrap_or_default(),
+                p.name.clone(),
+            ]
+        },
+    )
+}
+
+pub fn activities(config: &config::Config, args: ActivitiesArgs) -> Result<()> {
+    let activities = match &args.project {
+        Some(needle) => {
+            let project = prompts::project(config, Some(needle))?;
+            api::get_activities_by_project(config, project.id)?
+        }
+        None => api::get_activities(config)?,
+    };
+    print(
+        &args.output,
+        &activities,
+        &[
+            ("ID", Align::Right),
+            ("Project", Align::Left),
+            ("Activity", Align::Left),
+        ],
+        |a| {
+            vec![
+                a.id.to_string(),
+                // Global activities belong to no project
+                a.parent_title.clone().unwrap_or_default(),
+                a.name.clone(),
+            ]
+        },
+    )
+}
+
+pub fn customers(config: &config::Config, args: CatalogArgs) -> Result<()> {
+    let customers = api::get_customers(config)?;
+    print(
+        &args,
+        &customers,
+        &[("ID", Align::Right), ("Customer", Align::Left)],
+        |c| vec![c.id.to_string(), c.name.clone()],
+    )
+}
+
+pub fn users(config: &config::Config, args: CatalogArgs) -> Result<()> {
+    let users = api::get_users(config)?;
+    print(
+        &args,
+        &users,
+        &[
+            ("ID", Align::Right),
+            ("Username", Align::Left),
+            ("Alias", Align::Left),
+        ],
+        |u| {
+            vec![
+                u.id.to_string(),
+                u.username.clone(),
+                u.alias.clone().unwrap_or_default(),
+            ]
+        },
+    )
+}
+
+pub fn tags(config: &config::Config, args: CatalogArgs) -> Result<()> {
+    let tags = api::get_tags(config)?;
+    print(&args, &tags, &[("Tag", Align::Left)], |t| {
+        vec![t.name.clone()]
+    })
+}
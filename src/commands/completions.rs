@@ -0,0 +1,30 @@
+use anyhow::Result;
+use clap::Args;
+use clap_complete::env::Shells;
+use clap_complete::Shell;
+
+#[derive(Args, Debug)]
+pub struct CompletionsArgs {
+    /// Shell to print the script for
+    pub shell: Shell,
+}
+
+/// Print a script that has the shell ask `kimai` itself for completions, so
+/// project and activity names can be completed from the cache. Load it on
+/// shell startup, e.g. `source <(kimai completions bash)`.
+pub fn run(args: &CompletionsArgs) -> Result<()> {
+    let shell = args.shell.to_string();
+    let shells = Shells::builtins();
+    let Some(completer) = shells.completer(&shell) else {
+        anyhow::bail!("Completions are not supported for {}", shell);
+    };
+    let exe = std::env::current_exe()?;
+    completer.write_registration(
+        "COMPLETE",
+        "kimai",
+        "kimai",
+        &exe.to_string_lossy(),
+        &mut std::io::stdout(),
+    )?;
+    Ok(())
+}
@@ -0,0 +1,213 @@
+use anyhow::Result;
+use chrono::NaiveTime;
+use clap::Subcommand;
+use inquire::validator::Validation;
+use inquire::{required, Confirm, Password, PasswordDisplayMode, Text};
+use std::path::Path;
+
+use crate::{api, config, prompts};
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Print the effective configuration
+    Show,
+    /// Create a configuration file interactively
+    Init,
+}
+
+pub fn run(config: &config::Config, command: &ConfigCommand) -> Result<()> {
+    match command {
+        ConfigCommand::Show => show(config),
+        ConfigCommand::Init => unreachable!("init runs before the config is loaded"),
+    }
+}
+
+/// The settings `init` asks for.
+const PROMPTED: [&str; 5] = [
+    "endpoint",
+    "token",
+    "default_start_time",
+    "select_customer",
+    "use_keyring",
+];
+
+/// Ask for the essential settings, check them against the server and write
+/// them to the config file.
+pub fn init(profile: Option<&str>, path: Option<&Path>) -> Result<()> {
+    let path = path
+        .map(Path::to_path_buf)
+        .unwrap_or_else(config::default_config_path);
+
+    let mut config = config::Config::default();
+    loop {
+        config.server.endpoint = Text::new("Kimai URL:")
+            .with_help_message("E.g. https://kimai.example.com")
+            .with_validator(|input: &str| {
+                Ok(
+                    if input.starts_with("http://") || input.starts_with("https://") {
+                        Validation::Valid
+                    } else {
+                        Validation::Invalid("The URL must start with http:// or https://".into())
+                    },
+                )
+            })
+            .prompt()?
+            .trim()
+            .trim_end_matches('/')
+            .to_string();
+        config.server.token = Password::new("API token:")
+            .with_display_mode(PasswordDisplayMode::Masked)
+            .without_confirmation()
+            .with_validator(required!("This field is required"))
+            .prompt()?
+            .trim()
+            .to_string();
+
+        match api::get_version(&config) {
+            Ok(version) => {
+                println!("Connected to Kimai {}", version.version);
+                break;
+            }
+            Err(e) => {
+                println!("Could not connect: {}", e);
+                if !Confirm::new("Try again?").with_default(true).prompt()? {
+                    anyhow::bail!("Configuration not written");
+                }
+            }
+        }
+    }
+
+    config.default_start_time = prompts::start_time(NaiveTime::from_hms_opt(9, 0, 0).unwrap())?;
+    config.select_customer = Confirm::new("Pick a customer before picking a project?")
+        .with_default(true)
+        .prompt()?;
+
+    config.use_keyring = Confirm::new("Store the token in the OS keyring instead of the file?")
+        .with_default(false)
+        .prompt()?;
+
+    let question = match profile {
+        Some(profile) if config::has_profile(&path, profile)? => Some(format!(
+            "Profile '{}' already exists in {}. Replace it?",
+            profile,
+            path.display()
+        )),
+        Some(_) => None,
+        None if path.is_file() => Some(format!(
+            "{} already exists. Overwrite its settings?",
+            path.display()
+        )),
+        None => None,
+    };
+    if let Some(question) = question {
+        if !Confirm::new(&question).with_default(false).prompt()? {
+            anyhow::bail!("Configuration not written");
+        }
+    }
+
+    // Not before the user agreed to replace what is there
+    if config.use_keyring {
+        config::keyring_entry(profile)?.set_password(&config.server.token)?;
+        config.server.token.clear();
+    }
+
+    // Only what was asked for, the rest keeps following the defaults
+    let all = toml::Table::try_from(&config)?;
+    let values: toml::Table = all
+        .into_iter()
+        .filter(|(key, value)| {
+            PROMPTED.contains(&key.as_str())
+                && !(key == "token" && value.as_str().is_some_and(str::is_empty))
+        })
+        .collect();
+    config::save_config(values, &path, profile)?;
+    println!("Configuration written to {}", path.display());
+    Ok(())
+}
+
+fn show(config: &config::Config) -> Result<()> {
+    match &config.path {
+        Some(path) => println!("# loaded from {}", path.display()),
+        None => println!("# no config file found, using environment only"),
+    }
+    if let Some(profile) = &config.profile {
+        println!("# profile {}", profile);
+    }
+    println!("endpoint = {}", config.server.endpoint);
+    println!("token = {}", redact(&config.server.token));
+    if let Some(path) = &config.server.ca_cert {
+        println!("ca_cert = {}", path.display());
+    }
+    if config.server.insecure {
+        println!("insecure = true");
+    }
+    if let Some(proxy) = &config.server.proxy {
+        println!("proxy = {}", redact_password(proxy));
+    }
+    if config.server.auth == api::Auth::Legacy {
+        println!("auth = legacy");
+        println!("username = {}", config.server.username);
+    }
+    println!("use_keyring = {}", config.use_keyring);
+    if let Some(command) = &config.token_cmd {
+        println!("token_cmd = {}", command);
+    }
+    println!(
+        "default_start_time = {}",
+        config.default_start_time.format("%H:%M")
+    );
+    println!("week_start = {}", config.week_start);
+    let time_format = match config.time_format {
+        config::TimeFormat::Hours24 => "24h",
+        config::TimeFormat::Hours12 => "12h",
+    };
+    println!("time_format = {}", time_format);
+    println!("date_format = {}", config.date_format);
+    if let Some(timezone) = &config.timezone {
+        println!("timezone = {}", timezone);
+    }
+    println!("select_customer = {}", config.select_customer);
+    println!("show_budget = {}", config.show_budget);
+    println!("expected_hours_per_day = {}", config.expected_hours_per_day);
+    let holidays: Vec<String> = config.holidays.iter().map(|d| d.to_string()).collect();
+    println!("holidays = [{}]", holidays.join(", "));
+    println!("absences = {}", config.absences);
+    let rules = [
+        ("round_begin", config.round_begin),
+        ("round_end", config.round_end),
+        ("round_duration", config.round_duration),
+    ];
+    for (name, rule) in rules {
+        if let Some(rule) = rule {
+            println!("{} = {}", name, rule);
+        }
+    }
+    Ok(())
+}
+
+/// Only show the last few characters of a secret, enough to tell tokens apart.
+fn redact(secret: &str) -> String {
+    let chars: Vec<char> = secret.chars().collect();
+    if chars.len() <= 8 {
+        return "********".to_string();
+    }
+    let visible: String = chars[chars.len() - 4..].iter().collect();
+    format!("********{}", visible)
+}
+
+/// `url` with the password of any `user:password@` in it hidden.
+fn redact_password(url: &str) -> String {
+    let (scheme, rest) = url.split_once("://").unwrap_or(("", url));
+    match rest.split_once('@') {
+        Some((credentials, host)) => {
+            let user = credentials.split(':').next().unwrap_or_default();
+            let scheme = if scheme.is_empty() {
+                String::new()
+            } else {
+                format!("{}://", scheme)
+            };
+            format!("{}{}:********@{}", scheme, user, host)
+        }
+        None => url.to_string(),
+    }
+}
@@ -0,0 +1,91 @@
+//! Copies of an entry on other days, e.g. for the same meeting every day.
+use anyhow::Result;
+use chrono::NaiveDate;
+use clap::Args;
+use inquire::Select;
+use std::io::IsTerminal;
+
+use crate::api::{self, TimesheetEditForm, TimesheetQuery};
+use crate::commands::log;
+use crate::{clock, config, format, parse, prompts, report};
+
+#[derive(Args, Debug)]
+pub struct CopyArgs {
+    /// Id of the entry to copy; pick from recent entries if omitted
+    pub id: Option<i32>,
+    /// Day to copy the entry to, e.g. tomorrow or 2024-05-01
+    #[arg(long, value_parser = parse::date_arg, allow_hyphen_values = true, conflicts_with = "to_range")]
+    pub to: Option<NaiveDate>,
+    /// Every day of a range to copy the entry to, e.g. mon..fri or
+    /// 2024-05-06..2024-05-10
+    #[arg(long, value_parser = parse::date_range_arg, allow_hyphen_values = true)]
+    pub to_range: Option<(NaiveDate, NaiveDate)>,
+}
+
+/// Let the user pick one of their recent finished entries.
+fn pick_recent(config: &config::Config) -> Result<i32> {
+    let recent = api::get_timesheets(
+        config,
+        &TimesheetQuery {
+            limit: Some(20),
+            ..Default::default()
+        },
+    )?;
+    let recent: Vec<_> = recent.into_iter().filter(|t| t.end.is_some()).collect();
+    if recent.is_empty() {
+        anyhow::bail!("No recent entries to copy");
+    }
+
+    let labels: Vec<String> = recent.iter().map(format::entry).collect();
+    let picked = Select::new("Entry:", labels).raw_prompt()?;
+    Ok(recent[picked.index].id)
+}
+
+pub fn run(config: &config::Config, args: CopyArgs) -> Result<()> {
+    let id = match args.id {
+        Some(id) => id,
+        None => pick_recent(config)?,
+    };
+    let entry = api::get_timesheet(config, id)?;
+    let Some(end) = entry.end else {
+        anyhow::bail!("Entry #{} is still running, stop it first", id);
+    };
+    let zone = clock::zone();
+    let begin = entry.begin.with_timezone(&zone);
+    let duration = end.with_timezone(&zone) - begin;
+
+    let days = match (args.to, args.to_range) {
+        (Some(to), _) => vec![to],
+        (None, Some((from, to))) => report::days(from, to),
+        (None, None) if std::io::stdin().is_terminal() => vec![prompts::date(clock::today())?],
+        (None, None) => anyhow::bail!("Pass the day to copy to with --to or --to-range"),
+    };
+    // The entry is there already on its own day
+    let days: Vec<NaiveDate> = days
+        .into_iter()
+        .filter(|day| *day != begin.date_naive())
+        .collect();
+    if days.is_empty() {
+        anyhow::bail!("Entry #{} is on that day already", id);
+    }
+
+    for day in days {
+        let copy_begin = clock::resolve(day.and_time(begin.time()))?;
+        let form = TimesheetEditForm {
+            begin: copy_begin.to_utc(),
+            project: entry.project,
+            activity: entry.activity,
+            end: Some((copy_begin + duration).to_utc()),
+            description: entry.description.clone(),
+            tags: Some(entry.tags.clone()),
+            billable: None,
+            meta_fields: Vec::new(),
+            user: None,
+        };
+        log::submit(config, form)?;
+        if !config.dry_run {
+            format::note!("Copied entry #{} to {}", id, format::day(day));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,142 @@
+//! Creating projects and activities, which needs the matching permissions in
+//! Kimai, usually an admin or team lead account.
+use anyhow::Result;
+use clap::{Args, Subcommand};
+use clap_complete::ArgValueCandidates;
+use inquire::validator::Validation;
+use inquire::{Confirm, Text};
+use std::io::IsTerminal;
+
+use crate::api::{self, ActivityForm, ProjectForm};
+use crate::{complete, config, format, prompts};
+
+#[derive(Subcommand, Debug)]
+pub enum ProjectCommand {
+    /// Create a project
+    Create(CreateProjectArgs),
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ActivityCommand {
+    /// Create an activity
+    Create(CreateActivityArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct CreateProjectArgs {
+    /// Customer of the project (name or id)
+    #[arg(long)]
+    pub customer: Option<String>,
+    /// Name of the project
+    #[arg(long)]
+    pub name: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct CreateActivityArgs {
+    /// Project of the activity (name or id)
+    #[arg(long, conflicts_with = "global", add = ArgValueCandidates::new(complete::projects))]
+    pub project: Option<String>,
+    /// Create an activity usable in every project
+    #[arg(long)]
+    pub global: bool,
+    /// Name of the activity
+    #[arg(long)]
+    pub name: Option<String>,
+}
+
+/// The name given on the command line, or asked for.
+fn name(given: Option<String>, message: &str) -> Result<String> {
+    if let Some(name) = given.filter(|n| !n.trim().is_empty()) {
+        return Ok(name.trim().to_string());
+    }
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!("Pass a name with --name");
+    }
+    let name = Text::new(message)
+        .with_validator(|input: &str| {
+            Ok(if input.trim().is_empty() {
+                Validation::Invalid("The name can't be empty".into())
+            } else {
+                Validation::Valid
+            })
+        })
+        .prompt()?;
+    Ok(name.trim().to_string())
+}
+
+pub fn project(config: &config::Config, command: ProjectCommand) -> Result<()> {
+    let ProjectCommand::Create(args) = command;
+    let customer = prompts::customer(config, args.customer.as_deref())?;
+    let name = name(args.name, "Project name:")?;
+
+    let existing = api::get_projects_by_customer(config, customer.id)?;
+    if existing.iter().any(|p| p.name.eq_ignore_ascii_case(&name)) {
+        anyhow::bail!("{} already has a project named '{}'", customer, name);
+    }
+
+    let project = api::create_project(
+        config,
+        &ProjectForm {
+            name,
+            customer: customer.id,
+            visible: true,
+        },
+    )?;
+    format::note!(
+        "Created project #{} {} for {}",
+        project.id,
+        project.name,
+        customer
+    );
+    Ok(())
+}
+
+pub fn activity(config: &config::Config, command: ActivityCommand) -> Result<()> {
+    let ActivityCommand::Create(args) = command;
+    let global = args.global
+        || (args.project.is_none()
+            && std::io::stdin().is_terminal()
+            && Confirm::new("Global activity, usable in every project?")
+                .with_default(false)
+                .prompt()?);
+    let project = if global {
+        None
+    } else {
+        Some(prompts::project(config, args.project.as_deref())?)
+    };
+    let name = name(args.name, "Activity name:")?;
+
+    let existing = match &project {
+        Some(project) => api::get_activities_by_project(config, project.id)?,
+        None => api::get_activities(config)?,
+    };
+    let taken = existing.iter().any(|a| {
+        a.name.eq_ignore_ascii_case(&name) && (project.is_some() || a.parent_title.is_none())
+    });
+    if taken {
+        match &project {
+            Some(project) => anyhow::bail!("{} already has an activity named '{}'", project, name),
+            None => anyhow::bail!("A global activity named '{}' already exists", name),
+        }
+    }
+
+    let activity = api::create_activity(
+        config,
+        &ActivityForm {
+            name,
+            project: project.as_ref().map(|p| p.id),
+            visible: true,
+        },
+    )?;
+    match project {
+        Some(project) => format::note!(
+            "Created activity #{} {} in {}",
+            activity.id,
+            activity.name,
+            project
+        ),
+        None => format::note!("Created global activity #{} {}", activity.id, activity.name),
+    }
+    Ok(())
+}
@@ -0,0 +1,228 @@
+//! Going through the entries of one day and fixing them up, one change at a
+//! time.
+use anyhow::Result;
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Timelike};
+use clap::Args;
+use inquire::{Confirm, InquireError, Select};
+use std::io::IsTerminal;
+
+use crate::api::{self, TimesheetEditForm, TimesheetExpanded};
+use crate::clock::{self, Zone};
+use crate::commands::{edit, split};
+use crate::invalid::invalid;
+use crate::{config, format, parse, prompts, report};
+
+#[derive(Args, Debug)]
+pub struct DayArgs {
+    /// The day to edit, e.g. yesterday, mon or 2024-05-01, defaults to today
+    #[arg(value_parser = parse::date_arg, allow_hyphen_values = true)]
+    pub date: Option<NaiveDate>,
+}
+
+const DONE: &str = "Done";
+
+const BEGIN: &str = "Begin";
+const END: &str = "End";
+const DURATION: &str = "Duration";
+const DESCRIPTION: &str = "Description";
+const SPLIT: &str = "Split in two";
+
+fn label(entry: &TimesheetExpanded) -> String {
+    let time = match entry.end {
+        Some(end) => format::span(entry.begin, end),
+        None => format!(
+            "{}– (running)",
+            format::time(entry.begin.with_timezone(&clock::zone()).time())
+        ),
+    };
+    let mut label = format!(
+        "{} {} / {} {}",
+        time,
+        entry.project.name,
+        entry.activity.name,
+        entry.description.as_deref().unwrap_or_default()
+    );
+    if entry.exported {
+        label.push_str(" [exported]");
+    }
+    label
+}
+
+/// The form to save `entry` unchanged.
+fn form(entry: &TimesheetExpanded) -> TimesheetEditForm {
+    TimesheetEditForm {
+        begin: entry.begin.to_utc(),
+        project: entry.project.id,
+        activity: entry.activity.id,
+        end: entry.end.map(|end| end.to_utc()),
+        description: entry.description.clone(),
+        tags: Some(entry.tags.clone()),
+        billable: None,
+        meta_fields: Vec::new(),
+        user: None,
+    }
+}
+
+/// `time` on the day `begin` is on, or on the next day if that's not after
+/// `begin`.
+pub fn after(begin: DateTime<Zone>, time: NaiveTime) -> Result<DateTime<Zone>> {
+    let begin_time = begin.time().with_second(0).unwrap_or(begin.time());
+    Ok(begin + parse::elapsed(begin_time, time).map_err(|e| invalid!("{}", e))?)
+}
+
+/// The later entries of the day, moved by `delta`, if they may be and the
+/// user wants that. `entries` is sorted by begin.
+fn shifted(
+    entries: &[TimesheetExpanded],
+    index: usize,
+    old_end: DateTime<Zone>,
+    delta: Duration,
+) -> Result<Vec<(i32, TimesheetEditForm)>> {
+    let later: Vec<&TimesheetExpanded> = entries[index + 1..]
+        .iter()
+        .filter(|e| e.begin >= old_end)
+        .collect();
+    if later.is_empty() || delta <= Duration::zero() {
+        return Ok(Vec::new());
+    }
+    if later.iter().any(|e| e.exported) {
+        println!("Later entries were exported, so they stay where they are");
+        return Ok(Vec::new());
+    }
+    let confirmed = Confirm::new(&format!(
+        "Move the {} later entries by {} as well?",
+        later.len(),
+        format::duration(delta)
+    ))
+    .with_default(true)
+    .prompt()?;
+    if !confirmed {
+        return Ok(Vec::new());
+    }
+
+    // Last first, so no entry is moved onto one that hasn't moved yet
+    Ok(later
+        .iter()
+        .rev()
+        .map(|e| {
+            let mut form = form(e);
+            form.begin += delta;
+            form.end = form.end.map(|end| end + delta);
+            (e.id, form)
+        })
+        .collect())
+}
+
+/// Ask what to change about `entries[index]` and save it.
+fn change(config: &config::Config, entries: &[TimesheetExpanded], index: usize) -> Result<()> {
+    let entry = &entries[index];
+    if entry.exported {
+        anyhow::bail!(
+            "Entry #{} was exported, e.g. to an invoice, and can't be changed anymore",
+            entry.id
+        );
+    }
+    let zone = clock::zone();
+    let begin = entry.begin.with_timezone(&zone);
+    let end = entry.end.map(|end| end.with_timezone(&zone));
+
+    let options = match end {
+        Some(_) => vec![BEGIN, END, DURATION, DESCRIPTION, SPLIT],
+        // Running timers have no end to change or split at yet
+        None => vec![BEGIN, DESCRIPTION],
+    };
+    let picked = Select::new("Change:", options).prompt()?;
+
+    let mut form = form(entry);
+    let mut updates = Vec::new();
+    match (picked, end) {
+        (BEGIN, _) => {
+            let time = prompts::time("Begin:", begin.time())?;
+            let new_begin = clock::resolve(begin.date_naive().and_time(time))?;
+            if end.is_some_and(|end| new_begin >= end) {
+                anyhow::bail!("The begin must be before the end");
+            }
+            form.begin = new_begin.to_utc();
+        }
+        (END | DURATION, Some(end)) => {
+            let new_end = if picked == END {
+                after(begin, prompts::time("End:", end.time())?)?
+            } else {
+                begin + prompts::duration(end - begin)?
+            };
+            form.end = Some(new_end.to_utc());
+            updates = shifted(entries, index, end, new_end - end)?;
+        }
+        (DESCRIPTION, _) => {
+            let current = entry.description.as_deref().unwrap_or_default();
+            form.description = Some(prompts::description_for(
+                config,
+                entry.project.id,
+                entry.activity.id,
+                current,
+            )?);
+        }
+        (SPLIT, Some(end)) => {
+            let middle = begin + (end - begin) / 2;
+            let at = after(begin, prompts::time("Split at:", middle.time())?)?;
+            split::check(entry.id, &form, at)?;
+            println!("The part after {}:", format::time(at.time()));
+            let project = prompts::project_with_default(config, entry.project.id)?;
+            let activity = prompts::activity_with_default(config, &project, entry.activity.id)?;
+            let current = entry.description.as_deref().unwrap_or_default();
+            let description = prompts::description_for(config, project.id, activity.id, current)?;
+            let rest = TimesheetEditForm {
+                project: project.id,
+                activity: activity.id,
+                description: Some(description),
+                ..form.clone()
+            };
+            return split::apply(config, entry.id, form, at, rest);
+        }
+        _ => unreachable!(),
+    }
+
+    for (id, form) in updates {
+        edit::update(config, id, &form)?;
+    }
+    edit::update(config, entry.id, &form)?;
+    Ok(())
+}
+
+pub fn run(config: &config::Config, args: DayArgs) -> Result<()> {
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!("The day editor needs a terminal, use `kimai edit` instead");
+    }
+    let date = args.date.unwrap_or_else(clock::today);
+
+    loop {
+        let mut entries = report::fetch(config, date, date)?;
+        if entries.is_empty() {
+            println!("No entries on {}", format::date(date));
+            return Ok(());
+        }
+        entries.sort_by_key(|e| e.begin);
+
+        let mut labels: Vec<String> = entries.iter().map(label).collect();
+        labels.push(DONE.to_string());
+        let message = format!("{}:", format::day(date));
+        let picked = match Select::new(&message, labels).raw_prompt() {
+            Ok(picked) => picked,
+            Err(InquireError::OperationCanceled) => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        if picked.index == entries.len() {
+            return Ok(());
+        }
+
+        match change(config, &entries, picked.index) {
+            Ok(()) => {}
+            // Nothing changes in a dry run, so go on with the same entries
+            Err(e) if api::is_dry_run(&e) => {}
+            // Escape goes back to the list
+            Err(e) if matches!(e.downcast_ref(), Some(InquireError::OperationCanceled)) => {}
+            Err(e) if e.downcast_ref::<InquireError>().is_some() => return Err(e),
+            Err(e) => eprintln!("Error: {:#}", e),
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use anyhow::Result;
+use chrono::NaiveTime;
+use clap::Args;
+use inquire::{Confirm, MultiSelect};
+
+use crate::api::{self, TimesheetQuery};
+use crate::commands::edit;
+use crate::{clock, config, format};
+
+#[derive(Args, Debug)]
+pub struct DeleteArgs {
+    /// Ids of the entries to delete; pick from today's entries if omitted
+    pub ids: Vec<i32>,
+    /// Don't ask for confirmation
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+/// Let the user pick any number of today's entries.
+fn pick_today(config: &config::Config) -> Result<Vec<i32>> {
+    let today = clock::today();
+    let entries = api::get_timesheets(
+        config,
+        &TimesheetQuery {
+            begin: Some(today.and_time(NaiveTime::MIN)),
+            end: Some(today.and_hms_opt(23, 59, 59).unwrap()),
+            ..Default::default()
+        },
+    )?;
+    if entries.is_empty() {
+        anyhow::bail!("No entries today");
+    }
+    let entries: Vec<_> = entries.into_iter().filter(|e| !e.exported).collect();
+    if entries.is_empty() {
+        anyhow::bail!("Today's entries were exported and can't be deleted anymore");
+    }
+
+    let labels: Vec<String> = entries.iter().map(format::entry).collect();
+    let picked = MultiSelect::new("Entries to delete:", labels).raw_prompt()?;
+    Ok(picked
+        .iter()
+        .map(|option| entries[option.index].id)
+        .collect())
+}
+
+pub fn run(config: &config::Config, args: DeleteArgs) -> Result<()> {
+    let ids = if args.ids.is_empty() {
+        pick_today(config)?
+    } else {
+        for id in &args.ids {
+            edit::editable(config, *id)?;
+        }
+        args.ids
+    };
+    if ids.is_empty() {
+        return Ok(());
+    }
+
+    if !args.yes {
+        let listed: Vec<String> = ids.iter().map(|id| format!("#{}", id)).collect();
+        let confirmed = Confirm::new(&format!("Delete {}?", listed.join(", ")))
+            .with_default(false)
+            .prompt()?;
+        if !confirmed {
+            return Ok(());
+        }
+    }
+
+    for id in ids {
+        match api::delete_timesheet(config, id) {
+            Ok(()) => format::note!("Deleted entry #{}", id),
+            // Show every request that would be made
+            Err(e) if api::is_dry_run(&e) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,181 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use std::path::Path;
+
+use crate::{api, config, format};
+
+/// Clocks further apart than this skew the begin of timers noticeably.
+const MAX_SKEW_SECONDS: i64 = 60;
+
+#[derive(Default)]
+struct Doctor {
+    problems: usize,
+}
+
+impl Doctor {
+    fn ok(&self, message: &str) {
+        println!("✓ {}", message);
+    }
+
+    fn warn(&self, message: &str, hint: &str) {
+        println!("! {}\n  {}", message, hint);
+    }
+
+    fn fail(&mut self, message: &str, hint: &str) {
+        self.problems += 1;
+        println!("✗ {}\n  {}", message, hint);
+    }
+
+    fn finish(self) -> Result<()> {
+        if self.problems > 0 {
+            anyhow::bail!("{} problem(s) found", self.problems);
+        }
+        println!("No problems found");
+        Ok(())
+    }
+}
+
+/// Check the configuration and the connection to Kimai step by step, stopping
+/// at the first problem later checks depend on. Runs before the config is
+/// loaded, since a broken config is one of the things it diagnoses.
+pub fn run(profile: Option<&str>, path: Option<&Path>) -> Result<()> {
+    let mut doctor = Doctor::default();
+
+    match config::find_config_file(path) {
+        Some(path) if path.is_file() => doctor.ok(&format!("Config file {}", path.display())),
+        Some(path) => doctor.fail(
+            &format!("Config file {} does not exist", path.display()),
+            "Check the path given with --config or KIMAI_CONFIG",
+        ),
+        None => doctor.warn(
+            "No config file found, only environment variables are used",
+            "Run `kimai config init` to create one",
+        ),
+    }
+
+    let config = match config::load_config(profile, path) {
+        Ok(config) => {
+            doctor.ok("Configuration is valid");
+            config
+        }
+        Err(e) => {
+            doctor.fail(
+                &format!("The configuration could not be loaded: {:#}", e),
+                "Fix the problem named above, the README describes the format",
+            );
+            return doctor.finish();
+        }
+    };
+
+    if !config.server.endpoint.starts_with("http://")
+        && !config.server.endpoint.starts_with("https://")
+    {
+        doctor.fail(
+            &format!("The endpoint '{}' is not a URL", config.server.endpoint),
+            "Set `endpoint` to the address you open Kimai at, e.g. https://kimai.example.com",
+        );
+        return doctor.finish();
+    }
+
+    // Ask without the token first, to tell network problems from token problems
+    let sent = Utc::now();
+    let agent = match api::agent(&config) {
+        Ok(agent) => agent,
+        Err(e) => {
+            doctor.fail(
+                &format!("{:#}", e),
+                "Check that `ca_cert` is a PEM file with the certificate of the CA",
+            );
+            return doctor.finish();
+        }
+    };
+    let response = match agent
+        .get(&format!("{}/api/version", config.server.endpoint))
+        .call()
+    {
+        Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+        Err(e) => {
+            doctor.fail(
+                &format!("Cannot reach {}: {}", config.server.endpoint, e),
+                "Check the URL and your network connection, VPN or proxy",
+            );
+            return doctor.finish();
+        }
+    };
+    doctor.ok(&format!("{} is reachable", config.server.endpoint));
+    let received = Utc::now();
+    check_clock(
+        &doctor,
+        sent + (received - sent) / 2,
+        response.header("Date"),
+    );
+
+    match config::token(&config) {
+        Ok(token) if token.is_empty() => {
+            doctor.fail(
+                "No API token configured",
+                "Set `token` or `token_cmd`, or run `kimai auth login`",
+            );
+            return doctor.finish();
+        }
+        Ok(_) => {}
+        Err(e) => {
+            doctor.fail(
+                &format!("{:#}", e),
+                "Check `token_cmd`, or the token stored with `kimai auth login`",
+            );
+            return doctor.finish();
+        }
+    }
+    match api::get_version(&config) {
+        Ok(version) => doctor.ok(&format!("Kimai {}", version.version)),
+        Err(e) => {
+            let hint = if api::is_unauthorized(&e) && config.server.auth == api::Auth::Legacy {
+                "Check `username`, and that `token` is the API password set in your Kimai profile"
+            } else if api::is_unauthorized(&e) {
+                "Create a new API token in Kimai, under API access in your profile"
+            } else if api::is_not_found(&e) {
+                "Set `endpoint` to the address you open Kimai at, without /api"
+            } else {
+                "Check that the endpoint points at Kimai 1.x or 2.x"
+            };
+            doctor.fail(&format!("The API did not answer: {:#}", e), hint);
+            return doctor.finish();
+        }
+    }
+    match api::get_current_user(&config) {
+        Ok(user) => doctor.ok(&format!("Logged in as {}", user)),
+        Err(e) => doctor.fail(
+            &format!("Could not look up the token's user: {:#}", e),
+            "Check that the token's user is active and may use the API",
+        ),
+    }
+
+    doctor.finish()
+}
+
+/// Compare the server's `Date` header with the local time the request was
+/// answered at.
+fn check_clock(doctor: &Doctor, local: DateTime<Utc>, date: Option<&str>) {
+    let Some(server) = date.and_then(|d| DateTime::parse_from_rfc2822(d).ok()) else {
+        return;
+    };
+    let skew = local - server.to_utc();
+    if skew.num_seconds().abs() <= MAX_SKEW_SECONDS {
+        doctor.ok("The clock matches the server's");
+        return;
+    }
+    let direction = if skew > Duration::zero() {
+        "ahead of"
+    } else {
+        "behind"
+    };
+    doctor.warn(
+        &format!(
+            "The clock is {} {} the server's",
+            format::duration(skew.abs()),
+            direction
+        ),
+        "Timers started here will begin at the wrong time, sync the system clock",
+    );
+}
@@ -0,0 +1,110 @@
+use anyhow::Result;
+use clap::Args;
+use inquire::Select;
+
+use crate::api::{self, Timesheet, TimesheetEditForm, TimesheetQuery};
+use crate::{clock, config, format, prompts};
+
+#[derive(Args, Debug)]
+pub struct EditArgs {
+    /// Id of the entry to edit; pick from recent entries if omitted
+    pub id: Option<i32>,
+}
+
+/// Let the user pick one of their most recent entries.
+fn pick_recent(config: &config::Config) -> Result<i32> {
+    let recent = api::get_timesheets(
+        config,
+        &TimesheetQuery {
+            limit: Some(20),
+            ..Default::default()
+        },
+    )?;
+    let recent: Vec<_> = recent.into_iter().filter(|t| !t.exported).collect();
+    if recent.is_empty() {
+        anyhow::bail!("No recent entries to edit");
+    }
+
+    let labels: Vec<String> = recent.iter().map(format::entry).collect();
+    let picked = Select::new("Entry:", labels).raw_prompt()?;
+    Ok(recent[picked.index].id)
+}
+
+/// Fetch entry `id`, refusing entries Kimai doesn't let users change
+/// anymore.
+pub fn editable(config: &config::Config, id: i32) -> Result<Timesheet> {
+    let entry = api::get_timesheet(config, id)?;
+    if entry.exported {
+        anyhow::bail!(
+            "Entry #{} was exported, e.g. to an invoice, and can't be changed anymore",
+            id
+        );
+    }
+    Ok(entry)
+}
+
+/// Change entry `id`, explaining why Kimai may refuse it.
+pub fn update(config: &config::Config, id: i32, form: &TimesheetEditForm) -> Result<Timesheet> {
+    api::update_timesheet(config, id, form).map_err(|e| {
+        if api::is_unauthorized(&e) {
+            // Kimai also locks entries in periods closed by a team lead
+            e.context(format!(
+                "Kimai refused to change entry #{}, it may be in a locked period",
+                id
+            ))
+        } else {
+            e
+        }
+    })
+}
+
+pub fn run(config: &config::Config, args: EditArgs) -> Result<()> {
+    let id = match args.id {
+        Some(id) => id,
+        None => pick_recent(config)?,
+    };
+    let current = editable(config, id)?;
+
+    let project = prompts::project_with_default(config, current.project)?;
+    let activity = prompts::activity_with_default(config, &project, current.activity)?;
+
+    let current_begin = current.begin.with_timezone(&clock::zone());
+    let date = prompts::date(current_begin.date_naive())?;
+    let start = prompts::start_time(current_begin.time())?;
+
+    // Running timers stay running; only finished entries have a duration to edit.
+    let duration = match current.end {
+        Some(end) => Some(prompts::duration(end - current.begin)?),
+        None => None,
+    };
+
+    let description = prompts::description_for(
+        config,
+        project.id,
+        activity.id,
+        current.description.as_deref().unwrap_or_default(),
+    )?;
+
+    let tags = prompts::tags(config, Vec::new(), &current.tags)?;
+
+    let begin = clock::resolve(date.and_time(start))?;
+
+    let updated = update(
+        config,
+        id,
+        &TimesheetEditForm {
+            begin: begin.to_utc(),
+            project: project.id,
+            activity: activity.id,
+            end: duration.map(|duration| (begin + duration).to_utc()),
+            description: Some(description),
+            tags: Some(tags),
+            billable: None,
+            meta_fields: Vec::new(),
+            user: None,
+        },
+    )?;
+
+    format::note!("Updated entry #{}", updated.id);
+    Ok(())
+}
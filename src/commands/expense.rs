@@ -0,0 +1,178 @@
+//! Expenses like train tickets or hardware, recorded with Kimai's expenses
+//! plugin.
+use anyhow::Result;
+use chrono::NaiveDate;
+use clap::{Args, Subcommand};
+use clap_complete::ArgValueCandidates;
+use inquire::CustomType;
+use std::io::IsTerminal;
+
+use crate::api::{self, ExpenseForm};
+use crate::format::{self, Align};
+use crate::{clock, complete, config, parse, prompts, report};
+
+const PLUGIN: &str = "ExpensesBundle";
+
+#[derive(Subcommand, Debug)]
+pub enum ExpenseCommand {
+    /// Record an expense on a project
+    Add(AddArgs),
+    /// List the expenses of a period
+    List(ListArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct AddArgs {
+    /// What it cost, e.g. 12.50
+    #[arg(long)]
+    pub amount: Option<f64>,
+    /// Project name or id
+    #[arg(long, add = ArgValueCandidates::new(complete::projects))]
+    pub project: Option<String>,
+    /// Activity name or id, if the expense belongs to one
+    #[arg(long, add = ArgValueCandidates::new(complete::activities))]
+    pub activity: Option<String>,
+    /// Id of the expense category
+    #[arg(long)]
+    pub category: Option<i32>,
+    /// What the expense was for
+    #[arg(long)]
+    pub description: Option<String>,
+    /// Paid out of pocket, to be paid back
+    #[arg(long)]
+    pub refundable: bool,
+    /// Date of the expense, defaults to today
+    #[arg(long, value_parser = parse::date_arg, allow_hyphen_values = true)]
+    pub date: Option<NaiveDate>,
+}
+
+#[derive(Args, Debug)]
+pub struct ListArgs {
+    /// First day to include, defaults to the start of this month
+    #[arg(long, value_parser = parse::date_arg, allow_hyphen_values = true)]
+    pub from: Option<NaiveDate>,
+    /// Last day to include, defaults to the end of this month
+    #[arg(long, value_parser = parse::date_arg, allow_hyphen_values = true)]
+    pub to: Option<NaiveDate>,
+    /// Only show the expenses of this project (name or id)
+    #[arg(long, add = ArgValueCandidates::new(complete::projects))]
+    pub project: Option<String>,
+}
+
+/// Fail early when the server has no expenses, rather than with a 404.
+fn check_plugin(config: &config::Config) -> Result<()> {
+    if !api::has_plugin(config, PLUGIN)? {
+        anyhow::bail!("Kimai has no expenses, the expenses plugin isn't installed");
+    }
+    Ok(())
+}
+
+fn add(config: &config::Config, args: AddArgs) -> Result<()> {
+    let interactive = std::io::stdin().is_terminal();
+    let cost = match args.amount {
+        Some(amount) => amount,
+        None if interactive => CustomType::<f64>::new("Amount:")
+            .with_error_message("Please enter an amount, e.g. 12.50")
+            .prompt()?,
+        None => anyhow::bail!("Pass the amount with --amount"),
+    };
+    if cost <= 0.0 {
+        anyhow::bail!("The amount must be more than zero");
+    }
+    let project = prompts::project(config, args.project.as_deref())?;
+    let activity = match args.activity.as_deref() {
+        Some(needle) => Some(prompts::activity(config, &project, Some(needle))?.id),
+        None => None,
+    };
+    let description = match args.description {
+        Some(description) => description,
+        None if interactive => prompts::description_with_default("")?,
+        None => String::new(),
+    };
+
+    // Today's expense happened now, an earlier one at the start of the day
+    let begin = match args.date {
+        Some(date) if date != clock::today() => {
+            clock::resolve(date.and_time(config.default_start_time))?
+        }
+        _ => clock::now(),
+    };
+    let form = ExpenseForm {
+        begin: begin.to_utc(),
+        project: project.id,
+        activity,
+        category: args.category,
+        description,
+        cost,
+        refundable: args.refundable,
+    };
+    let expense = api::create_expense(config, &form)?;
+    format::note!(
+        "Added expense #{} of {} on {}",
+        expense.id,
+        format::money(expense.cost),
+        project
+    );
+    Ok(())
+}
+
+fn list(config: &config::Config, args: ListArgs) -> Result<()> {
+    let (month_start, month_end) = report::month(clock::today());
+    let from = args.from.unwrap_or(month_start);
+    let to = args.to.unwrap_or(month_end);
+    let project = match args.project.as_deref() {
+        Some(needle) => Some(prompts::project(config, Some(needle))?.id),
+        None => None,
+    };
+    let expenses = api::get_expenses(config, from, to, project)?;
+    if expenses.is_empty() {
+        println!(
+            "No expenses between {} and {}",
+            format::date(from),
+            format::date(to)
+        );
+        return Ok(());
+    }
+
+    let projects = api::get_projects(config)?;
+    let headers = [
+        ("Date", Align::Left),
+        ("Project", Align::Left),
+        ("Description", Align::Left),
+        ("Amount", Align::Right),
+        ("Refundable", Align::Left),
+    ];
+    let mut rows: Vec<Vec<String>> = expenses
+        .iter()
+        .map(|expense| {
+            let project = projects
+                .iter()
+                .find(|p| p.id == expense.project)
+                .map_or_else(|| format!("#{}", expense.project), |p| p.to_string());
+            vec![
+                format::date(expense.begin.date_naive()),
+                project,
+                format::first_line(expense.description.as_deref().unwrap_or_default()),
+                format::money(expense.cost),
+                if expense.refundable { "yes" } else { "no" }.to_string(),
+            ]
+        })
+        .collect();
+    rows.push(vec![
+        "Total".to_string(),
+        String::new(),
+        String::new(),
+        format::money(expenses.iter().map(|e| e.cost).sum()),
+        String::new(),
+    ]);
+    format::table(&headers, &rows);
+    Ok(())
+}
+
+pub fn run(config: &config::Config, command: ExpenseCommand) -> Result<()> {
+    check_plugin(config)?;
+    match command {
+        ExpenseCommand::Add(args) => add(config, args),
+        ExpenseCommand::List(args) => list(config, args),
+    }
+}
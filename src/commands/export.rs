@@ -0,0 +1,225 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use clap::{Args, ValueEnum};
+use rust_xlsxwriter::{Format as CellFormat, Workbook};
+use std::path::{Path, PathBuf};
+
+use crate::api::TimesheetExpanded;
+use crate::{clock, config, parse, report};
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum Format {
+    Csv,
+    Xlsx,
+    /// Check-in and check-out lines for hledger and ledger
+    Timeclock,
+}
+
+#[derive(Args, Debug)]
+pub struct ExportArgs {
+    /// First day to include, defaults to the start of this month
+    #[arg(long, value_parser = parse::date_arg, allow_hyphen_values = true)]
+    pub from: Option<NaiveDate>,
+    /// Last day to include, defaults to the end of this month
+    #[arg(long, value_parser = parse::date_arg, allow_hyphen_values = true)]
+    pub to: Option<NaiveDate>,
+    /// File format; taken from the extension of --out if omitted
+    #[arg(long, value_enum)]
+    pub format: Option<Format>,
+    /// File to write; CSV and timeclock go to stdout if omitted
+    #[arg(long)]
+    pub out: Option<PathBuf>,
+}
+
+const HEADERS: [&str; 11] = [
+    "Date",
+    "Begin",
+    "End",
+    "Customer",
+    "Project",
+    "Activity",
+    "Tags",
+    "Duration",
+    "Hours",
+    "Description",
+    "Rate",
+];
+
+enum Cell {
+    Text(String),
+    Number(f64),
+}
+
+impl Cell {
+    fn text(&self) -> String {
+        match self {
+            Cell::Text(text) => text.clone(),
+            Cell::Number(number) => format!("{:.2}", number),
+        }
+    }
+}
+
+fn row(entry: &TimesheetExpanded) -> Vec<Cell> {
+    let duration = report::entry_duration(entry);
+    vec![
+        Cell::Text(entry.begin.format("%Y-%m-%d").to_string()),
+        Cell::Text(entry.begin.format("%H:%M").to_string()),
+        Cell::Text(
+            entry
+                .end
+                .map(|end| end.format("%H:%M").to_string())
+                .unwrap_or_default(),
+        ),
+        Cell::Text(entry.project.parent_title.clone().unwrap_or_default()),
+        Cell::Text(entry.project.name.clone()),
+        Cell::Text(entry.activity.name.clone()),
+        Cell::Text(entry.tags.join(", ")),
+        Cell::Text(crate::format::duration(duration)),
+        Cell::Number(duration.num_seconds() as f64 / 3600.0),
+        Cell::Text(entry.description.clone().unwrap_or_default()),
+        match entry.rate {
+            Some(rate) => Cell::Number(rate),
+            None => Cell::Text(String::new()),
+        },
+    ]
+}
+
+const TIMECLOCK: &str = "%Y-%m-%d %H:%M:%S";
+
+/// A part of a timeclock account name, which can't contain the separator or
+/// two spaces in a row.
+fn account_part(name: &str) -> String {
+    name.replace(':', "-")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The check-in and check-out lines of a finished entry, with the account
+/// being customer:project:activity.
+fn timeclock(entry: &TimesheetExpanded) -> Option<String> {
+    let end = entry.end?;
+    let account = [
+        entry.project.parent_title.as_deref().unwrap_or_default(),
+        &entry.project.name,
+        &entry.activity.name,
+    ]
+    .iter()
+    .filter(|part| !part.is_empty())
+    .map(|part| account_part(part))
+    .collect::<Vec<_>>()
+    .join(":");
+    let mut check_in = format!("i {} {}", entry.begin.format(TIMECLOCK), account);
+    // Two spaces end the account; the description has to stay on its line
+    let description = entry
+        .description
+        .as_deref()
+        .unwrap_or_default()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    if !description.is_empty() {
+        check_in.push_str("  ");
+        check_in.push_str(&description);
+    }
+    Some(format!("{}\no {}\n", check_in, end.format(TIMECLOCK)))
+}
+
+fn write_timeclock(entries: &[TimesheetExpanded], out: Option<&Path>) -> Result<usize> {
+    let mut writer: Box<dyn std::io::Write> = match out {
+        Some(path) => Box::new(std::fs::File::create(path)?),
+        None => Box::new(std::io::stdout()),
+    };
+    let mut written = 0;
+    for lines in entries.iter().filter_map(timeclock) {
+        writer.write_all(lines.as_bytes())?;
+        written += 1;
+    }
+    writer.flush()?;
+    Ok(written)
+}
+
+fn write_csv(rows: &[Vec<Cell>], out: Option<&Path>) -> Result<()> {
+    let writer: Box<dyn std::io::Write> = match out {
+        Some(path) => Box::new(std::fs::File::create(path)?),
+        None => Box::new(std::io::stdout()),
+    };
+    let mut writer = csv::Writer::from_writer(writer);
+    writer.write_record(HEADERS)?;
+    for row in rows {
+        writer.write_record(row.iter().map(Cell::text))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_xlsx(rows: &[Vec<Cell>], out: &Path) -> Result<()> {
+    let mut workbook = Workbook::new();
+    let sheet = workbook.add_worksheet();
+    let bold = CellFormat::new().set_bold();
+    let decimal = CellFormat::new().set_num_format("0.00");
+
+    for (col, header) in HEADERS.iter().enumerate() {
+        sheet.write_with_format(0, col as u16, *header, &bold)?;
+    }
+    for (index, row) in rows.iter().enumerate() {
+        let line = index as u32 + 1;
+        for (col, cell) in row.iter().enumerate() {
+            match cell {
+                Cell::Text(text) => sheet.write(line, col as u16, text)?,
+                Cell::Number(number) => {
+                    sheet.write_with_format(line, col as u16, *number, &decimal)?
+                }
+            };
+        }
+    }
+    sheet.autofit();
+    workbook.save(out)?;
+    Ok(())
+}
+
+pub fn run(config: &config::Config, args: ExportArgs) -> Result<()> {
+    let (month_start, month_end) = report::month(clock::today());
+    let from = args.from.unwrap_or(month_start);
+    let to = args.to.unwrap_or(month_end);
+
+    let format = match (args.format, &args.out) {
+        (Some(format), _) => format,
+        (None, Some(out)) if out.extension().is_some_and(|ext| ext == "xlsx") => Format::Xlsx,
+        (None, Some(out)) if out.extension().is_some_and(|ext| ext == "timeclock") => {
+            Format::Timeclock
+        }
+        (None, _) => Format::Csv,
+    };
+    if format == Format::Xlsx && args.out.is_none() {
+        anyhow::bail!("XLSX files need an --out path");
+    }
+
+    let mut entries = report::fetch(config, from, to)?;
+    entries.sort_by_key(|e| e.begin);
+    let rows: Vec<Vec<Cell>> = entries.iter().map(row).collect();
+
+    let count = match format {
+        Format::Csv => {
+            write_csv(&rows, args.out.as_deref())?;
+            rows.len()
+        }
+        Format::Xlsx => {
+            write_xlsx(&rows, args.out.as_deref().unwrap())?;
+            rows.len()
+        }
+        // Running timers have no check-out yet and are left out
+        Format::Timeclock => write_timeclock(&entries, args.out.as_deref())?,
+    };
+
+    if let Some(out) = &args.out {
+        println!(
+            "Exported {} entries from {} to {} to {}",
+            count,
+            crate::format::date(from),
+            crate::format::date(to),
+            out.display()
+        );
+    }
+    Ok(())
+}
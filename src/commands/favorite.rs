@@ -0,0 +1,121 @@
+use anyhow::Result;
+use clap::{Subcommand, ValueEnum};
+
+use crate::favorites::{self, Favorites};
+use crate::{api, config, format, prompts};
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum Kind {
+    Project,
+    Activity,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum FavoriteCommand {
+    /// Pin a project or activity to the top of the pickers
+    Add {
+        kind: Kind,
+        /// Name or id; pick one if omitted
+        name: Option<String>,
+        /// Project to look up the activity in, for activities
+        #[arg(long)]
+        project: Option<String>,
+    },
+    /// Unpin a project or activity
+    Remove {
+        kind: Kind,
+        /// Name or id; pick one if omitted
+        name: Option<String>,
+        /// Project to look up the activity in, for activities
+        #[arg(long)]
+        project: Option<String>,
+    },
+    /// List favorites
+    List,
+}
+
+pub fn run(config: &config::Config, command: FavoriteCommand) -> Result<()> {
+    match command {
+        FavoriteCommand::Add {
+            kind,
+            name,
+            project,
+        } => {
+            let mut favorites = favorites::load(config)?;
+            let (id, label) = resolve(config, kind, name.as_deref(), project.as_deref())?;
+            let ids = ids(&mut favorites, kind);
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+            favorites::save(config, &favorites)?;
+            format::note!("Added {} to favorites", label);
+            Ok(())
+        }
+        FavoriteCommand::Remove {
+            kind,
+            name,
+            project,
+        } => {
+            let mut favorites = favorites::load(config)?;
+            let (id, label) = resolve(config, kind, name.as_deref(), project.as_deref())?;
+            ids(&mut favorites, kind).retain(|favorite| *favorite != id);
+            favorites::save(config, &favorites)?;
+            format::note!("Removed {} from favorites", label);
+            Ok(())
+        }
+        FavoriteCommand::List => list(config),
+    }
+}
+
+fn ids(favorites: &mut Favorites, kind: Kind) -> &mut Vec<i32> {
+    match kind {
+        Kind::Project => &mut favorites.projects,
+        Kind::Activity => &mut favorites.activities,
+    }
+}
+
+/// Id and display name of the project or activity meant by `name`.
+fn resolve(
+    config: &config::Config,
+    kind: Kind,
+    name: Option<&str>,
+    project: Option<&str>,
+) -> Result<(i32, String)> {
+    match kind {
+        Kind::Project => {
+            let project = prompts::project(config, name)?;
+            Ok((project.id, project.to_string()))
+        }
+        Kind::Activity => {
+            let project = prompts::project(config, project)?;
+            let activity = prompts::activity(config, &project, name)?;
+            Ok((activity.id, activity.to_string()))
+        }
+    }
+}
+
+fn list(config: &config::Config) -> Result<()> {
+    let favorites = favorites::load(config)?;
+    if favorites.projects.is_empty() && favorites.activities.is_empty() {
+        println!("No favorites yet, add some with `kimai favorite add`");
+        return Ok(());
+    }
+
+    let projects = api::get_projects(config)?;
+    let activities = api::get_activities(config)?;
+    println!("Projects:");
+    for id in &favorites.projects {
+        match projects.iter().find(|p| p.id == *id) {
+            Some(project) => println!("  {}", project),
+            None => println!("  #{} (no longer visible)", id),
+        }
+    }
+    println!("Activities:");
+    for id in &favorites.activities {
+        match activities.iter().find(|a| a.id == *id) {
+            Some(activity) => println!("  {}", activity),
+            None => println!("  #{} (no longer visible)", id),
+        }
+    }
+    Ok(())
+}
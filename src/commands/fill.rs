@@ -0,0 +1,83 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, NaiveDate};
+use clap::Args;
+
+use crate::api::TimesheetEditForm;
+use crate::commands::log;
+use crate::{clock, config, format, parse, prompts, report, rounding};
+
+#[derive(Args, Debug)]
+pub struct FillArgs {
+    /// Day to fill, e.g. 2024-05-01, yesterday or mon; defaults to today
+    #[arg(long, value_parser = parse::date_arg, allow_hyphen_values = true)]
+    pub date: Option<NaiveDate>,
+}
+
+pub fn run(config: &config::Config, args: FillArgs) -> Result<()> {
+    let day = args.date.unwrap_or_else(clock::today);
+    let days_off = report::days_off(config, day, day)?;
+    if let Some(reason) = days_off.get(&day) {
+        anyhow::bail!("{} is a day off ({})", format::day(day), reason);
+    }
+    let target = report::expected(config, day, &days_off);
+    if target <= Duration::zero() {
+        anyhow::bail!("No hours are expected on {}", format::day(day));
+    }
+
+    let entries = report::fetch(config, day, day)?;
+    let mut logged: Duration = entries.iter().map(report::entry_duration).sum();
+    // Continue after the last entry of the day, a running timer ends now
+    let last_end = entries
+        .iter()
+        .map(|e| e.end.unwrap_or_else(|| clock::now().fixed_offset()))
+        .max();
+    let mut begin: DateTime<clock::Zone> = match last_end {
+        Some(end) => end.with_timezone(&clock::zone()),
+        None => clock::resolve(day.and_time(config.default_start_time))?,
+    };
+
+    while logged < target {
+        println!(
+            "{} of {} logged, next entry starts at {}",
+            format::duration(logged),
+            format::duration(target),
+            format::time(begin.time())
+        );
+        let task = prompts::task(config, None, None)?;
+        let duration = prompts::duration(target - logged)?;
+        // Entering 0 is a way to stop early
+        if duration <= Duration::zero() {
+            break;
+        }
+        let description = prompts::description(config, None, &task)?;
+        let tags = prompts::tags(config, Vec::new(), &task.tags)?;
+
+        let mut form = TimesheetEditForm {
+            begin: begin.to_utc(),
+            project: task.project.id,
+            activity: task.activity.id,
+            end: Some((begin + duration).to_utc()),
+            description: Some(description),
+            tags: Some(tags),
+            billable: None,
+            meta_fields: Vec::new(),
+            user: None,
+        };
+        if rounding::apply(config, &mut form) {
+            let end = form.end.unwrap_or(form.begin);
+            println!("Rounded to {}", format::span(form.begin, end));
+        }
+        let end = form.end.unwrap_or(form.begin).with_timezone(&clock::zone());
+        logged += end.to_utc() - form.begin;
+        log::submit(config, form)?;
+        begin = end;
+    }
+
+    println!(
+        "{} of {} logged on {}",
+        format::duration(logged),
+        format::duration(target),
+        format::day(day)
+    );
+    Ok(())
+}
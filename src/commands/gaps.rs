@@ -0,0 +1,137 @@
+//! Time between a day's entries that isn't logged, e.g. after forgetting to
+//! start a timer, and filling it in.
+use anyhow::Result;
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate};
+use clap::Args;
+use inquire::Select;
+use std::io::IsTerminal;
+
+use crate::api::TimesheetEditForm;
+use crate::commands::log;
+use crate::{clock, config, format, parse, prompts, report};
+
+#[derive(Args, Debug)]
+pub struct GapsArgs {
+    /// Day to look at, e.g. yesterday or 2024-05-01; defaults to today
+    #[arg(long, value_parser = parse::date_arg, allow_hyphen_values = true)]
+    pub date: Option<NaiveDate>,
+    /// Only gaps of at least this many minutes, instead of `min_minutes`
+    /// from `[gaps]`
+    #[arg(long)]
+    pub min: Option<i64>,
+    /// Log every gap as a break, on the project and activity from `[gaps]`,
+    /// without asking
+    #[arg(long = "break")]
+    pub as_break: bool,
+}
+
+const LOG: &str = "Log it";
+const BREAK: &str = "Log it as a break";
+const SKIP: &str = "Skip";
+const DONE: &str = "Done";
+
+/// The entry filling the gap from `begin` to `end`.
+fn form(
+    begin: DateTime<FixedOffset>,
+    end: DateTime<FixedOffset>,
+    project: i32,
+    activity: i32,
+    description: String,
+    tags: Vec<String>,
+) -> TimesheetEditForm {
+    TimesheetEditForm {
+        begin: begin.to_utc(),
+        project,
+        activity,
+        end: Some(end.to_utc()),
+        description: Some(description),
+        tags: Some(tags),
+        billable: None,
+        meta_fields: Vec::new(),
+        user: None,
+    }
+}
+
+/// The entry for a break from `begin` to `end`.
+fn break_form(
+    config: &config::Config,
+    gaps: &config::Gaps,
+    begin: DateTime<FixedOffset>,
+    end: DateTime<FixedOffset>,
+) -> Result<TimesheetEditForm> {
+    let Some(project) = gaps.project.as_deref() else {
+        anyhow::bail!("Set the project for breaks with `project` in `[gaps]`");
+    };
+    let project = prompts::project(config, Some(project))?;
+    let activity = prompts::activity(config, &project, gaps.activity.as_deref())?;
+    Ok(form(
+        begin,
+        end,
+        project.id,
+        activity.id,
+        String::new(),
+        Vec::new(),
+    ))
+}
+
+pub fn run(config: &config::Config, args: GapsArgs) -> Result<()> {
+    let day = args.date.unwrap_or_else(clock::today);
+    let settings = config.gaps.clone().unwrap_or_default();
+    let min = Duration::minutes(args.min.unwrap_or(settings.min_minutes).max(1));
+    let entries = report::fetch(config, day, day)?;
+    let gaps = report::gaps(&entries, min);
+    if gaps.is_empty() {
+        println!(
+            "No gaps of {} or more on {}",
+            format::duration(min),
+            format::day(day)
+        );
+        return Ok(());
+    }
+
+    // Without a terminal there's no asking how to fill them
+    if !args.as_break && !std::io::stdin().is_terminal() {
+        for (begin, end) in &gaps {
+            println!("{} not logged", format::span(*begin, *end));
+        }
+        return Ok(());
+    }
+
+    for (begin, end) in gaps {
+        let span = format::span(begin, end);
+        // Gaps are filled exactly, rounding would overlap the entries around them
+        let form = if args.as_break {
+            break_form(config, &settings, begin, end)?
+        } else {
+            println!("{} not logged", span);
+            let mut options = vec![LOG];
+            if settings.project.is_some() {
+                options.push(BREAK);
+            }
+            options.extend([SKIP, DONE]);
+            match Select::new("Fill it?", options).prompt()? {
+                LOG => {
+                    let task = prompts::task(config, None, None)?;
+                    let description = prompts::description(config, None, &task)?;
+                    let tags = prompts::tags(config, Vec::new(), &task.tags)?;
+                    form(
+                        begin,
+                        end,
+                        task.project.id,
+                        task.activity.id,
+                        description,
+                        tags,
+                    )
+                }
+                BREAK => break_form(config, &settings, begin, end)?,
+                SKIP => continue,
+                _ => break,
+            }
+        };
+        log::submit(config, form)?;
+        if !config.dry_run {
+            format::note!("Logged {}", span);
+        }
+    }
+    Ok(())
+}
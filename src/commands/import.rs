@@ -0,0 +1,1014 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use clap::{Args, Subcommand};
+use clap_complete::ArgValueCandidates;
+use inquire::{Confirm, MultiSelect};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::IsTerminal;
+use std::path::PathBuf;
+
+use crate::api::{self, TimesheetEditForm};
+use crate::commands::log;
+use crate::invalid::invalid;
+use crate::{
+    clock, complete, config, format, ics, parse, prompts, report, rounding, state, taskwarrior,
+};
+
+#[derive(Subcommand, Debug)]
+pub enum ImportCommand {
+    /// Log meetings from an iCalendar (.ics) file or URL
+    Ics(IcsArgs),
+    /// Copy entries from the CSV export of a Toggl Track detailed report
+    Toggl(MigrateArgs),
+    /// Copy entries from the CSV export of a Clockify detailed report
+    Clockify(MigrateArgs),
+    /// Create entries from a CSV file with the columns date, start, duration
+    /// or end, project, activity, description and tags
+    Csv {
+        /// The CSV file; its first line names the columns
+        file: PathBuf,
+        /// Don't ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Log the time spent on active and completed Taskwarrior tasks
+    Taskwarrior(TaskwarriorArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct IcsArgs {
+    /// Path or URL of the calendar, e.g. a calendar's secret iCal address
+    pub source: String,
+    /// Day to import, e.g. 2024-05-01, yesterday or mon; defaults to today
+    #[arg(long, value_parser = parse::date_arg, allow_hyphen_values = true)]
+    pub date: Option<NaiveDate>,
+    /// Import the whole week of --date
+    #[arg(long)]
+    pub week: bool,
+    /// Project name or id, instead of the one configured in `[calendar]`
+    #[arg(long, add = ArgValueCandidates::new(complete::projects))]
+    pub project: Option<String>,
+    /// Activity name or id, instead of the one configured in `[calendar]`
+    #[arg(long, add = ArgValueCandidates::new(complete::activities))]
+    pub activity: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct TaskwarriorArgs {
+    /// Day to import, e.g. 2024-05-01, yesterday or mon; defaults to today
+    #[arg(long, value_parser = parse::date_arg, allow_hyphen_values = true)]
+    pub date: Option<NaiveDate>,
+    /// Import the whole week of --date
+    #[arg(long)]
+    pub week: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct MigrateArgs {
+    /// The exported CSV file
+    pub file: PathBuf,
+    /// Format of the dates in the file, e.g. %d/%m/%Y; several common ones
+    /// are tried if omitted
+    #[arg(long)]
+    pub date_format: Option<String>,
+    /// Don't ask for confirmation
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+pub fn run(config: &config::Config, command: ImportCommand) -> Result<()> {
+    match command {
+        ImportCommand::Ics(args) => ics(config, args),
+        ImportCommand::Toggl(args) => migrate(config, "toggl", args),
+        ImportCommand::Clockify(args) => migrate(config, "clockify", args),
+        ImportCommand::Csv { file, yes } => csv(config, &file, yes),
+        ImportCommand::Taskwarrior(args) => taskwarrior(config, args),
+    }
+}
+
+/// Read a calendar from disk, or download it if `source` is a URL.
+fn read_calendar(config: &config::Config, source: &str) -> Result<String> {
+    let url = match source.strip_prefix("webcal://") {
+        Some(rest) => Some(format!("https://{}", rest)),
+        None if source.starts_with("http://") || source.starts_with("https://") => {
+            Some(source.to_string())
+        }
+        None => None,
+    };
+    match url {
+        Some(url) => api::agent(config)?
+            .get(&url)
+            .call()
+            .with_context(|| format!("Could not download {}", source))?
+            .into_string()
+            .with_context(|| format!("Could not read {}", source)),
+        None => {
+            std::fs::read_to_string(source).with_context(|| format!("Could not read {}", source))
+        }
+    }
+}
+
+fn ics(config: &config::Config, args: IcsArgs) -> Result<()> {
+    let date = args.date.unwrap_or_else(clock::today);
+    let (from, to) = if args.week {
+        report::week(date)
+    } else {
+        (date, date)
+    };
+
+    let text = read_calendar(config, &args.source)?;
+    let events = ics::events(&text, from, to)?;
+    if events.is_empty() {
+        format::note!(
+            "No meetings between {} and {}",
+            format::date(from),
+            format::date(to)
+        );
+        return Ok(());
+    }
+
+    // Don't suggest meetings that were imported before
+    let logged = report::fetch(config, from, to)?;
+    let is_logged = |event: &ics::Event| {
+        logged.iter().any(|entry| {
+            entry.begin == event.begin && entry.description.as_deref() == Some(&event.summary)
+        })
+    };
+    let labels: Vec<String> = events
+        .iter()
+        .map(|event| {
+            let label = format!(
+                "{} {}–{} ({}) {}",
+                event.begin.format("%a %d"),
+                format::time(event.begin.time()),
+                format::time(event.end.time()),
+                format::duration(event.end - event.begin),
+                event.summary
+            );
+            if is_logged(event) {
+                format!("{} [logged]", label)
+            } else {
+                label
+            }
+        })
+        .collect();
+    let unlogged: Vec<usize> = (0..events.len())
+        .filter(|i| !is_logged(&events[*i]))
+        .collect();
+
+    let picked = MultiSelect::new("Meetings to log:", labels)
+        .with_default(&unlogged)
+        .raw_prompt()?;
+    if picked.is_empty() {
+        return Ok(());
+    }
+
+    let calendar = config.calendar.as_ref();
+    let project = args
+        .project
+        .as_deref()
+        .or(calendar.map(|c| c.project.as_str()));
+    let project = prompts::project(config, project)?;
+    let activity = args
+        .activity
+        .as_deref()
+        .or(calendar.and_then(|c| c.activity.as_deref()));
+    let activity = prompts::activity(config, &project, activity)?;
+
+    for option in &picked {
+        let event = &events[option.index];
+        let mut form = TimesheetEditForm {
+            begin: event.begin.to_utc(),
+            project: project.id,
+            activity: activity.id,
+            end: Some(event.end.to_utc()),
+            description: Some(event.summary.clone()),
+            tags: None,
+            billable: None,
+            meta_fields: Vec::new(),
+            user: None,
+        };
+        rounding::apply(config, &mut form);
+        log::submit(config, form)?;
+    }
+    format::note!(
+        "Logged {} meeting(s) on {} / {}",
+        picked.len(),
+        project,
+        activity.name
+    );
+    Ok(())
+}
+
+/// A stretch of work on a Taskwarrior task.
+struct Worked<'a> {
+    task: &'a taskwarrior::Task,
+    /// `None` for a task completed without any recorded times, which gets
+    /// asked how long it took.
+    begin: Option<DateTime<Utc>>,
+    end: DateTime<Utc>,
+}
+
+fn taskwarrior(config: &config::Config, args: TaskwarriorArgs) -> Result<()> {
+    let date = args.date.unwrap_or_else(clock::today);
+    let (from, to) = if args.week {
+        report::week(date)
+    } else {
+        (date, date)
+    };
+    let zone = clock::zone();
+    let in_range =
+        |time: &DateTime<Utc>| (from..=to).contains(&time.with_timezone(&zone).date_naive());
+
+    let tasks = taskwarrior::export(&["(", "+ACTIVE", "or", "status:completed", ")"])?;
+    let now = Utc::now();
+    let mut worked = Vec::new();
+    for task in &tasks {
+        let intervals = task.intervals(now);
+        if intervals.is_empty() {
+            if let Some(end) = task.end.filter(in_range) {
+                worked.push(Worked {
+                    task,
+                    begin: None,
+                    end,
+                });
+            }
+        }
+        for (begin, end) in intervals.into_iter().filter(|(begin, _)| in_range(begin)) {
+            worked.push(Worked {
+                task,
+                begin: Some(begin),
+                end,
+            });
+        }
+    }
+    if worked.is_empty() {
+        format::note!(
+            "No tasks worked on between {} and {}",
+            format::date(from),
+            format::date(to)
+        );
+        return Ok(());
+    }
+    worked.sort_by_key(|w| w.begin.unwrap_or(w.end));
+
+    // Don't suggest what was imported before
+    let logged = report::fetch(config, from, to)?;
+    let is_logged = |w: &Worked| {
+        logged.iter().any(|entry| {
+            entry.description.as_deref() == Some(&w.task.description)
+                && match w.begin {
+                    Some(begin) => entry.begin.to_utc() == begin,
+                    None => entry.end.map(|end| end.to_utc()) == Some(w.end),
+                }
+        })
+    };
+    let labels: Vec<String> = worked
+        .iter()
+        .map(|w| {
+            let end = w.end.with_timezone(&zone);
+            let label = match w.begin {
+                Some(begin) => format!(
+                    "{} {} {}",
+                    begin.with_timezone(&zone).format("%a %d"),
+                    format::span(begin, w.end),
+                    w.task.description
+                ),
+                None => format!(
+                    "{} done at {} {}",
+                    end.format("%a %d"),
+                    format::time(end.time()),
+                    w.task.description
+                ),
+            };
+            if is_logged(w) {
+                format!("{} [logged]", label)
+            } else {
+                label
+            }
+        })
+        .collect();
+    let unlogged: Vec<usize> = (0..worked.len())
+        .filter(|i| !is_logged(&worked[*i]))
+        .collect();
+    let picked = MultiSelect::new("Tasks to log:", labels)
+        .with_default(&unlogged)
+        .raw_prompt()?;
+
+    // Tasks of one Taskwarrior project mostly go to the same place, so each
+    // is only asked for once
+    let mut targets: BTreeMap<(Option<String>, Option<String>), Target> = BTreeMap::new();
+    for option in &picked {
+        let w = &worked[option.index];
+        let (project, activity) = w.task.needles(config);
+        let key = (w.task.project.clone(), activity.map(String::from));
+        let target = match targets.get(&key) {
+            Some(target) => *target,
+            None => {
+                if project.is_none() {
+                    println!(
+                        "Where should time on {} go?",
+                        w.task.project.as_deref().unwrap_or(&w.task.description)
+                    );
+                }
+                let project = prompts::project(config, project)?;
+                let activity = prompts::activity(config, &project, activity)?;
+                let target = Target {
+                    project: project.id,
+                    activity: activity.id,
+                };
+                targets.insert(key, target);
+                target
+            }
+        };
+        let begin = match w.begin {
+            Some(begin) => begin,
+            None => {
+                let message = format!("Time spent on {}:", w.task.description);
+                w.end - prompts::duration_with_message(&message, chrono::Duration::hours(1))?
+            }
+        };
+        let mut form = TimesheetEditForm {
+            begin,
+            project: target.project,
+            activity: target.activity,
+            end: Some(w.end),
+            description: Some(w.task.description.clone()),
+            tags: Some(w.task.kimai_tags(config)),
+            billable: None,
+            meta_fields: Vec::new(),
+            user: None,
+        };
+        rounding::apply(config, &mut form);
+        log::submit(config, form)?;
+    }
+    if !picked.is_empty() {
+        format::note!("Logged {} task(s)", picked.len());
+    }
+    Ok(())
+}
+
+/// An entry read from a file, checked and ready to be created.
+struct Pending {
+    /// Line in the file, for error messages.
+    line: usize,
+    begin: DateTime<clock::Zone>,
+    end: DateTime<clock::Zone>,
+    project: i32,
+    activity: i32,
+    description: String,
+    tags: Vec<String>,
+}
+
+/// Create `entries`, skipping those that were imported before, and report
+/// how many were created. Asks first unless `yes` or run without a terminal.
+/// Fails if any entry could not be created.
+fn create(config: &config::Config, entries: Vec<Pending>, yes: bool) -> Result<()> {
+    let (Some(from), Some(to)) = (
+        entries.iter().map(|e| e.begin.date_naive()).min(),
+        entries.iter().map(|e| e.begin.date_naive()).max(),
+    ) else {
+        format::note!("Nothing to import");
+        return Ok(());
+    };
+    let existing = report::fetch(config, from, to)?;
+    let (known, entries): (Vec<Pending>, Vec<Pending>) = entries.into_iter().partition(|entry| {
+        existing.iter().any(|e| {
+            e.begin == entry.begin
+                && e.project.id == entry.project
+                && e.description.as_deref().unwrap_or_default() == entry.description
+        })
+    });
+    if !known.is_empty() {
+        format::note!("Skipping {} entries that already exist", known.len());
+    }
+    if entries.is_empty() {
+        return Ok(());
+    }
+    if !yes
+        && std::io::stdin().is_terminal()
+        && !Confirm::new(&format!(
+            "Create {} entries between {} and {}?",
+            entries.len(),
+            format::date(from),
+            format::date(to)
+        ))
+        .with_default(true)
+        .prompt()?
+    {
+        return Ok(());
+    }
+
+    let mut failed = 0;
+    let mut queued = 0;
+    for entry in &entries {
+        let mut form = TimesheetEditForm {
+            begin: entry.begin.to_utc(),
+            project: entry.project,
+            activity: entry.activity,
+            end: Some(entry.end.to_utc()),
+            description: Some(entry.description.clone()),
+            tags: Some(entry.tags.clone()),
+            billable: None,
+            meta_fields: Vec::new(),
+            user: None,
+        };
+        rounding::apply(config, &mut form);
+        match log::submit(config, form) {
+            Ok(None) if !config.dry_run => queued += 1,
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("Line {}: {}", entry.line, e);
+                failed += 1;
+            }
+        }
+    }
+    if queued > 0 {
+        format::note!(
+            "Created {} entries, {} queued for `kimai sync`, {} failed",
+            entries.len() - failed - queued,
+            queued,
+            failed
+        );
+    } else {
+        format::note!(
+            "Created {} entries, {} failed",
+            entries.len() - failed,
+            failed
+        );
+    }
+    if failed > 0 {
+        anyhow::bail!(
+            "{} of {} entries could not be created",
+            failed,
+            entries.len()
+        );
+    }
+    Ok(())
+}
+
+fn parse_date(value: &str, format: Option<&str>) -> Option<NaiveDate> {
+    let value = value.trim();
+    match format {
+        Some(format) => NaiveDate::parse_from_str(value, format).ok(),
+        None => ["%Y-%m-%d", "%m/%d/%Y", "%d.%m.%Y", "%d/%m/%Y"]
+            .iter()
+            .find_map(|format| NaiveDate::parse_from_str(value, format).ok()),
+    }
+}
+
+fn parse_time(value: &str) -> Option<NaiveTime> {
+    let value = value.trim();
+    ["%H:%M:%S", "%H:%M", "%I:%M:%S %p", "%I:%M %p"]
+        .iter()
+        .find_map(|format| NaiveTime::parse_from_str(value, format).ok())
+}
+
+/// Kimai project and activity that entries of an external project go to.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+struct Target {
+    project: i32,
+    activity: i32,
+}
+
+/// An entry of a Toggl or Clockify export, before it is mapped to Kimai.
+#[derive(Debug)]
+struct Foreign {
+    line: usize,
+    /// Client, project and task, which together pick the Kimai project and
+    /// activity.
+    key: String,
+    begin: DateTime<clock::Zone>,
+    end: DateTime<clock::Zone>,
+    description: String,
+    tags: Vec<String>,
+}
+
+/// Read the rows of a detailed report export. Toggl and Clockify name their
+/// columns alike, apart from the case.
+fn read_export<R: std::io::Read>(
+    mut reader: csv::Reader<R>,
+    date_format: Option<&str>,
+) -> Result<Vec<Foreign>> {
+    let headers = reader.headers()?.clone();
+    let column = |name: &str| {
+        headers
+            .iter()
+            .position(|h| {
+                h.trim_start_matches('\u{feff}')
+                    .trim()
+                    .eq_ignore_ascii_case(name)
+            })
+            .ok_or_else(|| anyhow::anyhow!("The file has no \"{}\" column", name))
+    };
+    let project = column("Project")?;
+    let client = column("Client")?;
+    let task = column("Task").ok();
+    let description = column("Description")?;
+    let start_date = column("Start date")?;
+    let start_time = column("Start time")?;
+    let end_date = column("End date")?;
+    let end_time = column("End time")?;
+    let tags = column("Tags").ok();
+
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        // The header is line 1
+        let line = index + 2;
+        let record = record?;
+        let field = |index: usize| record.get(index).unwrap_or_default().trim();
+        let at = |date: usize, time: usize| {
+            let date = parse_date(field(date), date_format)?;
+            let time = parse_time(field(time))?;
+            Some(date.and_time(time))
+        };
+        let (Some(begin), Some(end)) = (at(start_date, start_time), at(end_date, end_time)) else {
+            errors.push(format!("Line {}: invalid start or end", line));
+            continue;
+        };
+        let (begin, end) = match (clock::resolve(begin), clock::resolve(end)) {
+            (Ok(begin), Ok(end)) => (begin, end),
+            (Err(e), _) | (_, Err(e)) => {
+                errors.push(format!("Line {}: {}", line, e));
+                continue;
+            }
+        };
+        if end <= begin {
+            errors.push(format!("Line {}: the end is not after the start", line));
+            continue;
+        }
+
+        let key: Vec<&str> = [Some(client), Some(project), task]
+            .into_iter()
+            .flatten()
+            .map(field)
+            .filter(|part| !part.is_empty())
+            .collect();
+        entries.push(Foreign {
+            line,
+            key: key.join(" / "),
+            begin,
+            end,
+            description: field(description).to_string(),
+            tags: tags
+                .map(field)
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .map(str::to_string)
+                .collect(),
+        });
+    }
+    if !errors.is_empty() {
+        return Err(invalid!("Nothing was imported:\n{}", errors.join("\n")));
+    }
+    Ok(entries)
+}
+
+fn migrate(config: &config::Config, source: &str, args: MigrateArgs) -> Result<()> {
+    let reader = csv::Reader::from_path(&args.file)
+        .with_context(|| format!("Could not read {}", args.file.display()))?;
+    let entries = read_export(reader, args.date_format.as_deref())?;
+
+    // Remember the mapping, so the rest of a team or a later export doesn't
+    // have to answer the same questions
+    let name = format!("{}-projects", source);
+    let mut mapping: BTreeMap<String, Target> = state::load(config, &name)?;
+    let mut unmapped: Vec<&str> = entries
+        .iter()
+        .map(|e| e.key.as_str())
+        .filter(|key| !mapping.contains_key(*key))
+        .collect();
+    unmapped.sort();
+    unmapped.dedup();
+    for key in unmapped {
+        println!("Where should time on {} go?", key);
+        let project = prompts::project(config, None)?;
+        let activity = prompts::activity(config, &project, None)?;
+        mapping.insert(
+            key.to_string(),
+            Target {
+                project: project.id,
+                activity: activity.id,
+            },
+        );
+        state::save(config, &name, &mapping)?;
+    }
+
+    let entries = entries
+        .into_iter()
+        .map(|entry| {
+            let target = mapping[&entry.key];
+            Pending {
+                line: entry.line,
+                begin: entry.begin,
+                end: entry.end,
+                project: target.project,
+                activity: target.activity,
+                description: entry.description,
+                tags: entry.tags,
+            }
+        })
+        .collect();
+    create(config, entries, args.yes)
+}
+
+/// A row of a CSV file in the documented format, with its project and
+/// activity as written.
+#[derive(Debug)]
+struct Row {
+    line: usize,
+    begin: DateTime<clock::Zone>,
+    end: DateTime<clock::Zone>,
+    project: String,
+    activity: String,
+    description: String,
+    tags: Vec<String>,
+}
+
+/// Check one row of a CSV file in the documented format, `field` giving the
+/// value of a column if it isn't empty.
+fn row(line: usize, field: impl Fn(&str) -> Option<String>) -> Result<Row> {
+    let required = |name: &str| field(name).ok_or_else(|| anyhow::anyhow!("{} is missing", name));
+    let date = required("date")?;
+    let date = NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+        .ok()
+        .with_context(|| format!("invalid date '{}', expected YYYY-MM-DD", date))?;
+    let start = required("start")?;
+    let start = parse_time(&start).with_context(|| format!("invalid start '{}'", start))?;
+    let duration = match (field("duration"), field("end")) {
+        (Some(duration), None) => parse::duration(&duration)
+            .filter(|d| *d > chrono::Duration::zero())
+            .with_context(|| format!("invalid duration '{}'", duration))?,
+        (None, Some(end)) => {
+            let end = parse_time(&end).with_context(|| format!("invalid end '{}'", end))?;
+            parse::elapsed(start, end).map_err(|e| invalid!("{}", e))?
+        }
+        (Some(_), Some(_)) => anyhow::bail!("give either a duration or an end, not both"),
+        (None, None) => anyhow::bail!("duration or end is missing"),
+    };
+    let begin = clock::resolve(date.and_time(start))?;
+
+    Ok(Row {
+        line,
+        begin,
+        end: begin + duration,
+        project: required("project")?,
+        activity: required("activity")?,
+        description: field("description").unwrap_or_default(),
+        tags: field("tags")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(str::to_string)
+            .collect(),
+    })
+}
+
+/// Resolve the project and activity of `row`.
+fn pending(config: &config::Config, row: Row) -> Result<Pending> {
+    let project = prompts::project(config, Some(&row.project))?;
+    let activity = prompts::activity(config, &project, Some(&row.activity))?;
+    Ok(Pending {
+        line: row.line,
+        begin: row.begin,
+        end: row.end,
+        project: project.id,
+        activity: activity.id,
+        description: row.description,
+        tags: row.tags,
+    })
+}
+
+/// The column names of a CSV file in the documented format, in lower case.
+/// Fails if a column that every row needs is missing.
+fn columns<R: std::io::Read>(reader: &mut csv::Reader<R>) -> Result<Vec<String>> {
+    let headers: Vec<String> = reader
+        .headers()?
+        .iter()
+        .map(|h| h.trim_start_matches('\u{feff}').trim().to_lowercase())
+        .collect();
+    for name in ["date", "start", "project", "activity"] {
+        if !headers.iter().any(|h| h == name) {
+            anyhow::bail!("The file has no \"{}\" column", name);
+        }
+    }
+    if !headers.iter().any(|h| h == "duration" || h == "end") {
+        anyhow::bail!("The file needs a \"duration\" or an \"end\" column");
+    }
+    Ok(headers)
+}
+
+fn csv(config: &config::Config, file: &std::path::Path, yes: bool) -> Result<()> {
+    let mut reader = csv::Reader::from_path(file)
+        .with_context(|| format!("Could not read {}", file.display()))?;
+    let headers = columns(&mut reader)?;
+
+    // Check every row before creating anything, so a mistake in the file
+    // doesn't leave half of it imported
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        let line = index + 2;
+        let record = record?;
+        let field = |name: &str| {
+            headers
+                .iter()
+                .position(|h| h == name)
+                .and_then(|i| record.get(i))
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+        match row(line, field).and_then(|row| pending(config, row)) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => errors.push(format!("Line {}: {:#}", line, e)),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(invalid!("Nothing was imported:\n{}", errors.join("\n")));
+    }
+    create(config, entries, yes)
+}
+
+/// The value of a field of a batch entry, as it would be written in CSV.
+fn batch_field(entry: &serde_json::Value, name: &str) -> Option<String> {
+    let value = match entry.get(name)? {
+        serde_json::Value::Null => return None,
+        serde_json::Value::String(text) => text.trim().to_string(),
+        // Tags may be given as a list
+        serde_json::Value::Array(items) => items
+            .iter()
+            .map(|item| {
+                item.as_str()
+                    .map_or_else(|| item.to_string(), str::to_string)
+            })
+            .collect::<Vec<_>>()
+            .join(","),
+        other => other.to_string(),
+    };
+    Some(value).filter(|value| !value.is_empty())
+}
+
+/// Log the entries of `input`, a JSON array of objects or one object per
+/// line, for `kimai log --batch`. Every entry is checked before any is
+/// created; then each one is reported as it's created, or printed as JSON.
+pub fn batch(config: &config::Config, input: &str, user: Option<i32>, json: bool) -> Result<()> {
+    let input = input.trim();
+    let values: Vec<serde_json::Value> = if input.starts_with('[') {
+        serde_json::from_str(input).context("Could not read the entries as JSON")?
+    } else {
+        input
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_json::from_str(line).with_context(|| format!("Line {}", index + 1))
+            })
+            .collect::<Result<_>>()?
+    };
+
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+    for (index, value) in values.iter().enumerate() {
+        let number = index + 1;
+        if !value.is_object() {
+            errors.push(format!("Entry {}: not an object", number));
+            continue;
+        }
+        match row(number, |name| batch_field(value, name)).and_then(|row| pending(config, row)) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => errors.push(format!("Entry {}: {:#}", number, e)),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(invalid!("Nothing was logged:\n{}", errors.join("\n")));
+    }
+    if entries.is_empty() {
+        format::note!("Nothing to log");
+        return Ok(());
+    }
+
+    let total = entries.len();
+    let mut failed = 0;
+    let mut queued = 0;
+    for (index, entry) in entries.into_iter().enumerate() {
+        let progress = format!("[{}/{}]", index + 1, total);
+        let mut form = TimesheetEditForm {
+            begin: entry.begin.to_utc(),
+            project: entry.project,
+            activity: entry.activity,
+            end: Some(entry.end.to_utc()),
+            description: Some(entry.description),
+            tags: Some(entry.tags),
+            billable: None,
+            meta_fields: Vec::new(),
+            user,
+        };
+        rounding::apply(config, &mut form);
+        match log::submit(config, form) {
+            Ok(Some(timesheet)) if json => log::print_json(config, &timesheet),
+            Ok(Some(timesheet)) => format::note!(
+                "{} Entry {}: logged #{} {}",
+                progress,
+                entry.line,
+                timesheet.id,
+                format::span(timesheet.begin, timesheet.end.unwrap_or(timesheet.begin))
+            ),
+            Ok(None) if !config.dry_run => queued += 1,
+            Ok(None) => {}
+            Err(e) => {
+                eprintln!("{} Entry {}: {:#}", progress, entry.line, e);
+                failed += 1;
+            }
+        }
+    }
+    if failed > 0 {
+        anyhow::bail!("{} of {} entries could not be logged", failed, total);
+    }
+    if queued > 0 {
+        format::note!(
+            "Logged {} entries, {} queued for `kimai sync`",
+            total - queued,
+            queued
+        );
+    } else {
+        format::note!("Logged {} entries", total);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The row of `fields`, as `(column, value)`.
+    fn parsed(fields: &[(&str, &str)]) -> Result<Row> {
+        row(2, |name| {
+            fields
+                .iter()
+                .find(|(column, _)| *column == name)
+                .map(|(_, value)| value.to_string())
+        })
+    }
+
+    fn span(row: &Row) -> (String, String) {
+        let time = |t: DateTime<clock::Zone>| t.naive_local().format("%F %R").to_string();
+        (time(row.begin), time(row.end))
+    }
+
+    const BASE: [(&str, &str); 4] = [
+        ("date", "2024-05-01"),
+        ("start", "09:00"),
+        ("project", "Webshop"),
+        ("activity", "Development"),
+    ];
+
+    #[test]
+    fn duration_or_end() {
+        let worked = (
+            "2024-05-01 09:00".to_string(),
+            "2024-05-01 10:30".to_string(),
+        );
+        let by_duration = parsed(&[BASE.as_slice(), &[("duration", "1h30m")]].concat()).unwrap();
+        assert_eq!(span(&by_duration), worked);
+        let by_end = parsed(&[BASE.as_slice(), &[("end", "10:30")]].concat()).unwrap();
+        assert_eq!(span(&by_end), worked);
+        assert_eq!(
+            (by_end.project.as_str(), by_end.activity.as_str()),
+            ("Webshop", "Development")
+        );
+
+        let both = parsed(&[BASE.as_slice(), &[("duration", "1h"), ("end", "10:30")]].concat());
+        assert_eq!(
+            both.unwrap_err().to_string(),
+            "give either a duration or an end, not both"
+        );
+        assert_eq!(
+            parsed(&BASE).unwrap_err().to_string(),
+            "duration or end is missing"
+        );
+        assert!(parsed(&[BASE.as_slice(), &[("duration", "0")]].concat()).is_err());
+        assert!(parsed(&[BASE.as_slice(), &[("end", "09:00")]].concat()).is_err());
+        // An end before the start is on the next day
+        let overnight = parsed(&[BASE.as_slice(), &[("end", "01:00")]].concat()).unwrap();
+        assert_eq!(span(&overnight).1, "2024-05-02 01:00");
+    }
+
+    #[test]
+    fn tags_split_at_commas() {
+        let tags = [("duration", "1h"), ("tags", " review, urgent,,")];
+        let row = parsed(&[BASE.as_slice(), &tags].concat()).unwrap();
+        assert_eq!(row.tags, ["review", "urgent"]);
+        assert_eq!(row.description, "");
+    }
+
+    /// Key, span and tags of the entries of an exported `file`.
+    fn exported(
+        file: &str,
+        date_format: Option<&str>,
+    ) -> Vec<(String, (String, String), Vec<String>)> {
+        let reader = csv::Reader::from_reader(file.as_bytes());
+        read_export(reader, date_format)
+            .unwrap()
+            .into_iter()
+            .map(|entry| {
+                let time = |t: DateTime<clock::Zone>| t.naive_local().format("%F %R").to_string();
+                (entry.key, (time(entry.begin), time(entry.end)), entry.tags)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn toggl_export() {
+        let file = "\u{feff}User,Email,Client,Project,Task,Description,Billable,Start date,\
+                    Start time,End date,End time,Duration,Tags,Amount ()\n\
+                    Anna,anna@example.com,Acme,Webshop,Checkout,Fix cart,Yes,2024-05-01,\
+                    09:00:00,2024-05-01,10:30:00,01:30:00,\"review, urgent\",\n\
+                    Anna,anna@example.com,,Internal,,Standup,No,2024-05-01,\
+                    23:45:00,2024-05-02,00:15:00,00:30:00,,\n";
+        assert_eq!(
+            exported(file, None),
+            [
+                (
+                    "Acme / Webshop / Checkout".to_string(),
+                    (
+                        "2024-05-01 09:00".to_string(),
+                        "2024-05-01 10:30".to_string()
+                    ),
+                    vec!["review".to_string(), "urgent".to_string()],
+                ),
+                (
+                    "Internal".to_string(),
+                    (
+                        "2024-05-01 23:45".to_string(),
+                        "2024-05-02 00:15".to_string()
+                    ),
+                    vec![],
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn clockify_export() {
+        let file = "Project,Client,Description,Task,User,Group,Email,Tags,Billable,\
+                    Start Date,Start Time,End Date,End Time,Duration (h),Duration (decimal)\n\
+                    Webshop,Acme,Fix cart,,Anna,,anna@example.com,review,Yes,\
+                    05/01/2024,09:00:00 AM,05/01/2024,01:15:00 PM,04:15:00,4.25\n";
+        let entries = exported(file, None);
+        assert_eq!(entries[0].0, "Acme / Webshop");
+        assert_eq!(
+            entries[0].1,
+            (
+                "2024-05-01 09:00".to_string(),
+                "2024-05-01 13:15".to_string()
+            )
+        );
+
+        // Days first, as exported in most of Europe
+        let file = file.replace("05/01/2024", "01/05/2024");
+        assert_eq!(exported(&file, Some("%d/%m/%Y"))[0].1, entries[0].1);
+    }
+
+    #[test]
+    fn export_with_bad_rows() {
+        let file = "Client,Project,Description,Start date,Start time,End date,End time\n\
+                    Acme,Webshop,,2024-05-01,10:00,2024-05-01,09:00\n\
+                    Acme,Webshop,,2024-05-01,soon,2024-05-01,09:00\n";
+        let reader = csv::Reader::from_reader(file.as_bytes());
+        assert_eq!(
+            read_export(reader, None).unwrap_err().to_string(),
+            "Nothing was imported:\n\
+             Line 2: the end is not after the start\n\
+             Line 3: invalid start or end"
+        );
+
+        let reader = csv::Reader::from_reader("Project,Description\n".as_bytes());
+        assert_eq!(
+            read_export(reader, None).unwrap_err().to_string(),
+            "The file has no \"Client\" column"
+        );
+    }
+
+    #[test]
+    fn columns_despite_byte_order_mark() {
+        let file = "\u{feff}Date,Start,Duration,Project,Activity\n2024-05-01,09:00,1h,A,B\n";
+        let mut reader = csv::Reader::from_reader(file.as_bytes());
+        assert_eq!(
+            columns(&mut reader).unwrap(),
+            ["date", "start", "duration", "project", "activity"]
+        );
+
+        let file = "date,start,project,activity\n";
+        let mut reader = csv::Reader::from_reader(file.as_bytes());
+        assert_eq!(
+            columns(&mut reader).unwrap_err().to_string(),
+            "The file needs a \"duration\" or an \"end\" column"
+        );
+    }
+}
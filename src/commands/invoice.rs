@@ -0,0 +1,151 @@
+use anyhow::Result;
+use chrono::{Duration, NaiveDate, NaiveTime};
+use clap::{Args, Subcommand};
+use std::collections::BTreeMap;
+
+use crate::api::{self, TimesheetQuery};
+use crate::format::{self, Align};
+use crate::{clock, config, parse, prompts, report};
+
+#[derive(Subcommand, Debug)]
+pub enum InvoiceCommand {
+    /// List invoices created in Kimai
+    List {
+        /// Only show invoices of this customer (name or id)
+        #[arg(long)]
+        customer: Option<String>,
+    },
+    /// Show what would be invoiced to a customer for a period
+    Preview(PreviewArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct PreviewArgs {
+    /// Customer name or id
+    #[arg(long)]
+    pub customer: Option<String>,
+    /// First day to include, defaults to the start of this month
+    #[arg(long, value_parser = parse::date_arg, allow_hyphen_values = true)]
+    pub from: Option<NaiveDate>,
+    /// Last day to include, defaults to the end of this month
+    #[arg(long, value_parser = parse::date_arg, allow_hyphen_values = true)]
+    pub to: Option<NaiveDate>,
+}
+
+pub fn run(config: &config::Config, command: InvoiceCommand) -> Result<()> {
+    match command {
+        InvoiceCommand::List { customer } => list(config, customer.as_deref()),
+        InvoiceCommand::Preview(args) => preview(config, args),
+    }
+}
+
+fn list(config: &config::Config, customer: Option<&str>) -> Result<()> {
+    let customer = match customer {
+        Some(needle) => Some(prompts::customer(config, Some(needle))?.id),
+        None => None,
+    };
+    let invoices = api::get_invoices(config, customer)?;
+    if invoices.is_empty() {
+        println!("No invoices");
+        return Ok(());
+    }
+
+    let headers = [
+        ("Number", Align::Left),
+        ("Date", Align::Left),
+        ("Customer", Align::Left),
+        ("Total", Align::Right),
+        ("Status", Align::Left),
+    ];
+    let rows: Vec<Vec<String>> = invoices
+        .iter()
+        .map(|invoice| {
+            vec![
+                invoice.invoice_number.clone(),
+                format::date(invoice.created_at.date_naive()),
+                invoice
+                    .customer
+                    .as_ref()
+                    .map(|c| c.name.clone())
+                    .unwrap_or_default(),
+                format!("{} {}", format::money(invoice.total), invoice.currency),
+                invoice.status.clone(),
+            ]
+        })
+        .collect();
+    format::table(&headers, &rows);
+    Ok(())
+}
+
+/// Billable entries not exported yet are what Kimai puts on the next
+/// invoice, so sum those up per project and activity.
+fn preview(config: &config::Config, args: PreviewArgs) -> Result<()> {
+    let customer = prompts::customer(config, args.customer.as_deref())?;
+    let (month_start, month_end) = report::month(clock::today());
+    let from = args.from.unwrap_or(month_start);
+    let to = args.to.unwrap_or(month_end);
+
+    let entries = api::get_timesheets(
+        config,
+        &TimesheetQuery {
+            begin: Some(from.and_time(NaiveTime::MIN)),
+            end: Some(to.and_hms_opt(23, 59, 59).unwrap()),
+            customer: Some(customer.id),
+            billable: Some(true),
+            exported: Some(false),
+            ..Default::default()
+        },
+    )?;
+    if entries.is_empty() {
+        println!(
+            "Nothing to invoice to {} between {} and {}",
+            customer,
+            format::date(from),
+            format::date(to)
+        );
+        return Ok(());
+    }
+
+    let mut lines: BTreeMap<(String, String), (Duration, f64)> = BTreeMap::new();
+    for entry in &entries {
+        let line = lines
+            .entry((entry.project.name.clone(), entry.activity.name.clone()))
+            .or_insert((Duration::zero(), 0.0));
+        line.0 += report::entry_duration(entry);
+        line.1 += entry.rate.unwrap_or_default();
+    }
+
+    let headers = [
+        ("Project", Align::Left),
+        ("Activity", Align::Left),
+        ("Hours", Align::Right),
+        ("Amount", Align::Right),
+    ];
+    let mut rows: Vec<Vec<String>> = lines
+        .iter()
+        .map(|((project, activity), (duration, amount))| {
+            vec![
+                project.clone(),
+                activity.clone(),
+                format::duration(*duration),
+                format::money(*amount),
+            ]
+        })
+        .collect();
+    rows.push(vec![
+        "Total".to_string(),
+        String::new(),
+        format::duration(lines.values().map(|(duration, _)| *duration).sum()),
+        format::money(lines.values().map(|(_, amount)| amount).sum()),
+    ]);
+
+    println!(
+        "{}, {} – {}, {} entries",
+        customer,
+        format::date(from),
+        format::date(to),
+        entries.len()
+    );
+    format::table(&headers, &rows);
+    Ok(())
+}
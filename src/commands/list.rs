@@ -0,0 +1,123 @@
+use anyhow::Result;
+use chrono::{NaiveDate, NaiveTime};
+use clap::Args;
+use clap_complete::ArgValueCandidates;
+
+use crate::api::{self, TimesheetExpanded, TimesheetQuery};
+use crate::format::{self, Align, Style};
+use crate::{clock, complete, config, parse, prompts, report};
+
+#[derive(Args, Debug)]
+pub struct ListArgs {
+    /// First day to include, e.g. 2024-05-01 or mon, defaults to the start of this week
+    #[arg(long, value_parser = parse::date_arg, allow_hyphen_values = true)]
+    pub from: Option<NaiveDate>,
+    /// Last day to include, e.g. 2024-05-01 or today, defaults to the end of this week
+    #[arg(long, value_parser = parse::date_arg, allow_hyphen_values = true)]
+    pub to: Option<NaiveDate>,
+    /// Only show entries for this project (name or id)
+    #[arg(long, add = ArgValueCandidates::new(complete::projects))]
+    pub project: Option<String>,
+    /// Only show billable entries
+    #[arg(long)]
+    pub billable_only: bool,
+    /// Only show entries that weren't exported yet, and can still be changed
+    #[arg(long)]
+    pub not_exported: bool,
+    /// Show another user's entries (id, username or alias)
+    #[arg(long)]
+    pub user: Option<String>,
+    /// Maximum number of entries to show
+    #[arg(long, default_value_t = 50)]
+    pub limit: usize,
+}
+
+pub fn run(config: &config::Config, args: ListArgs) -> Result<()> {
+    let (week_start, week_end) = report::week(clock::today());
+    let from = args.from.unwrap_or(week_start);
+    let to = args.to.unwrap_or(week_end);
+
+    let project = match &args.project {
+        Some(needle) => Some(prompts::project(config, Some(needle))?.id),
+        None => None,
+    };
+    let user = match &args.user {
+        Some(needle) => Some(prompts::user(config, needle)?.id),
+        None => None,
+    };
+
+    let timesheets = api::get_timesheets(
+        config,
+        &TimesheetQuery {
+            begin: Some(from.and_time(NaiveTime::MIN)),
+            end: Some(to.and_hms_opt(23, 59, 59).unwrap()),
+            project,
+            billable: args.billable_only.then_some(true),
+            exported: args.not_exported.then_some(false),
+            user,
+            limit: Some(args.limit),
+            ..Default::default()
+        },
+    )?;
+
+    if timesheets.is_empty() {
+        println!(
+            "No entries between {} and {}",
+            format::date(from),
+            format::date(to)
+        );
+        return Ok(());
+    }
+
+    // Most entries are billable and not exported, so only show the column
+    // when it says something
+    let flags = |t: &TimesheetExpanded| {
+        let mut flags = Vec::new();
+        if t.exported {
+            flags.push("exported");
+        }
+        if !t.billable {
+            flags.push("not billable");
+        }
+        flags.join(", ")
+    };
+    let show_flags = timesheets.iter().any(|t| !flags(t).is_empty());
+
+    let rows: Vec<Vec<String>> = timesheets
+        .iter()
+        .map(|t| {
+            let mut row = vec![
+                t.id.to_string(),
+                format::day(t.begin.date_naive()),
+                format::project(&t.project.name),
+                t.activity.name.clone(),
+                if t.end.is_some() {
+                    format::duration(chrono::Duration::seconds(t.duration))
+                } else {
+                    format::paint("running", Style::Green)
+                },
+            ];
+            if show_flags {
+                row.push(format::paint(&flags(t), Style::Yellow));
+            }
+            row.push(format::first_line(
+                t.description.as_deref().unwrap_or_default(),
+            ));
+            row
+        })
+        .collect();
+
+    let mut headers = vec![
+        ("ID", Align::Right),
+        ("Date", Align::Left),
+        ("Project", Align::Left),
+        ("Activity", Align::Left),
+        ("Duration", Align::Right),
+    ];
+    if show_flags {
+        headers.push(("Flags", Align::Left));
+    }
+    headers.push(("Description", Align::Left));
+    format::table(&headers, &rows);
+    Ok(())
+}
@@ -0,0 +1,829 @@
+use anyhow::{Context, Result};
+use chrono::{Duration, NaiveDate, NaiveTime};
+use clap::Args;
+use clap_complete::ArgValueCandidates;
+use inquire::{Confirm, InquireError, Select};
+use std::io::{IsTerminal, Read};
+
+use crate::api::{self, TimesheetEditForm};
+use crate::commands::{import, open, undo};
+use crate::format::Style;
+use crate::invalid::invalid;
+use crate::parse::Length;
+use crate::{
+    budget, caps, clock, complete, config, format, git, integrations, parse, prompts, queue,
+    rounding, taskwarrior,
+};
+
+#[derive(Args, Debug, Default, Clone)]
+pub struct LogArgs {
+    /// Project name or id
+    #[arg(long, add = ArgValueCandidates::new(complete::projects))]
+    pub project: Option<String>,
+    /// Activity name or id
+    #[arg(long, add = ArgValueCandidates::new(complete::activities))]
+    pub activity: Option<String>,
+    /// Split the time into consecutive entries on several activities, given
+    /// comma separated or picked when left out
+    #[arg(
+        long,
+        num_args = 0..,
+        value_delimiter = ',',
+        conflicts_with = "activity",
+        add = ArgValueCandidates::new(complete::activities)
+    )]
+    pub activities: Option<Vec<String>>,
+    /// Duration, e.g. 1.5, 2:30, 90m or 1h30m
+    #[arg(long, value_parser = parse::duration_arg, conflicts_with = "end")]
+    pub duration: Option<Duration>,
+    /// Date of the entry, e.g. 2024-05-01, yesterday, mon or -2
+    #[arg(long, value_parser = parse::date_arg, allow_hyphen_values = true)]
+    pub date: Option<NaiveDate>,
+    /// Start time, e.g. 9:30 or 9:30am
+    #[arg(long, value_parser = parse::time_arg)]
+    pub start: Option<NaiveTime>,
+    /// End time, instead of a duration; before the start time means the next
+    /// day
+    #[arg(long, value_parser = parse::time_arg)]
+    pub end: Option<NaiveTime>,
+    /// Description of the entry, `-` to read it from stdin
+    #[arg(long, allow_hyphen_values = true)]
+    pub description: Option<String>,
+    /// Write the description in $EDITOR, e.g. for several lines
+    #[arg(long)]
+    pub edit_description: bool,
+    /// Offer the git branch and recent commits as descriptions, and pick the
+    /// project by the branch name if a rule matches
+    #[arg(long)]
+    pub from_git: bool,
+    /// Take the project, activity, description and tags that aren't given
+    /// from a Taskwarrior task, by uuid or id
+    #[arg(long)]
+    pub from_task: Option<String>,
+    /// Tags for the entry, may be repeated or comma separated
+    #[arg(long = "tag", value_delimiter = ',')]
+    pub tags: Vec<String>,
+    /// Custom field value as name=value, may be repeated
+    #[arg(long = "meta", value_parser = parse::meta_arg)]
+    pub meta: Vec<(String, String)>,
+    /// Mark the entry as billable, instead of asking
+    #[arg(long, conflicts_with = "not_billable")]
+    pub billable: bool,
+    /// Mark the entry as not billable
+    #[arg(long)]
+    pub not_billable: bool,
+    /// Log for another user (id, username or alias), e.g. as a team lead
+    #[arg(long)]
+    pub user: Option<String>,
+    /// Log even if the entry goes over a monthly cap
+    #[arg(long)]
+    pub force: bool,
+    /// Print the created entries as JSON, one per line, instead of what was
+    /// done
+    #[arg(long)]
+    pub json: bool,
+    /// Log the entries of a JSON array or JSON lines read from stdin, with
+    /// the fields of `kimai import csv`
+    #[arg(
+        long,
+        conflicts_with_all = ["project", "activity", "activities", "duration", "date", "start", "end", "description", "from_task"]
+    )]
+    pub batch: bool,
+}
+
+pub fn run(config: &config::Config, mut args: LogArgs) -> Result<()> {
+    if args.batch {
+        let mut input = String::new();
+        std::io::stdin()
+            .read_to_string(&mut input)
+            .context("Could not read the entries from stdin")?;
+        let user = match &args.user {
+            Some(needle) => Some(prompts::user(config, needle)?.id),
+            None => None,
+        };
+        if args.json {
+            format::set_quiet(true);
+        }
+        return import::batch(config, &input, user, args.json);
+    }
+    if args.description.as_deref() == Some("-") {
+        let mut description = String::new();
+        std::io::stdin()
+            .read_to_string(&mut description)
+            .context("Could not read the description from stdin")?;
+        args.description = Some(description.trim_end().to_string());
+    }
+
+    // Further entries of the session keep the flags and choices given, but
+    // ask for their own time and description
+    let session = LogArgs {
+        duration: None,
+        start: None,
+        end: None,
+        description: None,
+        from_task: None,
+        ..args.clone()
+    };
+
+    if let Some(uuid) = &args.from_task {
+        let task = taskwarrior::task(uuid)?;
+        let (project, activity) = task.needles(config);
+        args.project = args.project.or(project.map(String::from));
+        if args.activities.is_none() {
+            args.activity = args.activity.or(activity.map(String::from));
+        }
+        args.description = args.description.or(Some(task.description.clone()));
+        if args.tags.is_empty() {
+            args.tags = task.kimai_tags(config);
+        }
+    }
+
+    // Only offer another round when the user is answering prompts anyway
+    let interactive = prompted(&args);
+    if args.json {
+        format::set_quiet(true);
+    }
+
+    // The same user for every entry of the session
+    let user = match &args.user {
+        Some(needle) => Some(prompts::user(config, needle)?.id),
+        None => None,
+    };
+    let mut last = log_entry(config, args, user, None)?;
+    while interactive
+        && Confirm::new("Log another entry?")
+            .with_default(true)
+            .prompt()?
+    {
+        last = log_entry(config, session.clone(), user, Some(last))?;
+    }
+    Ok(())
+}
+
+/// Whether logging with `args` asks for anything.
+fn prompted(args: &LogArgs) -> bool {
+    std::io::stdin().is_terminal()
+        && (args.project.is_none()
+            || match &args.activities {
+                Some(given) => given.is_empty(),
+                None => args.activity.is_none(),
+            }
+            || (args.duration.is_none() && args.end.is_none())
+            || args.date.is_none()
+            || (args.description.is_none() && !args.edit_description))
+}
+
+/// The answers for an entry, kept apart so any of them can be changed
+/// before it's logged.
+struct Draft {
+    task: prompts::Task,
+    /// With `--activities`, the activities to split the time between.
+    activities: Vec<api::Activity>,
+    date: NaiveDate,
+    start: NaiveTime,
+    duration: Duration,
+    description: String,
+    tags: Vec<String>,
+    meta_fields: Vec<api::MetaValue>,
+    billable: Option<bool>,
+}
+
+impl Draft {
+    fn form(&self, user: Option<i32>) -> Result<TimesheetEditForm> {
+        let begin = clock::resolve(self.date.and_time(self.start))?;
+        Ok(TimesheetEditForm {
+            begin: begin.to_utc(),
+            project: self.task.project.id,
+            activity: self.task.activity.id,
+            end: Some((begin + self.duration).to_utc()),
+            description: Some(self.description.clone()),
+            tags: Some(self.tags.clone()),
+            billable: self.billable,
+            meta_fields: self.meta_fields.clone(),
+            user,
+        })
+    }
+
+    /// Print what is about to be logged as `form`.
+    fn print(&self, form: &TimesheetEditForm, rounded: bool) {
+        let end = form.end.unwrap_or(form.begin);
+        let mut time = format::span(form.begin, end);
+        if rounded {
+            time.push_str(" (rounded)");
+        }
+        let tags = if self.tags.is_empty() {
+            "none".to_string()
+        } else {
+            self.tags.join(", ")
+        };
+        let mut rows = vec![
+            ("Project", self.task.project.to_string()),
+            ("Activity", self.task.activity.name.clone()),
+            ("Date", format::day(self.date)),
+            ("Time", time),
+            ("Description", format::first_line(&self.description)),
+            ("Tags", tags),
+        ];
+        if let Some(billable) = self.billable {
+            rows.push(("Billable", if billable { "yes" } else { "no" }.to_string()));
+        }
+        for (label, value) in rows {
+            let label = format!("{:<12}", label);
+            println!("  {} {}", format::paint(&label, Style::Dim), value);
+        }
+    }
+}
+
+const LOG: &str = "Log it";
+const CHANGE_TASK: &str = "Change project and activity";
+const CHANGE_DATE: &str = "Change date";
+const CHANGE_TIME: &str = "Change time";
+const CHANGE_DESCRIPTION: &str = "Change description";
+const CHANGE_TAGS: &str = "Change tags";
+const CHANGE_BILLABLE: &str = "Change billable";
+const CANCEL: &str = "Cancel";
+
+/// Show the entry and let the user log it, or change any of the answers
+/// first. Returns the form to log.
+fn confirm(
+    config: &config::Config,
+    mut draft: Draft,
+    user: Option<i32>,
+) -> Result<TimesheetEditForm> {
+    loop {
+        let mut form = draft.form(user)?;
+        let rounded = rounding::apply(config, &mut form);
+        draft.print(&form, rounded);
+
+        let mut options = vec![
+            LOG,
+            CHANGE_TASK,
+            CHANGE_DATE,
+            CHANGE_TIME,
+            CHANGE_DESCRIPTION,
+            CHANGE_TAGS,
+        ];
+        if draft.billable.is_some() {
+            options.push(CHANGE_BILLABLE);
+        }
+        options.push(CANCEL);
+        match Select::new("Log this entry?", options).prompt()? {
+            LOG => return Ok(form),
+            CHANGE_TASK => {
+                draft.task = prompts::task(config, None, None)?;
+                budget::check(config, &draft.task.project)?;
+            }
+            CHANGE_DATE => draft.date = prompts::date(draft.date)?,
+            CHANGE_TIME => match prompts::length(draft.duration)? {
+                Length::Duration(duration) => {
+                    draft.duration = duration;
+                    draft.start = prompts::start_time(draft.start)?;
+                }
+                Length::Range(start, end) => {
+                    draft.start = start;
+                    draft.duration = parse::elapsed(start, end).map_err(|e| invalid!("{}", e))?;
+                }
+            },
+            CHANGE_DESCRIPTION => {
+                draft.description = prompts::description_for(
+                    config,
+                    draft.task.project.id,
+                    draft.task.activity.id,
+                    &draft.description,
+                )?;
+            }
+            CHANGE_TAGS => draft.tags = prompts::tags(config, Vec::new(), &draft.tags)?,
+            CHANGE_BILLABLE => {
+                draft.billable = Some(
+                    Confirm::new("Billable?")
+                        .with_default(draft.billable.unwrap_or(true))
+                        .prompt()?,
+                );
+            }
+            _ => anyhow::bail!("Nothing was logged"),
+        }
+    }
+}
+
+/// The questions asked for an entry, in order.
+#[derive(Clone, Copy)]
+enum Step {
+    Task,
+    Length,
+    Date,
+    Start,
+    Description,
+    Tags,
+    Meta,
+    Billable,
+}
+
+const STEPS: [Step; 8] = [
+    Step::Task,
+    Step::Length,
+    Step::Date,
+    Step::Start,
+    Step::Description,
+    Step::Tags,
+    Step::Meta,
+    Step::Billable,
+];
+
+/// The answers so far. Going back to a question starts from its previous
+/// answer.
+#[derive(Default)]
+struct Answers {
+    task: Option<prompts::Task>,
+    activities: Vec<api::Activity>,
+    length: Option<Length>,
+    date: Option<NaiveDate>,
+    start: Option<NaiveTime>,
+    description: Option<String>,
+    tags: Option<Vec<String>>,
+    meta_fields: Option<Vec<api::MetaValue>>,
+    billable: Option<Option<bool>>,
+}
+
+impl Answers {
+    fn task(&self) -> &prompts::Task {
+        self.task.as_ref().expect("the task is asked first")
+    }
+
+    fn draft(self, config: &config::Config) -> Result<Draft> {
+        let (
+            Some(task),
+            Some(length),
+            Some(date),
+            Some(start),
+            Some(description),
+            Some(tags),
+            Some(meta_fields),
+            Some(billable),
+        ) = (
+            self.task,
+            self.length,
+            self.date,
+            self.start,
+            self.description,
+            self.tags,
+            self.meta_fields,
+            self.billable,
+        )
+        else {
+            unreachable!("every question is answered");
+        };
+        let duration = match length {
+            Length::Duration(duration) => duration,
+            Length::Range(_, end) => parse::elapsed(start, end).map_err(|e| invalid!("{}", e))?,
+        };
+        Ok(Draft {
+            task,
+            activities: self.activities,
+            date,
+            start,
+            duration,
+            description: integrations::expand(config, description),
+            tags,
+            meta_fields,
+            billable,
+        })
+    }
+}
+
+/// Answer `step` from `args`, or ask for it. Returns whether anything was
+/// asked, so Esc on the next question can go back to it.
+fn answer(
+    config: &config::Config,
+    args: &LogArgs,
+    git: Option<&git::Context>,
+    previous: Option<(NaiveDate, NaiveTime)>,
+    step: Step,
+    answers: &mut Answers,
+) -> Result<bool> {
+    let terminal = std::io::stdin().is_terminal();
+    let asked = match step {
+        Step::Task => {
+            let rule = match git {
+                Some(git) => git.rule(config)?,
+                None => None,
+            };
+            let (project, activity) =
+                git::needles(rule, args.project.as_deref(), args.activity.as_deref());
+            let task = if let Some(given) = &args.activities {
+                let (task, activities) = prompts::split_task(config, project, given)?;
+                answers.activities = activities;
+                task
+            } else {
+                match answers.task.take() {
+                    Some(current) if project.is_none() && activity.is_none() => {
+                        prompts::task_with_default(config, current)?
+                    }
+                    _ => prompts::task(config, project, activity)?,
+                }
+            };
+            budget::check(config, &task.project)?;
+            answers.task = Some(task);
+            match &args.activities {
+                Some(given) => project.is_none() || given.is_empty(),
+                None => project.is_none() || activity.is_none(),
+            }
+        }
+        Step::Length => {
+            let (length, asked) = match (args.duration, args.end) {
+                (Some(duration), _) => (Length::Duration(duration), false),
+                (None, Some(end)) => (Length::Range(config.default_start_time, end), false),
+                (None, None) => {
+                    let default = match answers.length {
+                        Some(Length::Duration(duration)) => duration,
+                        Some(Length::Range(start, end)) => {
+                            parse::elapsed(start, end).unwrap_or(Duration::hours(1))
+                        }
+                        None => Duration::hours(1),
+                    };
+                    (prompts::length(default)?, true)
+                }
+            };
+            answers.length = Some(length);
+            asked
+        }
+        Step::Date => {
+            let (date, asked) = match args.date {
+                Some(date) => (date, false),
+                None => {
+                    let default = answers
+                        .date
+                        .or(previous.map(|(date, _)| date))
+                        .unwrap_or_else(clock::today);
+                    (prompts::date(default)?, true)
+                }
+            };
+            answers.date = Some(date);
+            asked
+        }
+        Step::Start => {
+            let (start, asked) = match answers.length {
+                Some(Length::Duration(duration)) => match args.start {
+                    Some(start) => (start, false),
+                    None => {
+                        // Continue where the previous entry ended. Otherwise, if the selected date
+                        // is the current date, default to current time minus duration, or else use
+                        // the configured value.
+                        let default_time = if let Some(start) = answers.start {
+                            start
+                        } else if let Some((_, end)) = previous {
+                            end
+                        } else if answers.date == Some(clock::today()) {
+                            clock::now().time() - duration
+                        } else {
+                            config.default_start_time
+                        };
+                        (prompts::start_time(default_time)?, true)
+                    }
+                },
+                // With only --end given the start time is still unknown here
+                Some(Length::Range(range_start, _)) => match (args.start, args.end) {
+                    (None, Some(_)) => {
+                        let default = answers.start.unwrap_or(config.default_start_time);
+                        (prompts::start_time(default)?, true)
+                    }
+                    (Some(start), _) => (start, false),
+                    (None, None) => (range_start, false),
+                },
+                None => unreachable!("the length is asked before the start"),
+            };
+            answers.start = Some(start);
+            asked
+        }
+        Step::Description => {
+            let task = answers.task();
+            let initial = answers.description.as_deref().unwrap_or(&task.description);
+            let (description, asked) = match (&args.description, git) {
+                (given, _) if args.edit_description => {
+                    let initial = answers
+                        .description
+                        .as_deref()
+                        .or(given.as_deref())
+                        .unwrap_or(&task.description);
+                    (
+                        prompts::description_in_editor(config, task.project.id, initial)?,
+                        true,
+                    )
+                }
+                (Some(given), _) => (given.clone(), false),
+                (None, Some(git)) => (
+                    prompts::description_from(
+                        config,
+                        task.project.id,
+                        task.activity.id,
+                        &git.descriptions(),
+                        initial,
+                    )?,
+                    true,
+                ),
+                (None, None) => (
+                    prompts::description_for(config, task.project.id, task.activity.id, initial)?,
+                    true,
+                ),
+            };
+            answers.description = Some(description);
+            asked
+        }
+        Step::Tags => {
+            let current = answers.tags.as_deref().unwrap_or(&answers.task().tags);
+            let tags = prompts::tags(config, args.tags.clone(), current)?;
+            answers.tags = Some(tags);
+            args.tags.is_empty() && terminal
+        }
+        Step::Meta => {
+            let meta_fields = prompts::meta_fields(config, args.meta.clone())?;
+            // Only required fields missing from --meta are asked for
+            let asked = meta_fields.len() > args.meta.len();
+            answers.meta_fields = Some(meta_fields);
+            asked
+        }
+        Step::Billable => {
+            let billable = prompts::billable(answers.task(), args.billable, args.not_billable)?;
+            answers.billable = Some(billable);
+            !args.billable && !args.not_billable && terminal
+        }
+    };
+    Ok(asked)
+}
+
+/// Log a single entry for `user`, or the token's own, prompting for whatever
+/// `args` leaves out. `previous` is the date and end time of the entry logged
+/// before in the same session, which the next one most likely follows.
+/// Returns the new entry's date and end time.
+fn log_entry(
+    config: &config::Config,
+    args: LogArgs,
+    user: Option<i32>,
+    previous: Option<(NaiveDate, NaiveTime)>,
+) -> Result<(NaiveDate, NaiveTime)> {
+    let git = if args.from_git || config.from_git {
+        git::Context::current()
+    } else {
+        None
+    };
+
+    let mut answers = Answers::default();
+    // The questions that were asked, to go back through with Esc
+    let mut asked = Vec::new();
+    let mut index = 0;
+    while let Some(&step) = STEPS.get(index) {
+        match answer(config, &args, git.as_ref(), previous, step, &mut answers) {
+            Ok(true) => {
+                asked.push(index);
+                index += 1;
+            }
+            Ok(false) => index += 1,
+            // Esc on the first question still cancels
+            Err(e) if matches!(e.downcast_ref(), Some(InquireError::OperationCanceled)) => {
+                match asked.pop() {
+                    Some(back) => index = back,
+                    None => return Err(e),
+                }
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    let ask = prompted(&args);
+    let draft = answers.draft(config)?;
+    if !draft.activities.is_empty() {
+        return log_split(config, draft, user, ask, &args);
+    }
+    // Having answered prompts, the user gets to look over the answers
+    if ask {
+        let form = confirm(config, draft, user)?;
+        caps::check(config, &form, args.force)?;
+        let end = form.end.unwrap_or(form.begin).with_timezone(&clock::zone());
+        if let Some(timesheet) = submit(config, form)? {
+            logged(config, &timesheet, args.json);
+        }
+        return Ok((end.date_naive(), end.time()));
+    }
+
+    let mut form = draft.form(user)?;
+    if rounding::apply(config, &mut form) {
+        let rounded = format::span(form.begin, form.end.unwrap_or(form.begin));
+        let confirmed = !std::io::stdin().is_terminal()
+            || Confirm::new(&format!("Log {} instead, rounded as configured?", rounded))
+                .with_default(true)
+                .prompt()?;
+        if !confirmed {
+            anyhow::bail!("Nothing was logged");
+        }
+    }
+    caps::check(config, &form, args.force)?;
+
+    let end = form.end.unwrap_or(form.begin).with_timezone(&clock::zone());
+    if let Some(timesheet) = submit(config, form)? {
+        logged(config, &timesheet, args.json);
+    }
+    Ok((end.date_naive(), end.time()))
+}
+
+/// `total` split into `parts` equal shares of whole minutes, the first ones a
+/// minute longer where it doesn't divide evenly. Fails if that leaves a share
+/// without a minute.
+fn equal_shares(total: Duration, parts: usize) -> Result<Vec<Duration>> {
+    let minutes = total.num_minutes();
+    if minutes < parts as i64 {
+        return Err(invalid!(
+            "{} minutes can't be split across {} activities",
+            minutes,
+            parts
+        ));
+    }
+    let parts = parts as i64;
+    Ok((0..parts)
+        .map(|part| Duration::minutes(minutes / parts + i64::from(part < minutes % parts)))
+        .collect())
+}
+
+const EQUAL_SHARES: &str = "Equal shares";
+const OWN_SHARES: &str = "A share for each activity";
+
+/// Log the time of `draft` as consecutive entries, one on each of its
+/// activities, in equal shares or as asked. Returns the date and end time of
+/// the last one.
+fn log_split(
+    config: &config::Config,
+    draft: Draft,
+    user: Option<i32>,
+    ask: bool,
+    args: &LogArgs,
+) -> Result<(NaiveDate, NaiveTime)> {
+    let mut whole = draft.form(user)?;
+    rounding::apply(config, &mut whole);
+    let end = whole.end.unwrap_or(whole.begin);
+    let total = end - whole.begin;
+    let activities = &draft.activities;
+    let mut shares = equal_shares(total, activities.len())?;
+    if ask
+        && Select::new("Split the time into:", vec![EQUAL_SHARES, OWN_SHARES]).prompt()?
+            == OWN_SHARES
+    {
+        // The last activity gets what's left
+        let mut left = total;
+        for index in 0..activities.len() - 1 {
+            let message = format!("{}:", activities[index].name);
+            let share = prompts::duration_with_message(&message, shares[index].min(left))?;
+            if share <= Duration::zero() {
+                return Err(invalid!(
+                    "{} needs some of the time",
+                    activities[index].name
+                ));
+            }
+            if share >= left {
+                anyhow::bail!("That leaves no time for {}", activities[index + 1].name);
+            }
+            shares[index] = share;
+            left -= share;
+        }
+        shares[activities.len() - 1] = left;
+    }
+
+    let mut forms = Vec::new();
+    let mut begin = whole.begin;
+    for (activity, share) in activities.iter().zip(shares) {
+        forms.push(TimesheetEditForm {
+            begin,
+            activity: activity.id,
+            end: Some(begin + share),
+            ..whole.clone()
+        });
+        begin += share;
+    }
+    // Whatever the shares leave over, e.g. seconds, goes to the last one
+    if let Some(last) = forms.last_mut() {
+        last.end = Some(end);
+    }
+
+    println!("{} on {}", draft.task.project, format::day(draft.date));
+    for (activity, form) in activities.iter().zip(&forms) {
+        let label = format!("{:<20}", activity.name);
+        println!(
+            "  {} {}",
+            format::paint(&label, Style::Dim),
+            format::span(form.begin, form.end.unwrap_or(form.begin))
+        );
+    }
+    if ask
+        && !Confirm::new(&format!("Log these {} entries?", forms.len()))
+            .with_default(true)
+            .prompt()?
+    {
+        anyhow::bail!("Nothing was logged");
+    }
+    caps::check(config, &whole, args.force)?;
+    for form in forms {
+        if let Some(timesheet) = submit(config, form)? {
+            logged(config, &timesheet, args.json);
+        }
+    }
+    let end = end.with_timezone(&clock::zone());
+    Ok((end.date_naive(), end.time()))
+}
+
+/// Say that `timesheet` was logged and where to edit it, or print it as
+/// JSON.
+fn logged(config: &config::Config, timesheet: &api::Timesheet, json: bool) {
+    if json {
+        print_json(config, timesheet);
+        return;
+    }
+    let end = timesheet.end.unwrap_or(timesheet.begin);
+    format::note!(
+        "Logged entry #{} {}",
+        timesheet.id,
+        format::span(timesheet.begin, end)
+    );
+    note_link(config, timesheet.id);
+}
+
+/// Print the link to edit entry `id`, unless `--quiet` leaves it out.
+pub fn note_link(config: &config::Config, id: i32) {
+    // The link isn't worth a request when it's not printed
+    if format::quiet() {
+        return;
+    }
+    if let Ok(url) = open::timesheet_url(config, id) {
+        println!("  {}", format::paint(&url, Style::Dim));
+    }
+}
+
+/// Print the created `timesheet` on one line of JSON, with the id and link
+/// for scripts to edit or delete it later.
+pub fn print_json(config: &config::Config, timesheet: &api::Timesheet) {
+    let entry = serde_json::json!({
+        "id": timesheet.id,
+        "url": open::timesheet_url(config, timesheet.id).ok(),
+        "begin": timesheet.begin.to_rfc3339(),
+        "end": timesheet.end.map(|end| end.to_rfc3339()),
+        "project": timesheet.project,
+        "activity": timesheet.activity,
+        "description": timesheet.description,
+        "tags": timesheet.tags,
+    });
+    println!("{}", entry);
+}
+
+/// Create an entry, or queue it when Kimai can't be reached. Returns the
+/// entry as Kimai stored it, if it was created.
+pub fn submit(config: &config::Config, form: TimesheetEditForm) -> Result<Option<api::Timesheet>> {
+    match api::insert_timesheet_entry(config, &form) {
+        Ok(timesheet) => {
+            undo::remember(config, timesheet.id);
+            Ok(Some(timesheet))
+        }
+        // Keep going, so a dry run can walk through a whole session
+        Err(e) if api::is_dry_run(&e) => Ok(None),
+        Err(e) if api::is_unreachable(&e) => {
+            queue::push(config, form)?;
+            eprintln!("{}", e);
+            eprintln!("Kimai is unreachable, the entry was queued. Run `kimai sync` to submit it.");
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn minutes(shares: Result<Vec<Duration>>) -> Vec<i64> {
+        shares.unwrap().iter().map(Duration::num_minutes).collect()
+    }
+
+    #[test]
+    fn remainder_goes_to_the_first_shares() {
+        assert_eq!(
+            minutes(equal_shares(Duration::hours(2), 4)),
+            [30, 30, 30, 30]
+        );
+        assert_eq!(
+            minutes(equal_shares(Duration::minutes(100), 3)),
+            [34, 33, 33]
+        );
+        assert_eq!(minutes(equal_shares(Duration::minutes(3), 3)), [1, 1, 1]);
+        // Seconds are dropped rather than shared
+        assert_eq!(
+            minutes(equal_shares(Duration::seconds(61 * 60 + 59), 2)),
+            [31, 30]
+        );
+    }
+
+    #[test]
+    fn no_empty_shares() {
+        let error = equal_shares(Duration::minutes(2), 3).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "2 minutes can't be split across 3 activities"
+        );
+        assert!(crate::invalid::is_invalid(&error));
+        assert!(equal_shares(Duration::seconds(59), 1).is_err());
+    }
+}
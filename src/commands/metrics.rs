@@ -0,0 +1,236 @@
+//! The time tracked this week and the running timers as Prometheus metrics
+//! or JSON, e.g. for a Grafana dashboard.
+use anyhow::Result;
+use chrono::{Duration, NaiveDate};
+use clap::{Args, Subcommand};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+
+use crate::commands::timer;
+use crate::{clock, config, report};
+
+#[derive(Args, Debug)]
+pub struct MetricsArgs {
+    #[command(subcommand)]
+    pub command: Option<MetricsCommand>,
+    /// Print JSON instead of Prometheus metrics
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum MetricsCommand {
+    /// Serve the metrics over HTTP at /metrics, and as JSON at /metrics.json
+    Serve {
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:9834")]
+        listen: String,
+    },
+}
+
+#[derive(Debug, Serialize)]
+struct Timer {
+    project: String,
+    activity: String,
+    seconds: i64,
+}
+
+#[derive(Debug, Serialize)]
+struct Metrics {
+    week_start: NaiveDate,
+    week_logged_seconds: i64,
+    week_expected_seconds: i64,
+    today_logged_seconds: i64,
+    today_expected_seconds: i64,
+    /// Project name to the time logged on it this week.
+    projects: BTreeMap<String, i64>,
+    timers: Vec<Timer>,
+}
+
+fn collect(config: &config::Config) -> Result<Metrics> {
+    let today = clock::today();
+    let (from, to) = report::week(today);
+    let entries = report::fetch(config, from, to)?;
+    let days_off = report::days_off(config, from, to)?;
+    let grid = report::Grid::new(&entries, from, to);
+
+    let expected: Duration = report::days(from, to)
+        .into_iter()
+        .map(|day| report::expected(config, day, &days_off))
+        .sum();
+    let today_logged = grid
+        .days
+        .iter()
+        .zip(grid.day_totals())
+        .find(|(day, _)| **day == today)
+        .map(|(_, logged)| logged)
+        .unwrap_or_else(Duration::zero);
+    let projects: BTreeMap<String, i64> = grid
+        .rows
+        .iter()
+        .map(|(project, days)| {
+            let logged: Duration = days.iter().copied().sum();
+            (project.label.clone(), logged.num_seconds())
+        })
+        .collect();
+    let timers = timer::active(config)?
+        .iter()
+        .map(|entry| Timer {
+            project: entry.project.name.clone(),
+            activity: entry.activity.name.clone(),
+            seconds: report::entry_duration(entry).num_seconds(),
+        })
+        .collect();
+
+    Ok(Metrics {
+        week_start: from,
+        week_logged_seconds: projects.values().sum(),
+        week_expected_seconds: expected.num_seconds(),
+        today_logged_seconds: today_logged.num_seconds(),
+        today_expected_seconds: report::expected(config, today, &days_off).num_seconds(),
+        projects,
+        timers,
+    })
+}
+
+/// A label value, with quotes, backslashes and newlines escaped.
+fn label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// `metrics` in Prometheus' text format.
+fn prometheus(metrics: &Metrics) -> String {
+    let mut out = String::new();
+    let mut gauge = |name: &str, help: &str, samples: Vec<(String, i64)>| {
+        out.push_str(&format!("# HELP kimai_{} {}\n", name, help));
+        out.push_str(&format!("# TYPE kimai_{} gauge\n", name));
+        for (labels, value) in samples {
+            out.push_str(&format!("kimai_{}{} {}\n", name, labels, value));
+        }
+    };
+    gauge(
+        "week_logged_seconds",
+        "Time logged this week.",
+        vec![(String::new(), metrics.week_logged_seconds)],
+    );
+    gauge(
+        "week_expected_seconds",
+        "Time expected this week.",
+        vec![(String::new(), metrics.week_expected_seconds)],
+    );
+    gauge(
+        "today_logged_seconds",
+        "Time logged today.",
+        vec![(String::new(), metrics.today_logged_seconds)],
+    );
+    gauge(
+        "today_expected_seconds",
+        "Time expected today.",
+        vec![(String::new(), metrics.today_expected_seconds)],
+    );
+    gauge(
+        "project_week_logged_seconds",
+        "Time logged this week per project.",
+        metrics
+            .projects
+            .iter()
+            .map(|(project, seconds)| (format!("{{project=\"{}\"}}", label(project)), *seconds))
+            .collect(),
+    );
+    gauge(
+        "timers_running",
+        "Number of running timers.",
+        vec![(String::new(), metrics.timers.len() as i64)],
+    );
+    gauge(
+        "timer_seconds",
+        "Time each running timer has been running.",
+        metrics
+            .timers
+            .iter()
+            .map(|timer| {
+                (
+                    format!(
+                        "{{project=\"{}\",activity=\"{}\"}}",
+                        label(&timer.project),
+                        label(&timer.activity)
+                    ),
+                    timer.seconds,
+                )
+            })
+            .collect(),
+    );
+    out
+}
+
+/// Answer a single request, with the metrics collected afresh.
+fn respond(config: &config::Config, stream: TcpStream) -> Result<()> {
+    let mut request = String::new();
+    BufReader::new(&stream).read_line(&mut request)?;
+    let path = request.split_whitespace().nth(1).unwrap_or_default();
+
+    let (status, content_type, body) = match path {
+        "/metrics" | "/metrics.json" => match collect(config) {
+            Ok(metrics) if path == "/metrics" => {
+                ("200 OK", "text/plain; version=0.0.4", prometheus(&metrics))
+            }
+            Ok(metrics) => (
+                "200 OK",
+                "application/json",
+                serde_json::to_string(&metrics)?,
+            ),
+            Err(e) => {
+                eprintln!("Error: {:#}", e);
+                (
+                    "500 Internal Server Error",
+                    "text/plain",
+                    format!("{:#}\n", e),
+                )
+            }
+        },
+        _ => ("404 Not Found", "text/plain", "Not found\n".to_string()),
+    };
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    Ok(())
+}
+
+fn serve(config: &config::Config, listen: &str) -> Result<()> {
+    let listener = TcpListener::bind(listen)
+        .map_err(|e| anyhow::anyhow!("Could not listen on {}: {}", listen, e))?;
+    println!("Serving metrics at http://{}/metrics", listen);
+    for stream in listener.incoming() {
+        // One failed scrape shouldn't stop the server
+        if let Err(e) = stream.map_err(Into::into).and_then(|s| respond(config, s)) {
+            eprintln!("Error: {:#}", e);
+        }
+    }
+    Ok(())
+}
+
+pub fn run(config: &config::Config, args: MetricsArgs) -> Result<()> {
+    match args.command {
+        Some(MetricsCommand::Serve { listen }) => serve(config, &listen),
+        None => {
+            let metrics = collect(config)?;
+            if args.json {
+                println!("{}", serde_json::to_string_pretty(&metrics)?);
+            } else {
+                print!("{}", prometheus(&metrics));
+            }
+            Ok(())
+        }
+    }
+}
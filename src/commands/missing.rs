@@ -0,0 +1,93 @@
+use anyhow::Result;
+use chrono::{Duration, NaiveDate};
+use clap::Args;
+use inquire::Confirm;
+use std::io::IsTerminal;
+
+use crate::commands::fill;
+use crate::format::{self, Align};
+use crate::report::{self, Grid};
+use crate::{clock, config, parse};
+
+#[derive(Args, Debug)]
+pub struct MissingArgs {
+    /// Check a month instead of a week
+    #[arg(long)]
+    pub month: bool,
+    /// Any day in the week or month to check, defaults to today
+    #[arg(long, value_parser = parse::date_arg, allow_hyphen_values = true)]
+    pub date: Option<NaiveDate>,
+}
+
+pub fn run(config: &config::Config, args: MissingArgs) -> Result<()> {
+    let today = clock::today();
+    let date = args.date.unwrap_or(today);
+    let (from, to) = if args.month {
+        report::month(date)
+    } else {
+        report::week(date)
+    };
+    // Days still ahead can't be short yet
+    let to = to.min(today);
+    if to < from {
+        println!(
+            "{} – {} has not started yet",
+            format::date(from),
+            format::date(to)
+        );
+        return Ok(());
+    }
+
+    let entries = report::fetch(config, from, to)?;
+    let grid = Grid::new(&entries, from, to);
+    let days_off = report::days_off(config, from, to)?;
+    let short: Vec<(NaiveDate, Duration, Duration)> = grid
+        .days
+        .iter()
+        .zip(grid.day_totals())
+        .map(|(day, logged)| (*day, logged, report::expected(config, *day, &days_off)))
+        .filter(|(_, logged, expected)| logged < expected)
+        .collect();
+
+    if short.is_empty() {
+        println!(
+            "No hours missing between {} and {}",
+            format::date(from),
+            format::date(to)
+        );
+        return Ok(());
+    }
+
+    let rows: Vec<Vec<String>> = short
+        .iter()
+        .map(|(day, logged, expected)| {
+            vec![
+                format::day(*day),
+                format::duration(*logged),
+                format::duration(*expected),
+                format::duration(*expected - *logged),
+            ]
+        })
+        .collect();
+    format::table(
+        &[
+            ("Date", Align::Left),
+            ("Logged", Align::Right),
+            ("Expected", Align::Right),
+            ("Missing", Align::Right),
+        ],
+        &rows,
+    );
+    let total: Duration = short.iter().map(|(_, l, e)| *e - *l).sum();
+    println!("{} missing in total", format::duration(total));
+
+    let (first, ..) = short[0];
+    if std::io::stdin().is_terminal()
+        && Confirm::new(&format!("Fill {} now?", format::day(first)))
+            .with_default(true)
+            .prompt()?
+    {
+        fill::run(config, fill::FillArgs { date: Some(first) })?;
+    }
+    Ok(())
+}
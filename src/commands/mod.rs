@@ -0,0 +1,37 @@
+pub mod approve;
+pub mod auth;
+pub mod balance;
+pub mod catalog;
+pub mod completions;
+pub mod config;
+pub mod copy;
+pub mod create;
+pub mod day;
+pub mod delete;
+pub mod doctor;
+pub mod edit;
+pub mod expense;
+pub mod export;
+pub mod favorite;
+pub mod fill;
+pub mod gaps;
+pub mod import;
+pub mod invoice;
+pub mod list;
+pub mod log;
+pub mod metrics;
+pub mod missing;
+pub mod open;
+pub mod pomodoro;
+pub mod recur;
+pub mod report;
+pub mod review;
+pub mod split;
+pub mod suggest;
+pub mod sync;
+pub mod timer;
+pub mod today;
+pub mod track;
+pub mod tui;
+pub mod undo;
+pub mod watch;
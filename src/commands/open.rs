@@ -0,0 +1,86 @@
+//! Links into Kimai's web UI, for what the command line doesn't cover.
+use anyhow::Result;
+use chrono::NaiveDate;
+use clap::Subcommand;
+use clap_complete::ArgValueCandidates;
+use std::process::Command;
+
+use crate::{api, cache, clock, complete, config, parse, prompts};
+
+#[derive(Subcommand, Debug)]
+pub enum OpenCommand {
+    /// Edit a timesheet entry
+    Timesheet {
+        /// Id of the entry
+        id: i32,
+    },
+    /// Show a project's details
+    Project {
+        /// Project name or id
+        #[arg(add = ArgValueCandidates::new(complete::projects))]
+        project: String,
+    },
+    /// Enter the hours of a week
+    Week {
+        /// Any day of the week, defaults to this week
+        #[arg(value_parser = parse::date_arg, allow_hyphen_values = true)]
+        date: Option<NaiveDate>,
+    },
+}
+
+/// The path of `page` below the locale, the dashboard without one.
+fn path(config: &config::Config, page: Option<OpenCommand>) -> Result<String> {
+    Ok(match page {
+        None => "dashboard/".to_string(),
+        Some(OpenCommand::Timesheet { id }) => format!("timesheet/{}/edit", id),
+        Some(OpenCommand::Project { project }) => {
+            let project = prompts::project(config, Some(&project))?;
+            format!("admin/project/{}/details", project.id)
+        }
+        // Kimai shows the week the date is in
+        Some(OpenCommand::Week { date }) => format!(
+            "quick_entry/{}",
+            date.unwrap_or_else(clock::today).format("%Y-%m-%d")
+        ),
+    })
+}
+
+/// Open `url` in the default browser.
+fn browse(url: &str) -> bool {
+    let program = if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    Command::new(program)
+        .arg(url)
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// The web UI's URL for `path`, in the user's language.
+fn url(config: &config::Config, path: &str) -> Result<String> {
+    // The same user clock.rs looks up for the time zone
+    let user: api::User = cache::get_or_fetch(config, "me", || api::get_current_user(config))?;
+    Ok(format!(
+        "{}/{}/{}",
+        config.server.endpoint.trim_end_matches('/'),
+        user.language.as_deref().unwrap_or("en"),
+        path
+    ))
+}
+
+/// Where to edit entry `id` in the web UI.
+pub fn timesheet_url(config: &config::Config, id: i32) -> Result<String> {
+    url(config, &path(config, Some(OpenCommand::Timesheet { id }))?)
+}
+
+pub fn run(config: &config::Config, page: Option<OpenCommand>) -> Result<()> {
+    let url = url(config, &path(config, page)?)?;
+    // The link is printed either way, to copy it or open it elsewhere
+    println!("{}", url);
+    if !browse(&url) {
+        eprintln!("Could not open a browser");
+    }
+    Ok(())
+}
@@ -0,0 +1,127 @@
+//! Working in pomodoros: focused intervals with short breaks in between,
+//! each finished interval logged as it ends.
+use anyhow::Result;
+use chrono::{DateTime, Duration};
+use clap::Args;
+use clap_complete::ArgValueCandidates;
+
+use crate::api::{self, TimesheetEditForm};
+use crate::clock::{self, Zone};
+use crate::commands::{log, undo, watch};
+use crate::invalid::invalid;
+use crate::{budget, complete, config, format, parse, prompts};
+
+#[derive(Args, Debug)]
+pub struct PomodoroArgs {
+    /// Length of a work interval, e.g. 25m; `work_minutes` from
+    /// `[pomodoro]` by default
+    #[arg(long, value_parser = parse::duration_arg)]
+    pub work: Option<Duration>,
+    /// Length of a break, e.g. 5m; `break_minutes` from `[pomodoro]` by
+    /// default
+    #[arg(long = "break", value_parser = parse::duration_arg)]
+    pub pause: Option<Duration>,
+    /// Stop after this many work intervals, instead of when interrupted
+    #[arg(long)]
+    pub cycles: Option<u32>,
+    /// Log the session as one entry, breaks included
+    #[arg(long)]
+    pub merge: bool,
+    /// Project name or id
+    #[arg(long, add = ArgValueCandidates::new(complete::projects))]
+    pub project: Option<String>,
+    /// Activity name or id
+    #[arg(long, add = ArgValueCandidates::new(complete::activities))]
+    pub activity: Option<String>,
+    /// Description of the entries
+    #[arg(long)]
+    pub description: Option<String>,
+}
+
+fn wait_until(until: DateTime<Zone>) {
+    if let Ok(left) = (until - clock::now()).to_std() {
+        std::thread::sleep(left);
+    }
+}
+
+pub fn run(config: &config::Config, args: PomodoroArgs) -> Result<()> {
+    let settings = config.pomodoro.clone().unwrap_or_default();
+    let work = args
+        .work
+        .unwrap_or_else(|| Duration::minutes(settings.work_minutes));
+    let pause = args
+        .pause
+        .unwrap_or_else(|| Duration::minutes(settings.break_minutes));
+    if work <= Duration::zero() {
+        return Err(invalid!("Work intervals need a length"));
+    }
+    let merge = args.merge || settings.merge;
+    let task = prompts::task(config, args.project.as_deref(), args.activity.as_deref())?;
+    budget::check(config, &task.project)?;
+    let description = prompts::description(config, args.description, &task)?;
+
+    let session = clock::now();
+    // The merged entry, created with the first interval and extended after
+    let mut merged: Option<i32> = None;
+    let mut done = 0;
+    loop {
+        let begin = clock::now();
+        let end = begin + work;
+        println!(
+            "Pomodoro {} on {} until {}",
+            done + 1,
+            task.project,
+            format::time(end.time())
+        );
+        wait_until(end);
+        done += 1;
+
+        // Pomodoros are logged as they were, rounding would blur them
+        let form = TimesheetEditForm {
+            begin: if merge { session } else { begin }.to_utc(),
+            project: task.project.id,
+            activity: task.activity.id,
+            end: Some(end.to_utc()),
+            description: Some(description.clone()),
+            tags: Some(task.tags.clone()),
+            billable: None,
+            meta_fields: Vec::new(),
+            user: None,
+        };
+        let logged = match merged {
+            Some(id) => api::update_timesheet(config, id, &form).map(|_| ()),
+            None if merge => api::insert_timesheet_entry(config, &form).map(|timesheet| {
+                undo::remember(config, timesheet.id);
+                merged = Some(timesheet.id);
+            }),
+            None => log::submit(config, form).map(|_| ()),
+        };
+        // A failure to log shouldn't end the session, the next interval
+        // tries again when merging
+        match logged {
+            Ok(()) => {}
+            Err(e) if api::is_dry_run(&e) => {}
+            Err(e) => eprintln!("Could not log the pomodoro: {:#}", e),
+        }
+
+        if args.cycles.is_some_and(|cycles| done >= cycles) {
+            watch::notify(
+                "Pomodoros done",
+                &format!(
+                    "{} of work on {}",
+                    format::duration(work * done as i32),
+                    task.project
+                ),
+            );
+            break;
+        }
+        let until = clock::now() + pause;
+        watch::notify(
+            "Pomodoro done",
+            &format!("Take a break until {}", format::time(until.time())),
+        );
+        wait_until(until);
+        watch::notify("Break over", &format!("Back to {}", task.project));
+    }
+    Ok(())
+}
@@ -0,0 +1,260 @@
+//! Entries that repeat, like a daily standup, logged by `kimai recur run`
+//! once they are due, e.g. from cron.
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime};
+use clap::{Args, Subcommand};
+use clap_complete::ArgValueCandidates;
+use serde::{Deserialize, Serialize};
+
+use crate::api::{self, TimesheetEditForm, TimesheetExpanded};
+use crate::clock::{self, Zone};
+use crate::commands::log;
+use crate::format::{self, Align};
+use crate::{complete, config, parse, prompts, report, state};
+
+#[derive(Subcommand, Debug)]
+pub enum RecurCommand {
+    /// Add an entry that repeats
+    Add(RecurAddArgs),
+    /// List the repeating entries
+    List,
+    /// Stop repeating an entry
+    Remove {
+        /// Description or number from `kimai recur list`
+        entry: String,
+    },
+    /// Log the repeating entries that are due and not logged yet
+    Run,
+}
+
+#[derive(Args, Debug)]
+pub struct RecurAddArgs {
+    /// Description of the entries
+    pub description: String,
+    /// Days to log on: day, weekday (Monday to Friday) or days like mon,thu
+    #[arg(long, value_parser = every_arg, default_value = "weekday")]
+    pub every: String,
+    /// Start time, e.g. 9:30 or 9:30am
+    #[arg(long, value_parser = parse::time_arg)]
+    pub at: NaiveTime,
+    /// Duration, e.g. 15m, 1.5 or 1h30m
+    #[arg(long, value_parser = parse::duration_arg)]
+    pub duration: Duration,
+    /// Project name or id
+    #[arg(long, add = ArgValueCandidates::new(complete::projects))]
+    pub project: Option<String>,
+    /// Activity name or id
+    #[arg(long, add = ArgValueCandidates::new(complete::activities))]
+    pub activity: Option<String>,
+    /// Tags for the entries, may be repeated or comma separated
+    #[arg(long = "tag", value_delimiter = ',')]
+    pub tags: Vec<String>,
+}
+
+fn every_arg(input: &str) -> Result<String, String> {
+    match parse::weekdays(input) {
+        Some(_) => Ok(input.trim().to_lowercase()),
+        None => Err(format!(
+            "invalid days '{}', expected day, weekday or days like mon,thu",
+            input
+        )),
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Recurring {
+    description: String,
+    /// As given to `--every`.
+    every: String,
+    at: NaiveTime,
+    minutes: i64,
+    project: i32,
+    activity: i32,
+    #[serde(default)]
+    tags: Vec<String>,
+    /// The first day `kimai recur run` hasn't looked at yet.
+    next: NaiveDate,
+}
+
+const RECURRING: &str = "recurring";
+
+fn load(config: &config::Config) -> Result<Vec<Recurring>> {
+    state::load(config, RECURRING)
+}
+
+fn save(config: &config::Config, recurring: &[Recurring]) -> Result<()> {
+    state::save(config, RECURRING, &recurring)
+}
+
+fn add(config: &config::Config, args: RecurAddArgs) -> Result<()> {
+    if args.duration <= Duration::zero() {
+        anyhow::bail!("The duration must be more than zero");
+    }
+    let project = prompts::project(config, args.project.as_deref())?;
+    let activity = prompts::activity(config, &project, args.activity.as_deref())?;
+
+    let mut recurring = load(config)?;
+    if recurring
+        .iter()
+        .any(|r| r.description.eq_ignore_ascii_case(&args.description))
+    {
+        anyhow::bail!("'{}' repeats already", args.description);
+    }
+    recurring.push(Recurring {
+        description: args.description.clone(),
+        every: args.every.clone(),
+        at: args.at,
+        minutes: args.duration.num_minutes(),
+        project: project.id,
+        activity: activity.id,
+        tags: args.tags,
+        next: clock::today(),
+    });
+    save(config, &recurring)?;
+    format::note!(
+        "'{}' is logged every {} at {} by `kimai recur run`",
+        args.description,
+        args.every,
+        format::time(args.at)
+    );
+    Ok(())
+}
+
+fn list(config: &config::Config) -> Result<()> {
+    let recurring = load(config)?;
+    if recurring.is_empty() {
+        println!("No repeating entries, add one with `kimai recur add`");
+        return Ok(());
+    }
+
+    let projects = api::get_projects(config)?;
+    let activities = api::get_activities(config)?;
+    let headers = [
+        ("#", Align::Right),
+        ("Description", Align::Left),
+        ("Every", Align::Left),
+        ("At", Align::Left),
+        ("Duration", Align::Right),
+        ("Project", Align::Left),
+        ("Activity", Align::Left),
+    ];
+    let rows: Vec<Vec<String>> = recurring
+        .iter()
+        .enumerate()
+        .map(|(index, r)| {
+            let project = projects
+                .iter()
+                .find(|p| p.id == r.project)
+                .map_or_else(|| format!("#{}", r.project), |p| p.to_string());
+            let activity = activities
+                .iter()
+                .find(|a| a.id == r.activity)
+                .map_or_else(|| format!("#{}", r.activity), |a| a.name.clone());
+            vec![
+                (index + 1).to_string(),
+                r.description.clone(),
+                r.every.clone(),
+                format::time(r.at),
+                format::duration(Duration::minutes(r.minutes)),
+                project,
+                activity,
+            ]
+        })
+        .collect();
+    format::table(&headers, &rows);
+    Ok(())
+}
+
+fn remove(config: &config::Config, entry: &str) -> Result<()> {
+    let mut recurring = load(config)?;
+    let index = match entry.parse::<usize>() {
+        Ok(number) if (1..=recurring.len()).contains(&number) => number - 1,
+        _ => recurring
+            .iter()
+            .position(|r| r.description.eq_ignore_ascii_case(entry))
+            .ok_or_else(|| anyhow::anyhow!("No repeating entry '{}'", entry))?,
+    };
+    let removed = recurring.remove(index);
+    save(config, &recurring)?;
+    format::note!("'{}' doesn't repeat anymore", removed.description);
+    Ok(())
+}
+
+/// Whether `recurring` was logged at `begin` already, maybe by hand.
+fn exists(entries: &[TimesheetExpanded], recurring: &Recurring, begin: DateTime<Zone>) -> bool {
+    entries.iter().any(|e| {
+        e.project.id == recurring.project && e.activity.id == recurring.activity && e.begin == begin
+    })
+}
+
+/// Log what is due from each entry's next day up to now, skipping days off
+/// and entries that exist already, so running it again logs nothing twice.
+fn run_due(config: &config::Config) -> Result<()> {
+    let mut recurring = load(config)?;
+    let now = clock::now();
+    let today = now.date_naive();
+    let Some(from) = recurring.iter().map(|r| r.next).min() else {
+        println!("No repeating entries, add one with `kimai recur add`");
+        return Ok(());
+    };
+    if from > today {
+        return Ok(());
+    }
+    let days_off = report::days_off(config, from, today)?;
+    let entries = report::fetch(config, from, today)?;
+
+    let mut logged = 0;
+    for r in &mut recurring {
+        let weekdays = parse::weekdays(&r.every).unwrap_or_default();
+        let mut day = r.next;
+        while day <= today {
+            let begin = clock::resolve(day.and_time(r.at))?;
+            let end = begin + Duration::minutes(r.minutes);
+            // Not over yet, so the next run looks at today again
+            if end > now {
+                break;
+            }
+            if weekdays.contains(&day.weekday())
+                && !days_off.contains_key(&day)
+                && !exists(&entries, r, begin)
+            {
+                let form = TimesheetEditForm {
+                    begin: begin.to_utc(),
+                    project: r.project,
+                    activity: r.activity,
+                    end: Some(end.to_utc()),
+                    description: Some(r.description.clone()),
+                    tags: Some(r.tags.clone()),
+                    billable: None,
+                    meta_fields: Vec::new(),
+                    user: None,
+                };
+                log::submit(config, form)?;
+                logged += 1;
+                if !config.dry_run {
+                    format::note!("Logged '{}' on {}", r.description, format::day(day));
+                }
+            }
+            day += Duration::days(1);
+        }
+        r.next = day;
+    }
+
+    if config.dry_run {
+        return Ok(());
+    }
+    save(config, &recurring)?;
+    if logged == 0 {
+        format::note!("Nothing is due");
+    }
+    Ok(())
+}
+
+pub fn run(config: &config::Config, command: RecurCommand) -> Result<()> {
+    match command {
+        RecurCommand::Add(args) => add(config, args),
+        RecurCommand::List => list(config),
+        RecurCommand::Remove { entry } => remove(config, &entry),
+        RecurCommand::Run => run_due(config),
+    }
+}
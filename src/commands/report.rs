@@ -0,0 +1,190 @@
+use anyhow::Result;
+use chrono::{Duration, NaiveDate};
+use clap::Args;
+use clap_complete::ArgValueCandidates;
+
+use crate::api::TimesheetExpanded;
+use crate::format::{self, Align, Style};
+use crate::report::{self, Grid};
+use crate::{clock, complete, config, parse, prompts};
+
+#[derive(Args, Debug)]
+pub struct ReportArgs {
+    /// Report on a week (the default)
+    #[arg(long, conflicts_with = "month")]
+    pub week: bool,
+    /// Report on a month
+    #[arg(long)]
+    pub month: bool,
+    /// Any day in the week or month to report on, defaults to today
+    #[arg(long, value_parser = parse::date_arg, allow_hyphen_values = true)]
+    pub date: Option<NaiveDate>,
+    /// Include what the time is worth, if the token may see rates
+    #[arg(long)]
+    pub rates: bool,
+    /// Report on another user's time (id, username or alias)
+    #[arg(long)]
+    pub user: Option<String>,
+    /// Only report on time on one project, by name or id
+    #[arg(long, add = ArgValueCandidates::new(complete::projects))]
+    pub project: Option<String>,
+}
+
+fn cell(duration: Duration) -> String {
+    if duration.is_zero() {
+        String::new()
+    } else {
+        format::duration(duration)
+    }
+}
+
+pub fn run(config: &config::Config, args: ReportArgs) -> Result<()> {
+    let date = args.date.unwrap_or_else(clock::today);
+    let (from, to) = if args.month {
+        report::month(date)
+    } else {
+        report::week(date)
+    };
+
+    let user = match &args.user {
+        Some(needle) => Some(prompts::user(config, needle)?.id),
+        None => None,
+    };
+    let project = match &args.project {
+        Some(needle) => Some(prompts::project(config, Some(needle))?.id),
+        None => None,
+    };
+    let mut entries = report::fetch_for(config, from, to, user)?;
+    if let Some(project) = project {
+        entries.retain(|e| e.project.id == project);
+    }
+    if args.rates && !entries.is_empty() && entries.iter().all(|e| e.rate.is_none()) {
+        anyhow::bail!("Kimai does not show rates to this user");
+    }
+    let grid = Grid::new(&entries, from, to);
+    let amounts = report::amounts(&entries);
+
+    let mut headers = vec![("Project".to_string(), Align::Left)];
+    for day in &grid.days {
+        let label = if args.month {
+            day.format("%d").to_string()
+        } else {
+            day.format("%a %d").to_string()
+        };
+        headers.push((label, Align::Right));
+    }
+    headers.push(("Total".to_string(), Align::Right));
+    if args.rates {
+        headers.push(("Amount".to_string(), Align::Right));
+    }
+
+    let summary_row = |label: &str, values: &[Duration]| {
+        let mut row = vec![label.to_string()];
+        row.extend(values.iter().map(|d| cell(*d)));
+        row.push(format::duration(values.iter().copied().sum()));
+        row
+    };
+
+    let mut rows: Vec<Vec<String>> = grid
+        .rows
+        .iter()
+        .map(|(project, durations)| {
+            let mut row = summary_row(&project.label, durations);
+            row[0] = format::project(&project.label);
+            if args.rates {
+                row.push(format::money(
+                    amounts.get(project).copied().unwrap_or_default(),
+                ));
+            }
+            row
+        })
+        .collect();
+
+    let days_off = report::days_off(config, from, to)?;
+    if !days_off.is_empty() {
+        let mut row = vec!["Off".to_string()];
+        row.extend(
+            grid.days
+                .iter()
+                .map(|day| days_off.get(day).cloned().unwrap_or_default()),
+        );
+        rows.push(row);
+    }
+
+    let totals = grid.day_totals();
+    let targets: Vec<Duration> = grid
+        .days
+        .iter()
+        .map(|d| report::expected(config, *d, &days_off))
+        .collect();
+    let differences: Vec<Duration> = totals
+        .iter()
+        .zip(&targets)
+        .map(|(total, target)| *total - *target)
+        .collect();
+
+    let mut total_row = summary_row("Total", &totals);
+    if args.rates {
+        total_row.push(format::money(amounts.values().sum()));
+    }
+    rows.push(total_row);
+    // Only worth two more rows when some time isn't billed
+    if entries.iter().any(|e| !e.billable) {
+        let billable = report::billable_totals(&entries, &grid.days);
+        let not_billable: Vec<Duration> = totals
+            .iter()
+            .zip(&billable)
+            .map(|(total, billable)| *total - *billable)
+            .collect();
+        rows.push(summary_row("Billable", &billable));
+        rows.push(summary_row("Not billable", &not_billable));
+    }
+    // The targets are for all of the time, not one project's share
+    if project.is_none() {
+        rows.push(summary_row("Target", &targets));
+        let mut difference_row = summary_row("Difference", &differences);
+        for cell in difference_row.iter_mut().skip(1) {
+            if cell.starts_with('-') {
+                *cell = format::paint(cell, Style::Red);
+            }
+        }
+        rows.push(difference_row);
+    }
+
+    println!("{} – {}", format::date(from), format::date(to));
+    format::table(&headers, &rows);
+
+    if args.rates {
+        println!();
+        print_amounts(&entries);
+    }
+    Ok(())
+}
+
+/// What each entry is worth, oldest first.
+fn print_amounts(entries: &[TimesheetExpanded]) {
+    let headers = [
+        ("Date", Align::Left),
+        ("Project", Align::Left),
+        ("Activity", Align::Left),
+        ("Duration", Align::Right),
+        ("Hourly", Align::Right),
+        ("Amount", Align::Right),
+    ];
+    let mut entries: Vec<&TimesheetExpanded> = entries.iter().collect();
+    entries.sort_by_key(|e| e.begin);
+    let rows: Vec<Vec<String>> = entries
+        .iter()
+        .map(|e| {
+            vec![
+                format::day(e.begin.date_naive()),
+                format::project(&e.project.to_string()),
+                e.activity.name.clone(),
+                format::duration(report::entry_duration(e)),
+                e.hourly_rate.map(format::money).unwrap_or_default(),
+                e.rate.map(format::money).unwrap_or_default(),
+            ]
+        })
+        .collect();
+    format::table(&headers, &rows);
+}
@@ -0,0 +1,152 @@
+//! Going over a week or month before it's locked, e.g. for invoicing, with
+//! the days that look wrong flagged and fixed one at a time.
+use anyhow::Result;
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use clap::Args;
+use inquire::{InquireError, Select};
+use std::io::IsTerminal;
+
+use crate::api::TimesheetExpanded;
+use crate::commands::{day, fill};
+use crate::format::{self, Align, Style};
+use crate::report::{self, DaysOff, Grid};
+use crate::{clock, config, parse};
+
+#[derive(Args, Debug)]
+pub struct ReviewArgs {
+    /// Review a month instead of a week
+    #[arg(long)]
+    pub month: bool,
+    /// Any day in the week or month to review, defaults to today
+    #[arg(long, value_parser = parse::date_arg, allow_hyphen_values = true)]
+    pub date: Option<NaiveDate>,
+}
+
+/// Days with more logged than this are most likely a timer left running.
+const LONG_DAY: Duration = Duration::hours(12);
+
+/// What looks wrong about `day`, with `entries` being the day's.
+fn flags(
+    config: &config::Config,
+    day: NaiveDate,
+    logged: Duration,
+    entries: &[&TimesheetExpanded],
+    days_off: &DaysOff,
+) -> Vec<String> {
+    let mut flags = Vec::new();
+    if logged.is_zero() && report::expected(config, day, days_off) > Duration::zero() {
+        flags.push("nothing logged".to_string());
+    }
+    if logged > LONG_DAY {
+        flags.push(format!("over {} hours", LONG_DAY.num_hours()));
+    }
+    if !logged.is_zero() {
+        if matches!(day.weekday(), Weekday::Sat | Weekday::Sun) {
+            flags.push("weekend".to_string());
+        } else if let Some(reason) = days_off.get(&day) {
+            flags.push(format!("day off ({})", reason));
+        }
+    }
+    let undescribed = entries
+        .iter()
+        .filter(|e| {
+            e.description
+                .as_deref()
+                .unwrap_or_default()
+                .trim()
+                .is_empty()
+        })
+        .count();
+    match undescribed {
+        0 => {}
+        1 => flags.push("1 without description".to_string()),
+        n => flags.push(format!("{} without description", n)),
+    }
+    flags
+}
+
+pub fn run(config: &config::Config, args: ReviewArgs) -> Result<()> {
+    let today = clock::today();
+    let date = args.date.unwrap_or(today);
+    let (from, to) = if args.month {
+        report::month(date)
+    } else {
+        report::week(date)
+    };
+    // Days still ahead have nothing to review yet
+    let to = to.min(today);
+    if to < from {
+        println!(
+            "{} – {} has not started yet",
+            format::date(from),
+            format::date(to)
+        );
+        return Ok(());
+    }
+    let days_off = report::days_off(config, from, to)?;
+
+    loop {
+        let entries = report::fetch(config, from, to)?;
+        let grid = Grid::new(&entries, from, to);
+        let mut rows = Vec::new();
+        let mut flagged = Vec::new();
+        for (day, logged) in grid.days.iter().zip(grid.day_totals()) {
+            let of_day: Vec<&TimesheetExpanded> = entries
+                .iter()
+                .filter(|e| e.begin.date_naive() == *day)
+                .collect();
+            let flags = flags(config, *day, logged, &of_day, &days_off).join(", ");
+            rows.push(vec![
+                format::day(*day),
+                format::duration(logged),
+                format::duration(report::expected(config, *day, &days_off)),
+                format::paint(&flags, Style::Yellow),
+            ]);
+            if !flags.is_empty() {
+                flagged.push((*day, of_day.is_empty(), flags));
+            }
+        }
+        format::table(
+            &[
+                ("Date", Align::Left),
+                ("Logged", Align::Right),
+                ("Expected", Align::Right),
+                ("Flags", Align::Left),
+            ],
+            &rows,
+        );
+
+        if flagged.is_empty() {
+            println!("Nothing looks wrong");
+            return Ok(());
+        }
+        if !std::io::stdin().is_terminal() {
+            return Ok(());
+        }
+
+        let mut labels: Vec<String> = flagged
+            .iter()
+            .map(|(day, _, flags)| format!("{}: {}", format::day(*day), flags))
+            .collect();
+        labels.push("Done".to_string());
+        let picked = match Select::new("Fix:", labels).raw_prompt() {
+            Ok(picked) => picked,
+            Err(InquireError::OperationCanceled) => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        let Some((day, empty, _)) = flagged.get(picked.index) else {
+            return Ok(());
+        };
+        // A day without entries is filled, one with entries gone through
+        let fixed = if *empty {
+            fill::run(config, fill::FillArgs { date: Some(*day) })
+        } else {
+            day::run(config, day::DayArgs { date: Some(*day) })
+        };
+        match fixed {
+            Ok(()) => {}
+            Err(e) if matches!(e.downcast_ref(), Some(InquireError::OperationCanceled)) => {}
+            Err(e) => eprintln!("Error: {:#}", e),
+        }
+    }
+}
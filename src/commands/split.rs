@@ -0,0 +1,174 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveTime};
+use clap::Args;
+use clap_complete::ArgValueCandidates;
+use inquire::Select;
+use std::io::IsTerminal;
+
+use crate::api::{self, Timesheet, TimesheetEditForm, TimesheetQuery};
+use crate::clock::{self, Zone};
+use crate::commands::{day, edit, log};
+use crate::invalid::invalid;
+use crate::{complete, config, format, parse, prompts};
+
+#[derive(Args, Debug)]
+pub struct SplitArgs {
+    /// Id of the entry to split; pick from recent entries if omitted
+    pub id: Option<i32>,
+    /// Time to split at, e.g. 12:30 or 12:30pm
+    #[arg(long, value_parser = parse::time_arg)]
+    pub at: Option<NaiveTime>,
+    /// Project of the second part (name or id), defaults to the entry's
+    #[arg(long, add = ArgValueCandidates::new(complete::projects))]
+    pub project: Option<String>,
+    /// Activity of the second part (name or id), defaults to the entry's
+    #[arg(long, add = ArgValueCandidates::new(complete::activities))]
+    pub activity: Option<String>,
+    /// Description of the second part, defaults to the entry's
+    #[arg(long)]
+    pub description: Option<String>,
+}
+
+/// Let the user pick one of their recent finished entries.
+fn pick_recent(config: &config::Config) -> Result<i32> {
+    let recent = api::get_timesheets(
+        config,
+        &TimesheetQuery {
+            limit: Some(20),
+            ..Default::default()
+        },
+    )?;
+    let recent: Vec<_> = recent
+        .into_iter()
+        .filter(|t| t.end.is_some() && !t.exported)
+        .collect();
+    if recent.is_empty() {
+        anyhow::bail!("No recent entries to split");
+    }
+
+    let labels: Vec<String> = recent.iter().map(format::entry).collect();
+    let picked = Select::new("Entry:", labels).raw_prompt()?;
+    Ok(recent[picked.index].id)
+}
+
+/// Fail unless `at` is within entry `id`, currently saved as `whole`.
+pub fn check(id: i32, whole: &TimesheetEditForm, at: DateTime<Zone>) -> Result<()> {
+    let Some(end) = whole.end else {
+        anyhow::bail!("Entry #{} is still running, stop it first", id);
+    };
+    if at <= whole.begin || at >= end {
+        return Err(invalid!(
+            "{} is not between the begin and end of entry #{}",
+            format::time(at.time()),
+            id
+        ));
+    }
+    Ok(())
+}
+
+/// Let entry `id`, currently saved as `whole`, end at `at`, and log the
+/// time after it as `rest`.
+pub fn apply(
+    config: &config::Config,
+    id: i32,
+    whole: TimesheetEditForm,
+    at: DateTime<Zone>,
+    mut rest: TimesheetEditForm,
+) -> Result<()> {
+    check(id, &whole, at)?;
+    let end = whole.end.unwrap_or_default();
+
+    let first = TimesheetEditForm {
+        end: Some(at.to_utc()),
+        ..whole
+    };
+    edit::update(config, id, &first)?;
+
+    rest.begin = at.to_utc();
+    rest.end = Some(end);
+    log::submit(config, rest)?;
+    Ok(())
+}
+
+/// The form to save `entry` unchanged.
+fn whole(entry: &Timesheet) -> TimesheetEditForm {
+    TimesheetEditForm {
+        begin: entry.begin.to_utc(),
+        project: entry.project,
+        activity: entry.activity,
+        end: entry.end.map(|end| end.to_utc()),
+        description: entry.description.clone(),
+        tags: Some(entry.tags.clone()),
+        billable: None,
+        meta_fields: Vec::new(),
+        user: None,
+    }
+}
+
+pub fn run(config: &config::Config, args: SplitArgs) -> Result<()> {
+    let interactive = std::io::stdin().is_terminal();
+    let id = match args.id {
+        Some(id) => id,
+        None => pick_recent(config)?,
+    };
+    let entry = edit::editable(config, id)?;
+    let Some(end) = entry.end else {
+        anyhow::bail!("Entry #{} is still running, stop it first", id);
+    };
+    let begin = entry.begin.with_timezone(&clock::zone());
+    let end = end.with_timezone(&clock::zone());
+
+    let at = match args.at {
+        Some(at) => at,
+        None if interactive => {
+            let middle = begin + (end - begin) / 2;
+            prompts::time("Split at:", middle.time())?
+        }
+        None => anyhow::bail!("Pass the time to split at with --at"),
+    };
+    let at = day::after(begin, at)?;
+    let whole = whole(&entry);
+    check(id, &whole, at)?;
+
+    // Without a terminal the second part keeps what isn't given
+    let project = match args.project.as_deref() {
+        Some(needle) => prompts::project(config, Some(needle))?,
+        None if interactive => prompts::project_with_default(config, entry.project)?,
+        None => api::get_projects(config)?
+            .into_iter()
+            .find(|p| p.id == entry.project)
+            .with_context(|| {
+                format!(
+                    "Project #{} of entry #{} is not visible, pass --project",
+                    entry.project, id
+                )
+            })?,
+    };
+    let activity = match args.activity.as_deref() {
+        Some(needle) => prompts::activity(config, &project, Some(needle))?.id,
+        None if interactive => prompts::activity_with_default(config, &project, entry.activity)?.id,
+        None if project.id == entry.project => entry.activity,
+        None => anyhow::bail!("Pass the activity of the second part with --activity"),
+    };
+    let current = entry.description.as_deref().unwrap_or_default();
+    let description = match args.description {
+        Some(description) => description,
+        None if interactive => prompts::description_for(config, project.id, activity, current)?,
+        None => current.to_string(),
+    };
+
+    let rest = TimesheetEditForm {
+        project: project.id,
+        activity,
+        description: Some(description),
+        ..whole.clone()
+    };
+    apply(config, id, whole, at, rest)?;
+    format::note!(
+        "Split entry #{} at {}, the rest is on {}",
+        id,
+        format::time(at.time()),
+        project
+    );
+    Ok(())
+}
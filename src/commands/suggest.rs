@@ -0,0 +1,133 @@
+//! Entries suggested from what ActivityWatch or WakaTime saw being worked
+//! on, to accept or adjust one at a time.
+use anyhow::Result;
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
+use clap::{Args, ValueEnum};
+use inquire::Select;
+use regex::Regex;
+
+use crate::api::TimesheetEditForm;
+use crate::commands::log;
+use crate::invalid::invalid;
+use crate::{clock, config, format, parse, prompts, report, tracked};
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum Source {
+    Activitywatch,
+    Wakatime,
+}
+
+#[derive(Args, Debug)]
+pub struct SuggestArgs {
+    /// Where the activity was recorded
+    #[arg(long, value_enum, default_value = "activitywatch")]
+    pub from: Source,
+    /// Day to suggest entries for, e.g. yesterday or 2024-05-01; defaults to
+    /// today
+    #[arg(long, value_parser = parse::date_arg, allow_hyphen_values = true)]
+    pub date: Option<NaiveDate>,
+}
+
+const LOG: &str = "Log it";
+const ADJUST: &str = "Adjust it";
+const SKIP: &str = "Skip";
+const DONE: &str = "Done";
+
+/// The entry for a suggestion, asking for what needs it.
+fn form(
+    config: &config::Config,
+    rule: &config::SuggestRule,
+    block: &tracked::Block,
+    adjust: bool,
+) -> Result<TimesheetEditForm> {
+    let zone = clock::zone();
+    let mut begin = block.begin;
+    let mut end = block.end;
+    let mut project = prompts::project(config, Some(&rule.project))?;
+    let mut activity = prompts::activity(config, &project, rule.activity.as_deref())?;
+    if adjust {
+        let day = begin.with_timezone(&zone).date_naive();
+        let start = prompts::time("Start:", begin.with_timezone(&zone).time())?;
+        let stop = prompts::time("End:", end.with_timezone(&zone).time())?;
+        begin = clock::resolve(day.and_time(start))?.to_utc();
+        end = begin + parse::elapsed(start, stop).map_err(|e| invalid!("{}", e))?;
+        project = prompts::project_with_default(config, project.id)?;
+        activity = prompts::activity_with_default(config, &project, activity.id)?;
+    }
+    let initial = rule.description.as_deref().unwrap_or(&block.title);
+    let description = prompts::description_for(config, project.id, activity.id, initial)?;
+    Ok(TimesheetEditForm {
+        begin,
+        project: project.id,
+        activity: activity.id,
+        end: Some(end),
+        description: Some(description),
+        tags: None,
+        billable: None,
+        meta_fields: Vec::new(),
+        user: None,
+    })
+}
+
+pub fn run(config: &config::Config, args: SuggestArgs) -> Result<()> {
+    let settings = config.suggest.clone().unwrap_or_default();
+    if settings.rules.is_empty() {
+        anyhow::bail!("Add `[[suggest.rules]]` to say which activity belongs to which project");
+    }
+    let rules = settings
+        .rules
+        .iter()
+        .map(|rule| {
+            Regex::new(&rule.pattern)
+                .map_err(|e| anyhow::anyhow!("Invalid pattern '{}': {}", rule.pattern, e))
+        })
+        .collect::<Result<Vec<Regex>>>()?;
+
+    let day = args.date.unwrap_or_else(clock::today);
+    let begin = clock::resolve(day.and_time(NaiveTime::MIN))?.to_utc();
+    let end = clock::resolve((day + Duration::days(1)).and_time(NaiveTime::MIN))?.to_utc();
+    let events = match args.from {
+        Source::Activitywatch => {
+            tracked::activitywatch(config, &settings.activitywatch, begin, end)?
+        }
+        Source::Wakatime => {
+            let Some(key) = settings.wakatime_key.as_deref() else {
+                anyhow::bail!("Set `wakatime_key` in `[suggest]` to read from WakaTime");
+            };
+            tracked::wakatime(config, key, day)?
+        }
+    };
+
+    // What's logged already isn't suggested again
+    let logged: Vec<(DateTime<Utc>, DateTime<Utc>)> = report::fetch(config, day, day)?
+        .iter()
+        .map(|e| {
+            let begin = e.begin.to_utc();
+            (begin, begin + report::entry_duration(e))
+        })
+        .collect();
+    let min = Duration::minutes(settings.min_minutes.max(1));
+    let blocks = tracked::blocks(&events, &rules, &logged, min);
+    if blocks.is_empty() {
+        println!("Nothing to suggest for {}", format::day(day));
+        return Ok(());
+    }
+
+    for block in &blocks {
+        let rule = &settings.rules[block.rule];
+        let span = format::span(block.begin, block.end);
+        println!("{} {}: {}", span, rule.project, block.title);
+        let adjust = match Select::new("Log it?", vec![LOG, ADJUST, SKIP, DONE]).prompt()? {
+            LOG => false,
+            ADJUST => true,
+            SKIP => continue,
+            _ => break,
+        };
+        let form = form(config, rule, block, adjust)?;
+        log::submit(config, form)?;
+        if !config.dry_run {
+            format::note!("Logged {}", span);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use anyhow::Result;
+use clap::Args;
+
+use crate::{clock, config, format, queue};
+
+#[derive(Args, Debug)]
+pub struct SyncArgs {
+    /// Show the queued entries instead of submitting them
+    #[arg(long)]
+    pub list: bool,
+    /// Discard all queued entries
+    #[arg(long, conflicts_with = "list")]
+    pub clear: bool,
+}
+
+pub fn run(config: &config::Config, args: SyncArgs) -> Result<()> {
+    if args.list {
+        for form in queue::load(config)? {
+            let begin = form.begin.with_timezone(&clock::zone());
+            println!(
+                "{} project {} activity {} {}",
+                begin.format("%Y-%m-%d %H:%M"),
+                form.project,
+                form.activity,
+                form.description.unwrap_or_default()
+            );
+        }
+        return Ok(());
+    }
+
+    if args.clear {
+        queue::clear(config)?;
+        format::note!("Queue cleared");
+        return Ok(());
+    }
+
+    if queue::load(config)?.is_empty() {
+        format::note!("Nothing to submit");
+        return Ok(());
+    }
+
+    let flushed = queue::flush(config)?;
+    report(&flushed);
+    if flushed.offline {
+        anyhow::bail!("Kimai is still unreachable");
+    }
+    Ok(())
+}
+
+pub fn report(flushed: &queue::Flushed) {
+    if flushed.submitted > 0 {
+        format::note!("Submitted {} queued entries", flushed.submitted);
+    }
+    for error in &flushed.rejected {
+        eprintln!("A queued entry was rejected: {}", error);
+    }
+    if !flushed.rejected.is_empty() {
+        eprintln!("Rejected entries stay queued, see `kimai sync --list` and `kimai sync --clear`");
+    }
+}
@@ -0,0 +1,459 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+use clap::Args;
+use clap_complete::ArgValueCandidates;
+use inquire::Select;
+use std::io::IsTerminal;
+
+use crate::api::{self, TimesheetEditForm, TimesheetExpanded};
+use crate::clock::Zone;
+use crate::commands::{day, log, undo};
+use crate::format::Style;
+use crate::invalid::invalid;
+use crate::{
+    budget, cache, clock, complete, config, format, git, integrations, parse, prompts, report,
+    rounding, taskwarrior,
+};
+
+#[derive(Args, Debug, Default)]
+pub struct StartArgs {
+    /// Project name or id
+    #[arg(long, add = ArgValueCandidates::new(complete::projects))]
+    pub project: Option<String>,
+    /// Activity name or id
+    #[arg(long, add = ArgValueCandidates::new(complete::activities))]
+    pub activity: Option<String>,
+    /// Description of the entry
+    #[arg(long)]
+    pub description: Option<String>,
+    /// Offer the git branch and recent commits as descriptions, and pick the
+    /// project by the branch name if a rule matches
+    #[arg(long)]
+    pub from_git: bool,
+    /// Take the project, activity, description and tags that aren't given
+    /// from a Taskwarrior task, by uuid or id
+    #[arg(long)]
+    pub from_task: Option<String>,
+    /// Tags for the entry, may be repeated or comma separated
+    #[arg(long = "tag", value_delimiter = ',')]
+    pub tags: Vec<String>,
+    /// Custom field value as name=value, may be repeated
+    #[arg(long = "meta", value_parser = parse::meta_arg)]
+    pub meta: Vec<(String, String)>,
+    /// Mark the entry as billable, instead of asking
+    #[arg(long, conflicts_with = "not_billable")]
+    pub billable: bool,
+    /// Mark the entry as not billable
+    #[arg(long)]
+    pub not_billable: bool,
+    /// Print the started entry as JSON instead of what was done
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct RestartArgs {
+    /// Id of the entry to restart; pick from recent entries if omitted
+    pub id: Option<i32>,
+    /// Number of recent entries to pick from
+    #[arg(short = 'n', long, default_value_t = 10)]
+    pub count: usize,
+}
+
+#[derive(Args, Debug)]
+pub struct StopArgs {
+    /// When the work ended, e.g. 17:30 or 5:30pm, instead of now
+    #[arg(long, value_parser = parse::time_arg)]
+    pub at: Option<NaiveTime>,
+}
+
+#[derive(Args, Debug)]
+pub struct StatusArgs {
+    /// Print a single line for a shell prompt or status bar, or nothing when
+    /// idle. Uses the running timers last seen by kimai unless they are older
+    /// than 30 seconds, so it is fast enough to run on every prompt.
+    #[arg(long)]
+    pub short: bool,
+}
+
+/// Cache key of the running timers last fetched from the server.
+const ACTIVE: &str = "active";
+
+/// Seconds `status --short` shows the remembered timers for, so one started
+/// or stopped elsewhere, e.g. in the web UI, shows up soon.
+const ACTIVE_TTL: u64 = 30;
+
+/// Fetch the running timers and remember them for `status --short`.
+pub fn active(config: &config::Config) -> Result<Vec<TimesheetExpanded>> {
+    let active = api::get_active_timesheets(config)?;
+    cache::write(config, ACTIVE, &active);
+    Ok(active)
+}
+
+/// Update the remembered timers after starting or stopping one, and the
+/// Slack status. This is only a convenience, so errors are ignored.
+fn refresh_active(config: &config::Config) {
+    let Ok(active) = active(config) else {
+        return;
+    };
+    #[cfg(feature = "slack")]
+    crate::slack::update(config, &active);
+    #[cfg(not(feature = "slack"))]
+    let _ = active;
+}
+
+pub fn start(config: &config::Config, mut args: StartArgs) -> Result<()> {
+    // Nothing but the JSON on stdout, e.g. a stopped timer
+    if args.json {
+        format::set_quiet(true);
+    }
+    if let Some(uuid) = &args.from_task {
+        let task = taskwarrior::task(uuid)?;
+        let (project, activity) = task.needles(config);
+        args.project = args.project.or(project.map(String::from));
+        args.activity = args.activity.or(activity.map(String::from));
+        args.description = args.description.or(Some(task.description.clone()));
+        if args.tags.is_empty() {
+            args.tags = task.kimai_tags(config);
+        }
+    }
+    let git = if args.from_git || config.from_git {
+        git::Context::current()
+    } else {
+        None
+    };
+    let rule = match &git {
+        Some(git) => git.rule(config)?,
+        None => None,
+    };
+    let (project, activity) = git::needles(rule, args.project.as_deref(), args.activity.as_deref());
+    let task = prompts::task(config, project, activity)?;
+    budget::check(config, &task.project)?;
+    let description = match (args.description, &git) {
+        (None, Some(git)) => prompts::description_from(
+            config,
+            task.project.id,
+            task.activity.id,
+            &git.descriptions(),
+            &task.description,
+        )?,
+        (given, _) => prompts::description(config, given, &task)?,
+    };
+    let description = integrations::expand(config, description);
+    let tags = prompts::tags(config, args.tags, &task.tags)?;
+    let meta_fields = prompts::meta_fields(config, args.meta)?;
+    let billable = prompts::billable(&task, args.billable, args.not_billable)?;
+
+    let begin = match config.round_begin {
+        Some(rule) => rule.time(clock::now()).to_utc(),
+        None => Utc::now(),
+    };
+    guard(config, begin)?;
+    let timesheet = api::insert_timesheet_entry(
+        config,
+        &TimesheetEditForm {
+            begin,
+            project: task.project.id,
+            activity: task.activity.id,
+            end: None,
+            description: Some(description),
+            tags: Some(tags),
+            billable,
+            meta_fields,
+            user: None,
+        },
+    )?;
+    undo::remember(config, timesheet.id);
+
+    if args.json {
+        log::print_json(config, &timesheet);
+    } else {
+        format::note!(
+            "Started timer #{} for {} / {} at {}",
+            timesheet.id,
+            task.project,
+            task.activity,
+            format::time(timesheet.begin.with_timezone(&clock::zone()).time())
+        );
+        log::note_link(config, timesheet.id);
+    }
+    refresh_active(config);
+    Ok(())
+}
+
+pub fn restart(config: &config::Config, args: RestartArgs) -> Result<()> {
+    let id = match args.id {
+        Some(id) => id,
+        None => {
+            let recent = api::get_recent_timesheets(config, args.count)?;
+            if recent.is_empty() {
+                anyhow::bail!("No recent entries to restart");
+            }
+            let labels: Vec<String> = recent.iter().map(format::entry).collect();
+            let picked = Select::new("Entry:", labels).raw_prompt()?;
+            recent[picked.index].id
+        }
+    };
+
+    guard(config, Utc::now())?;
+    let timesheet = api::restart_timesheet(config, id)?;
+    undo::remember(config, timesheet.id);
+    format::note!(
+        "Started timer #{} (copy of #{}) at {}",
+        timesheet.id,
+        id,
+        format::time(timesheet.begin.with_timezone(&clock::zone()).time())
+    );
+    log::note_link(config, timesheet.id);
+    refresh_active(config);
+    Ok(())
+}
+
+/// Idle for at least this long counts as having stopped working.
+#[cfg(feature = "idle")]
+const AWAY_MINUTES: i64 = 10;
+
+/// When the keyboard and mouse were last used, if that was a while ago.
+fn away_since() -> Option<DateTime<Zone>> {
+    #[cfg(feature = "idle")]
+    if let Some(idle) = crate::idle::time().filter(|idle| *idle >= Duration::minutes(AWAY_MINUTES))
+    {
+        return Some(clock::now() - idle);
+    }
+    None
+}
+
+/// When to stop `running`: at `at` if given, otherwise at a time asked for
+/// when it looks like the timer was left running. `None` stops it now.
+fn stop_at(
+    config: &config::Config,
+    running: &TimesheetExpanded,
+    at: Option<NaiveTime>,
+) -> Result<Option<DateTime<Zone>>> {
+    let now = clock::now();
+    let begin = running.begin.with_timezone(&clock::zone());
+    let time = match at {
+        Some(at) => at,
+        None => {
+            if !std::io::stdin().is_terminal() {
+                return Ok(None);
+            }
+            let away = away_since().filter(|away| *away > begin);
+            let workday = Duration::minutes((config.expected_hours_per_day * 60.0) as i64);
+            let forgotten = now - begin > workday || begin.date_naive() != now.date_naive();
+            let message = match away {
+                Some(away) => format!(
+                    "No input since {}, stop timer #{} at:",
+                    format::time(away.time()),
+                    running.id
+                ),
+                None if forgotten => format!(
+                    "Timer #{} is running since {} {}, stop it at:",
+                    running.id,
+                    begin.format("%a"),
+                    format::time(begin.time())
+                ),
+                None => return Ok(None),
+            };
+            let time = prompts::time(&message, away.unwrap_or(now).time())?;
+            if time.format("%H:%M").to_string() == now.format("%H:%M").to_string() {
+                return Ok(None);
+            }
+            time
+        }
+    };
+
+    // The first such time after the timer started, which for a timer left
+    // running overnight is on the day it started
+    let end = day::after(begin, time)?;
+    if end > now {
+        return Err(invalid!("{} is still to come", format::time(end.time())));
+    }
+    Ok(Some(end))
+}
+
+/// `running` with its end set to `end`.
+fn ended(running: &TimesheetExpanded, end: DateTime<Utc>) -> TimesheetEditForm {
+    TimesheetEditForm {
+        begin: running.begin.to_utc(),
+        project: running.project.id,
+        activity: running.activity.id,
+        end: Some(end),
+        description: running.description.clone(),
+        tags: Some(running.tags.clone()),
+        billable: None,
+        meta_fields: Vec::new(),
+        user: None,
+    }
+}
+
+const DISCARD: &str = "Discard it";
+const ABORT: &str = "Don't start a new timer";
+
+/// Before starting a timer at `begin`, deal with those running already:
+/// stop them when the new one begins, discard them, or give up.
+fn guard(config: &config::Config, begin: DateTime<Utc>) -> Result<()> {
+    for running in api::take_active_timesheets(config)? {
+        let since = running.begin.with_timezone(&clock::zone());
+        let label = format!(
+            "#{} for {} / {} since {}",
+            running.id,
+            running.project,
+            running.activity,
+            format::time(since.time())
+        );
+        if !std::io::stdin().is_terminal() {
+            anyhow::bail!(
+                "Timer {} is still running, stop it first with `kimai stop`",
+                label
+            );
+        }
+        println!("Timer {} is still running", label);
+        // Rounding could have moved the new begin before the running one's
+        let end = begin.max(running.begin.to_utc());
+        let at = format::time(end.with_timezone(&clock::zone()).time());
+        let stop = format!("Stop it at {}", at);
+        let picked =
+            Select::new("What about it?", vec![stop.as_str(), DISCARD, ABORT]).raw_prompt()?;
+        let handled = match picked.index {
+            0 => api::update_timesheet(config, running.id, &ended(&running, end))
+                .map(|_| format!("Stopped timer #{} at {}", running.id, at)),
+            1 => api::delete_timesheet(config, running.id)
+                .map(|_| format!("Discarded timer #{}", running.id)),
+            _ => anyhow::bail!("Not starting a timer while #{} is running", running.id),
+        };
+        match handled {
+            Ok(note) => format::note!("{}", note),
+            Err(e) if api::is_dry_run(&e) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+pub fn stop(config: &config::Config, args: StopArgs) -> Result<()> {
+    let active = active(config)?;
+    if active.is_empty() {
+        anyhow::bail!("No timer is running");
+    }
+
+    let rounded = config.round_end.is_some() || config.round_duration.is_some();
+    for running in active {
+        let at = stop_at(config, &running, args.at)?;
+        // The stop endpoint ends timers at the current time, a rounded or
+        // earlier end has to be set like any other change
+        let stopped = if rounded || at.is_some() {
+            let end = rounding::stop(
+                config,
+                running.begin.to_utc(),
+                at.unwrap_or_else(clock::now),
+            );
+            api::update_timesheet(config, running.id, &ended(&running, end))
+        } else {
+            api::stop_timesheet(config, running.id)
+        };
+        let stopped = match stopped {
+            Ok(stopped) => stopped,
+            // Show every request that would be made
+            Err(e) if api::is_dry_run(&e) => continue,
+            Err(e) => return Err(e),
+        };
+        let end = stopped.end.unwrap_or_else(|| Utc::now().into());
+        format::note!(
+            "Stopped timer #{} for {} / {} after {}",
+            running.id,
+            running.project,
+            running.activity,
+            format::duration(end - stopped.begin)
+        );
+    }
+    refresh_active(config);
+    Ok(())
+}
+
+pub fn status(config: &config::Config, args: StatusArgs) -> Result<()> {
+    if args.short {
+        status_short(config);
+        return Ok(());
+    }
+
+    let active = active(config)?;
+    let timing = !active.is_empty();
+    if !timing {
+        println!("No timer is running");
+    }
+
+    for running in active {
+        let elapsed = Utc::now() - running.begin.with_timezone(&Utc);
+        println!(
+            "#{} {} / {} since {} ({})",
+            running.id,
+            format::project(&running.project.to_string()),
+            running.activity,
+            format::time(running.begin.with_timezone(&clock::zone()).time()),
+            format::duration(elapsed)
+        );
+        if let Some(description) = running.description.filter(|d| !d.is_empty()) {
+            println!("    {}", format::paint(&description, Style::Dim));
+        }
+    }
+    progress(config, timing)
+}
+
+/// Print how much of today's target is logged, and with a timer `running`,
+/// when it will be reached.
+fn progress(config: &config::Config, running: bool) -> Result<()> {
+    let today = clock::today();
+    let days_off = report::days_off(config, today, today)?;
+    let expected = report::expected(config, today, &days_off);
+    if expected.is_zero() {
+        return Ok(());
+    }
+    let logged: Duration = report::fetch(config, today, today)?
+        .iter()
+        .map(report::entry_duration)
+        .sum();
+
+    let fraction = logged.num_seconds() as f64 / expected.num_seconds() as f64;
+    let left = expected - logged;
+    let finish = if left <= Duration::zero() {
+        format::paint("target reached", Style::Green)
+    } else if running {
+        format!("done at {}", format::time((clock::now() + left).time()))
+    } else {
+        format!("{} to go", format::duration(left))
+    };
+    println!(
+        "Today {} {} of {} ({:.0}%), {}",
+        format::bar(fraction, 20),
+        format::duration(logged),
+        format::duration(expected),
+        fraction * 100.0,
+        finish
+    );
+    Ok(())
+}
+
+/// Print e.g. `▶ Acme/Development 0:42`. A shell prompt has no use for
+/// errors, so there is no output when the timers can't be determined.
+fn status_short(config: &config::Config) {
+    let Ok(active) = cache::get_or_fetch_within(config, ACTIVE, ACTIVE_TTL, || {
+        api::get_active_timesheets(config)
+    }) else {
+        return;
+    };
+    let timers: Vec<String> = active
+        .iter()
+        .map(|running| {
+            format!(
+                "▶ {}/{} {}",
+                running.project.name,
+                running.activity.name,
+                format::duration(Utc::now() - running.begin.with_timezone(&Utc))
+            )
+        })
+        .collect();
+    if !timers.is_empty() {
+        println!("{}", timers.join(" "));
+    }
+}
@@ -0,0 +1,73 @@
+//! What was done today at a glance: the entries as a timeline with the gaps
+//! between them, and the totals.
+use anyhow::Result;
+use chrono::Duration;
+
+use crate::format::{self, Style};
+use crate::report::{self, Grid};
+use crate::{clock, config};
+
+pub fn run(config: &config::Config) -> Result<()> {
+    let today = clock::today();
+    let mut entries = report::fetch(config, today, today)?;
+    entries.sort_by_key(|e| e.begin);
+    let gaps = report::gaps(&entries, Duration::minutes(1));
+
+    println!("{}", format::paint(&format::day(today), Style::Bold));
+    if entries.is_empty() {
+        println!("  Nothing logged yet");
+    }
+    for entry in &entries {
+        if let Some((from, to)) = gaps.iter().find(|(_, to)| *to == entry.begin) {
+            let gap = format!("{} not logged", format::span(*from, *to));
+            println!("  {}", format::paint(&gap, Style::Yellow));
+        }
+        let time = match entry.end {
+            Some(end) => format::span(entry.begin, end),
+            None => format!(
+                "{}–now   ({})",
+                format::time(entry.begin.with_timezone(&clock::zone()).time()),
+                format::duration(report::entry_duration(entry))
+            ),
+        };
+        let mut line = format!(
+            "  {}  {} / {}",
+            time,
+            format::project(&entry.project.to_string()),
+            entry.activity.name
+        );
+        if let Some(description) = entry.description.as_deref().filter(|d| !d.is_empty()) {
+            line.push_str("  ");
+            line.push_str(&format::paint(&format::first_line(description), Style::Dim));
+        }
+        if entry.end.is_none() {
+            line.push_str("  ");
+            line.push_str(&format::paint("▶ running", Style::Green));
+        }
+        println!("{}", line);
+    }
+
+    let grid = Grid::new(&entries, today, today);
+    let days_off = report::days_off(config, today, today)?;
+    let expected = report::expected(config, today, &days_off);
+    let total: Duration = grid.day_totals().into_iter().sum();
+    println!();
+    for (project, durations) in &grid.rows {
+        let logged: Duration = durations.iter().copied().sum();
+        println!(
+            "  {:>6}  {}",
+            format::duration(logged),
+            format::project(&project.label)
+        );
+    }
+    match days_off.get(&today) {
+        Some(reason) => println!("Total {}, off today ({})", format::duration(total), reason),
+        None if expected.is_zero() => println!("Total {}", format::duration(total)),
+        None => println!(
+            "Total {} of {}",
+            format::duration(total),
+            format::duration(expected)
+        ),
+    }
+    Ok(())
+}
@@ -0,0 +1,201 @@
+//! A stopwatch that only runs on this machine, for work that doesn't belong
+//! to a project yet. Kimai only hears about it when it's stopped.
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use clap::{Args, Subcommand};
+use clap_complete::ArgValueCandidates;
+use serde::{Deserialize, Serialize};
+
+use crate::api::TimesheetEditForm;
+use crate::commands::log;
+use crate::{clock, complete, config, format, prompts, rounding, state};
+
+#[derive(Subcommand, Debug)]
+pub enum TrackCommand {
+    /// Start the stopwatch
+    Start,
+    /// Pause the stopwatch, e.g. for lunch
+    Pause,
+    /// Continue after a pause
+    Resume,
+    /// Show how much time the stopwatch has tracked
+    Status,
+    /// Stop the stopwatch and log the tracked time as one entry
+    Stop(TrackStopArgs),
+    /// Stop the stopwatch without logging anything
+    Cancel,
+}
+
+#[derive(Args, Debug)]
+pub struct TrackStopArgs {
+    /// Project name or id
+    #[arg(long, add = ArgValueCandidates::new(complete::projects))]
+    pub project: Option<String>,
+    /// Activity name or id
+    #[arg(long, add = ArgValueCandidates::new(complete::activities))]
+    pub activity: Option<String>,
+    /// Description of the entry
+    #[arg(long)]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct Stopwatch {
+    /// When the stopwatch was started, unset when it isn't.
+    started: Option<DateTime<Utc>>,
+    /// When it was started or resumed last, unset while paused.
+    running_since: Option<DateTime<Utc>>,
+    /// Seconds tracked before the last pause.
+    tracked: i64,
+}
+
+impl Stopwatch {
+    fn elapsed(&self, now: DateTime<Utc>) -> Duration {
+        let running = self
+            .running_since
+            .map_or(Duration::zero(), |since| now - since);
+        Duration::seconds(self.tracked) + running
+    }
+}
+
+const STOPWATCH: &str = "stopwatch";
+
+fn load(config: &config::Config) -> Result<Stopwatch> {
+    state::load(config, STOPWATCH)
+}
+
+fn save(config: &config::Config, stopwatch: &Stopwatch) -> Result<()> {
+    state::save(config, STOPWATCH, stopwatch)
+}
+
+/// Load the stopwatch, failing if it isn't started.
+fn started(config: &config::Config) -> Result<(Stopwatch, DateTime<Utc>)> {
+    let stopwatch = load(config)?;
+    match stopwatch.started {
+        Some(started) => Ok((stopwatch, started)),
+        None => anyhow::bail!("The stopwatch isn't running, start it with `kimai track start`"),
+    }
+}
+
+fn time(at: DateTime<Utc>) -> String {
+    format::time(at.with_timezone(&clock::zone()).time())
+}
+
+fn stop(config: &config::Config, args: TrackStopArgs) -> Result<()> {
+    let (stopwatch, started) = started(config)?;
+    let tracked = stopwatch.elapsed(Utc::now());
+    format::note!(
+        "Tracked {} since {}",
+        format::duration(tracked),
+        time(started)
+    );
+
+    let task = prompts::task(config, args.project.as_deref(), args.activity.as_deref())?;
+    let description = prompts::description(config, args.description, &task)?;
+    let tags = prompts::tags(config, Vec::new(), &task.tags)?;
+    let meta_fields = prompts::meta_fields(config, Vec::new())?;
+    let billable = prompts::billable(&task, false, false)?;
+
+    // Pauses are left out, so the entry is as long as the tracked time
+    let mut form = TimesheetEditForm {
+        begin: started,
+        project: task.project.id,
+        activity: task.activity.id,
+        end: Some(started + tracked),
+        description: Some(description),
+        tags: Some(tags),
+        billable,
+        meta_fields,
+        user: None,
+    };
+    rounding::apply(config, &mut form);
+    log::submit(config, form)?;
+    if !config.dry_run {
+        save(config, &Stopwatch::default())?;
+        format::note!(
+            "Logged {} on {} / {}",
+            format::duration(tracked),
+            task.project,
+            task.activity
+        );
+    }
+    Ok(())
+}
+
+pub fn run(config: &config::Config, command: TrackCommand) -> Result<()> {
+    let now = Utc::now();
+    match command {
+        TrackCommand::Start => {
+            let stopwatch = load(config)?;
+            if let Some(started) = stopwatch.started {
+                anyhow::bail!(
+                    "The stopwatch is running since {}, stop or cancel it first",
+                    time(started)
+                );
+            }
+            save(
+                config,
+                &Stopwatch {
+                    started: Some(now),
+                    running_since: Some(now),
+                    tracked: 0,
+                },
+            )?;
+            format::note!("Started the stopwatch at {}", time(now));
+        }
+        TrackCommand::Pause => {
+            let (mut stopwatch, _) = started(config)?;
+            if stopwatch.running_since.is_none() {
+                anyhow::bail!("The stopwatch is already paused");
+            }
+            stopwatch.tracked = stopwatch.elapsed(now).num_seconds();
+            stopwatch.running_since = None;
+            save(config, &stopwatch)?;
+            format::note!(
+                "Paused at {} tracked",
+                format::duration(stopwatch.elapsed(now))
+            );
+        }
+        TrackCommand::Resume => {
+            let (mut stopwatch, _) = started(config)?;
+            if stopwatch.running_since.is_some() {
+                anyhow::bail!("The stopwatch isn't paused");
+            }
+            stopwatch.running_since = Some(now);
+            save(config, &stopwatch)?;
+            format::note!(
+                "Resumed at {} tracked",
+                format::duration(stopwatch.elapsed(now))
+            );
+        }
+        TrackCommand::Status => {
+            let stopwatch = load(config)?;
+            match stopwatch.started {
+                None => println!("The stopwatch isn't running"),
+                Some(started) => {
+                    let state = if stopwatch.running_since.is_some() {
+                        "running"
+                    } else {
+                        "paused"
+                    };
+                    println!(
+                        "{} tracked since {}, {}",
+                        format::duration(stopwatch.elapsed(now)),
+                        time(started),
+                        state
+                    );
+                }
+            }
+        }
+        TrackCommand::Stop(args) => stop(config, args)?,
+        TrackCommand::Cancel => {
+            let (stopwatch, _) = started(config)?;
+            save(config, &Stopwatch::default())?;
+            format::note!(
+                "Threw away {} of tracked time",
+                format::duration(stopwatch.elapsed(now))
+            );
+        }
+    }
+    Ok(())
+}
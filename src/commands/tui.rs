@@ -0,0 +1,325 @@
+use anyhow::Result;
+use chrono::{Duration, NaiveDate};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Paragraph, Row, Table, TableState};
+use ratatui::{DefaultTerminal, Frame};
+use std::time::Instant;
+
+use crate::api::{self, TimesheetExpanded};
+use crate::commands::{edit, log, timer, undo};
+use crate::{clock, config, format, report};
+
+/// How often to fetch entries again without being asked to.
+const REFETCH: std::time::Duration = std::time::Duration::from_secs(60);
+
+const HELP: &str =
+    "↑↓ select  l log  s start  x stop  r restart  e edit  d delete  g refresh  q quit";
+
+#[derive(Default)]
+struct App {
+    today: Vec<TimesheetExpanded>,
+    active: Vec<TimesheetExpanded>,
+    /// Logged time on each day of the current week.
+    week: Vec<(NaiveDate, Duration)>,
+    target: Duration,
+    table: TableState,
+    /// Shown instead of the key help until the next key press.
+    message: Option<String>,
+    /// Entry to delete once the user confirms.
+    deleting: Option<i32>,
+    fetched_at: Option<Instant>,
+}
+
+pub fn run(config: &config::Config) -> Result<()> {
+    let mut app = App::default();
+    // Fail before taking over the terminal if the server can't be reached
+    app.fetch(config)?;
+
+    let mut terminal = ratatui::init();
+    let result = app.run(config, &mut terminal);
+    ratatui::restore();
+    result
+}
+
+/// The result of a scoped thread, passing on its panic.
+fn join<T>(handle: std::thread::ScopedJoinHandle<'_, T>) -> T {
+    handle
+        .join()
+        .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+}
+
+/// Hand the terminal back to the regular prompts while `f` runs.
+fn suspend<T>(terminal: &mut DefaultTerminal, f: impl FnOnce() -> Result<T>) -> Result<T> {
+    terminal::disable_raw_mode()?;
+    execute!(std::io::stdout(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+
+    let result = f();
+
+    terminal::enable_raw_mode()?;
+    execute!(std::io::stdout(), EnterAlternateScreen)?;
+    terminal.clear()?;
+    result
+}
+
+impl App {
+    fn fetch(&mut self, config: &config::Config) -> Result<()> {
+        self.fetched_at = Some(Instant::now());
+
+        let today = clock::today();
+        let (from, to) = report::week(today);
+        let (entries, days_off, active) = std::thread::scope(|scope| {
+            let days_off = scope.spawn(|| report::days_off(config, from, to));
+            let active = scope.spawn(|| timer::active(config));
+            let entries = report::fetch(config, from, to);
+            (entries, join(days_off), join(active))
+        });
+        let mut entries = entries?;
+        entries.sort_by_key(|e| e.begin);
+
+        let grid = report::Grid::new(&entries, from, to);
+        let days_off = days_off?;
+        self.target = grid
+            .days
+            .iter()
+            .map(|day| report::expected(config, *day, &days_off))
+            .sum();
+        self.week = grid.days.iter().copied().zip(grid.day_totals()).collect();
+        self.today = entries
+            .into_iter()
+            .filter(|e| e.begin.date_naive() == today)
+            .collect();
+        self.active = active?;
+
+        let selected = match self.table.selected() {
+            _ if self.today.is_empty() => None,
+            Some(index) => Some(index.min(self.today.len() - 1)),
+            None => Some(self.today.len() - 1),
+        };
+        self.table.select(selected);
+        Ok(())
+    }
+
+    fn refresh(&mut self, config: &config::Config) {
+        if let Err(e) = self.fetch(config) {
+            self.message = Some(format!("Error: {}", e));
+        }
+    }
+
+    /// Report the outcome of an action and show its effect.
+    fn finish(&mut self, config: &config::Config, result: Result<String>) {
+        self.message = Some(match result {
+            Ok(message) => message,
+            Err(e) if api::is_dry_run(&e) => "Dry run, nothing was changed".to_string(),
+            Err(e) if is_cancelled(&e) => "Cancelled".to_string(),
+            Err(e) => format!("Error: {}", e),
+        });
+        self.refresh(config);
+    }
+
+    fn selected(&self) -> Option<i32> {
+        self.table
+            .selected()
+            .and_then(|index| self.today.get(index))
+            .map(|entry| entry.id)
+    }
+
+    fn run(&mut self, config: &config::Config, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            if self.fetched_at.is_none_or(|at| at.elapsed() >= REFETCH) {
+                self.refresh(config);
+            }
+            terminal.draw(|frame| self.draw(frame))?;
+
+            // Wake up every second so running timers count up
+            if !event::poll(std::time::Duration::from_secs(1))? {
+                continue;
+            }
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            if let Some(id) = self.deleting.take() {
+                if key.code == KeyCode::Char('y') {
+                    let result = api::delete_timesheet(config, id);
+                    self.finish(config, result.map(|_| format!("Deleted entry #{}", id)));
+                } else {
+                    self.message = Some(format!("Kept entry #{}", id));
+                }
+                continue;
+            }
+            self.message = None;
+
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return Ok(())
+                }
+                KeyCode::Down | KeyCode::Char('j') => self.table.select_next(),
+                KeyCode::Up | KeyCode::Char('k') => self.table.select_previous(),
+                KeyCode::Char('g') | KeyCode::F(5) => self.refresh(config),
+                KeyCode::Char('l') => {
+                    let result = suspend(terminal, || log::run(config, Default::default()));
+                    self.finish(config, result.map(|_| "Logged".to_string()));
+                }
+                KeyCode::Char('s') => {
+                    let result = suspend(terminal, || timer::start(config, Default::default()));
+                    self.finish(config, result.map(|_| "Started timer".to_string()));
+                }
+                KeyCode::Char('x') => {
+                    let result = self
+                        .active
+                        .iter()
+                        .map(|running| api::stop_timesheet(config, running.id).map(|_| running.id))
+                        .collect::<Result<Vec<_>>>()
+                        .map(|ids| match ids.as_slice() {
+                            [] => "No timer is running".to_string(),
+                            ids => format!("Stopped {}", ids_label(ids)),
+                        });
+                    self.finish(config, result);
+                }
+                KeyCode::Char('r') => {
+                    if let Some(id) = self.selected() {
+                        let result = api::restart_timesheet(config, id).map(|started| {
+                            undo::remember(config, started.id);
+                            format!("Started timer #{}", started.id)
+                        });
+                        self.finish(config, result);
+                    }
+                }
+                KeyCode::Char('e') => {
+                    if let Some(id) = self.selected() {
+                        let args = edit::EditArgs { id: Some(id) };
+                        let result = suspend(terminal, || edit::run(config, args));
+                        self.finish(config, result.map(|_| format!("Updated entry #{}", id)));
+                    }
+                }
+                KeyCode::Char('d') => {
+                    if let Some(id) = self.selected() {
+                        self.deleting = Some(id);
+                        self.message = Some(format!("Delete entry #{}? (y/N)", id));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let timers = self.active.len().max(1) as u16;
+        let [timer_area, today_area, week_area, footer_area] = Layout::vertical([
+            Constraint::Length(timers + 2),
+            Constraint::Min(4),
+            Constraint::Length(4),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let timers: Vec<Line> = if self.active.is_empty() {
+            vec![Line::from("No timer is running")]
+        } else {
+            self.active
+                .iter()
+                .map(|running| {
+                    Line::from(format!(
+                        "▶ {} / {} since {} ({}) {}",
+                        running.project,
+                        running.activity,
+                        format::time(running.begin.with_timezone(&clock::zone()).time()),
+                        format::duration(report::entry_duration(running)),
+                        running.description.as_deref().unwrap_or_default()
+                    ))
+                })
+                .collect()
+        };
+        frame.render_widget(
+            Paragraph::new(timers).block(Block::bordered().title(" Timer ")),
+            timer_area,
+        );
+
+        let total: Duration = self.today.iter().map(report::entry_duration).sum();
+        let rows = self.today.iter().map(|entry| {
+            Row::new(vec![
+                format::time(entry.begin.with_timezone(&clock::zone()).time()),
+                entry
+                    .end
+                    .map(|end| format::time(end.with_timezone(&clock::zone()).time()))
+                    .unwrap_or_else(|| "…".to_string()),
+                format::duration(report::entry_duration(entry)),
+                entry.project.name.clone(),
+                entry.activity.name.clone(),
+                entry.description.clone().unwrap_or_default(),
+            ])
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(5),
+                Constraint::Length(5),
+                Constraint::Length(6),
+                Constraint::Fill(1),
+                Constraint::Fill(1),
+                Constraint::Fill(2),
+            ],
+        )
+        .header(
+            Row::new(["Begin", "End", "Time", "Project", "Activity", "Description"])
+                .style(Style::new().add_modifier(Modifier::BOLD)),
+        )
+        .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+        .block(Block::bordered().title(format!(" Today {} ", format::duration(total))));
+        frame.render_stateful_widget(table, today_area, &mut self.table);
+
+        let week_total: Duration = self.week.iter().map(|(_, logged)| *logged).sum();
+        let mut days: Vec<String> = self
+            .week
+            .iter()
+            .map(|(day, _)| day.format("%a").to_string())
+            .collect();
+        days.push("Total".to_string());
+        let mut logged: Vec<String> = self
+            .week
+            .iter()
+            .map(|(_, logged)| format::duration(*logged))
+            .collect();
+        logged.push(format!(
+            "{} / {}",
+            format::duration(week_total),
+            format::duration(self.target)
+        ));
+        let mut widths = vec![Constraint::Length(6); self.week.len()];
+        widths.push(Constraint::Fill(1));
+        let week = Table::new([Row::new(logged)], widths)
+            .header(Row::new(days).style(Style::new().add_modifier(Modifier::BOLD)))
+            .block(Block::bordered().title(" This week "));
+        frame.render_widget(week, week_area);
+
+        frame.render_widget(
+            Paragraph::new(self.message.as_deref().unwrap_or(HELP)),
+            footer_area,
+        );
+    }
+}
+
+fn ids_label(ids: &[i32]) -> String {
+    let ids: Vec<String> = ids.iter().map(|id| format!("#{}", id)).collect();
+    ids.join(", ")
+}
+
+/// Whether the user backed out of a prompt with Esc or Ctrl-C.
+fn is_cancelled(e: &anyhow::Error) -> bool {
+    matches!(
+        e.downcast_ref::<inquire::InquireError>(),
+        Some(
+            inquire::InquireError::OperationCanceled | inquire::InquireError::OperationInterrupted
+        )
+    )
+}
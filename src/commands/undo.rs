@@ -0,0 +1,107 @@
+use anyhow::Result;
+use clap::Args;
+use inquire::Confirm;
+use serde::{Deserialize, Serialize};
+
+use crate::api::{self, Timesheet};
+use crate::{clock, config, format, state};
+
+#[derive(Args, Debug)]
+pub struct UndoArgs {
+    /// Only show the entry that would be deleted
+    #[arg(long)]
+    pub show: bool,
+    /// Don't ask for confirmation
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+/// The entry created most recently by this CLI.
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct Last {
+    id: Option<i32>,
+}
+
+const LAST: &str = "last-created";
+
+/// Remember the entry `kimai undo` would delete. The entry exists at this
+/// point, so failing to remember it is only worth a warning.
+pub fn remember(config: &config::Config, id: i32) {
+    if let Err(e) = state::save(config, LAST, &Last { id: Some(id) }) {
+        eprintln!("Could not remember entry #{} for undo: {}", id, e);
+    }
+}
+
+fn describe(config: &config::Config, entry: &Timesheet) -> Result<String> {
+    let project = api::get_projects(config)?
+        .into_iter()
+        .find(|p| p.id == entry.project)
+        .map_or_else(|| format!("project #{}", entry.project), |p| p.to_string());
+    let activity = api::get_activities(config)?
+        .into_iter()
+        .find(|a| a.id == entry.activity)
+        .map_or_else(|| format!("activity #{}", entry.activity), |a| a.name);
+    let begin = entry.begin.with_timezone(&clock::zone());
+    let time = match entry.end {
+        Some(end) => format!(
+            "{}–{} ({})",
+            format::time(begin.time()),
+            format::time(end.with_timezone(&clock::zone()).time()),
+            format::duration(end - entry.begin)
+        ),
+        None => format!(
+            "since {} ({}, running)",
+            format::time(begin.time()),
+            format::duration(clock::now().fixed_offset() - entry.begin)
+        ),
+    };
+    let mut summary = format!(
+        "#{} {} {} {} / {}",
+        entry.id,
+        format::day(begin.date_naive()),
+        time,
+        project,
+        activity
+    );
+    if let Some(description) = entry.description.as_deref().filter(|d| !d.is_empty()) {
+        summary.push_str(" – ");
+        summary.push_str(description);
+    }
+    Ok(summary)
+}
+
+pub fn run(config: &config::Config, args: UndoArgs) -> Result<()> {
+    let Some(id) = state::load::<Last>(config, LAST)?.id else {
+        anyhow::bail!("No entry to undo");
+    };
+    let entry = match api::get_timesheet(config, id) {
+        Ok(entry) => entry,
+        Err(e) if api::is_not_found(&e) => {
+            state::save(config, LAST, &Last::default())?;
+            anyhow::bail!("Entry #{} no longer exists", id);
+        }
+        Err(e) => return Err(e),
+    };
+    if entry.exported {
+        anyhow::bail!("Entry #{} was exported and can't be deleted anymore", id);
+    }
+    let summary = describe(config, &entry)?;
+    if args.show {
+        println!("{}", summary);
+        return Ok(());
+    }
+
+    if !args.yes {
+        // Deleting loses the entry for good, so the default is to keep it
+        let confirmed = Confirm::new(&format!("Delete {}?", summary))
+            .with_default(false)
+            .prompt()?;
+        if !confirmed {
+            return Ok(());
+        }
+    }
+    api::delete_timesheet(config, id)?;
+    state::save(config, LAST, &Last::default())?;
+    format::note!("Deleted entry #{}", id);
+    Ok(())
+}
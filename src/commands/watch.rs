@@ -0,0 +1,189 @@
+//! Reminders from a process left running in the background, e.g. started
+//! with the desktop session.
+use anyhow::Result;
+use chrono::{DateTime, Duration};
+use clap::Args;
+use std::collections::HashSet;
+use std::process::Command;
+
+use crate::clock::{self, Zone};
+use crate::commands::timer;
+use crate::{config, format, report};
+
+#[derive(Args, Debug)]
+pub struct WatchArgs {
+    /// Check once and exit, e.g. from cron
+    #[arg(long)]
+    pub once: bool,
+}
+
+/// What was reminded of already, so reminders don't repeat every check.
+#[derive(Default)]
+struct Reminded {
+    /// When untracked time was last reminded of.
+    untracked: Option<DateTime<Zone>>,
+    /// Timers reminded of for running too long.
+    timers: HashSet<i32>,
+}
+
+/// Shows a balloon tip from the tray for a few seconds, with the text taken
+/// from the environment.
+const WINDOWS_BALLOON: &str = "Add-Type -AssemblyName System.Windows.Forms, System.Drawing; \
+    $icon = New-Object System.Windows.Forms.NotifyIcon; \
+    $icon.Icon = [System.Drawing.SystemIcons]::Information; \
+    $icon.Visible = $true; \
+    $icon.ShowBalloonTip(10000, $env:KIMAI_TITLE, $env:KIMAI_BODY, 'Info'); \
+    Start-Sleep -Seconds 10; \
+    $icon.Dispose()";
+
+/// The command showing a notification with the tool the desktop comes with:
+/// `osascript` on macOS, PowerShell on Windows and `notify-send` elsewhere.
+/// That keeps D-Bus and Windows runtime bindings out of the build, at the cost
+/// of needing `notify-send` installed on Linux. The text is passed as
+/// arguments or through the environment, never as part of a script.
+fn notifier(title: &str, body: &str) -> Command {
+    let mut command;
+    if cfg!(target_os = "macos") {
+        command = Command::new("osascript");
+        command.args([
+            "-e",
+            "on run argv",
+            "-e",
+            "display notification (item 2 of argv) with title (item 1 of argv)",
+            "-e",
+            "end run",
+            title,
+            body,
+        ]);
+    } else if cfg!(windows) {
+        command = Command::new("powershell");
+        command
+            .args(["-NoProfile", "-NonInteractive", "-Command", WINDOWS_BALLOON])
+            .env("KIMAI_TITLE", title)
+            .env("KIMAI_BODY", body);
+    } else {
+        command = Command::new("notify-send");
+        command.args(["--app-name=kimai", title, body]);
+    }
+    command
+}
+
+/// Show a desktop notification and print it, for whoever reads the output.
+pub fn notify(title: &str, body: &str) {
+    println!("{} {}: {}", format::time(clock::now().time()), title, body);
+    let mut command = notifier(title, body);
+    // The balloon stays up for a while, which shouldn't hold up the timers
+    let shown = if cfg!(windows) {
+        command.spawn().map(|_| ())
+    } else {
+        command.status().and_then(|status| {
+            if status.success() {
+                Ok(())
+            } else {
+                Err(std::io::Error::other(format!("it exited with {}", status)))
+            }
+        })
+    };
+    match shown {
+        Ok(()) => {}
+        // Won't change while running, so once is enough
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            static MISSING: std::sync::Once = std::sync::Once::new();
+            MISSING.call_once(|| {
+                let program = command.get_program().to_string_lossy();
+                let hint = if cfg!(any(target_os = "macos", windows)) {
+                    ""
+                } else {
+                    ", install libnotify (e.g. the libnotify-bin package) to get them"
+                };
+                eprintln!(
+                    "Warning: no desktop notifications, {} was not found{}",
+                    program, hint
+                );
+            });
+        }
+        Err(e) => eprintln!("Could not show a desktop notification: {}", e),
+    }
+}
+
+/// Whether `now` is within the work hours of a day with expected hours.
+fn working(config: &config::Config, watch: &config::Watch, now: DateTime<Zone>) -> Result<bool> {
+    let today = now.date_naive();
+    let days_off = report::days_off(config, today, today)?;
+    Ok(
+        report::expected(config, today, &days_off) > Duration::zero()
+            && (watch.work_start..watch.work_end).contains(&now.time()),
+    )
+}
+
+fn check(config: &config::Config, watch: &config::Watch, reminded: &mut Reminded) -> Result<()> {
+    let now = clock::now();
+    let active = timer::active(config)?;
+
+    if active.is_empty() {
+        if !working(config, watch, now)? {
+            return Ok(());
+        }
+        let today = now.date_naive();
+        let start = clock::resolve(today.and_time(watch.work_start))?;
+        let since = report::fetch(config, today, today)?
+            .iter()
+            .filter_map(|e| e.end)
+            .max()
+            .map(|end| end.with_timezone(&clock::zone()))
+            .filter(|end| *end > start)
+            .unwrap_or(start);
+        let untracked = Duration::minutes(watch.untracked);
+        let due = reminded
+            .untracked
+            .is_none_or(|last| now - last >= untracked);
+        if now - since >= untracked && due {
+            notify(
+                "Nothing tracked",
+                &format!(
+                    "No timer has been running since {}",
+                    format::time(since.time())
+                ),
+            );
+            reminded.untracked = Some(now);
+        }
+        return Ok(());
+    }
+
+    reminded.untracked = None;
+    let limit = watch.timer_limit.unwrap_or(config.expected_hours_per_day);
+    let limit = Duration::minutes((limit * 60.0) as i64);
+    for running in active {
+        let elapsed = now.fixed_offset() - running.begin;
+        if elapsed >= limit && reminded.timers.insert(running.id) {
+            notify(
+                "Timer still running",
+                &format!(
+                    "{} / {} has been running for {}",
+                    running.project.name,
+                    running.activity.name,
+                    format::duration(elapsed)
+                ),
+            );
+        }
+    }
+    Ok(())
+}
+
+pub fn run(config: &config::Config, args: WatchArgs) -> Result<()> {
+    let watch = config.watch.clone().unwrap_or_default();
+    let mut reminded = Reminded::default();
+    loop {
+        // Keep watching through network hiccups or a server restart
+        if let Err(e) = check(config, &watch, &mut reminded) {
+            if args.once {
+                return Err(e);
+            }
+            eprintln!("{:#}", e);
+        }
+        if args.once {
+            return Ok(());
+        }
+        std::thread::sleep(std::time::Duration::from_secs(watch.interval.max(1) * 60));
+    }
+}
@@ -0,0 +1,60 @@
+use clap_complete::CompletionCandidate;
+use serde::de::DeserializeOwned;
+
+use crate::api::{Activity, Project};
+use crate::{cache, config};
+
+/// Every cached list whose key starts with `prefix`. Completion must be fast
+/// and quiet, so this never talks to the server and ignores errors.
+fn cached<T: DeserializeOwned>(prefix: &str) -> Vec<T> {
+    // Only the environment can select a profile while completing
+    let Ok(config) = config::load_config(None, None) else {
+        return Vec::new();
+    };
+    let Some(Ok(files)) = cache::dir(&config).map(std::fs::read_dir) else {
+        return Vec::new();
+    };
+
+    let mut items = Vec::new();
+    for file in files.flatten() {
+        let path = file.path();
+        let Some(key) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        if key.starts_with(prefix) {
+            if let Some((data, _)) = cache::read::<Vec<T>>(&config, key) {
+                items.extend(data);
+            }
+        }
+    }
+    items
+}
+
+fn candidates(mut names: Vec<(String, Option<String>)>) -> Vec<CompletionCandidate> {
+    names.sort();
+    names.dedup_by(|a, b| a.0 == b.0);
+    names
+        .into_iter()
+        .map(|(name, help)| CompletionCandidate::new(name).help(help.map(Into::into)))
+        .collect()
+}
+
+/// Names of cached projects, for `--project`.
+pub fn projects() -> Vec<CompletionCandidate> {
+    candidates(
+        cached::<Project>("projects")
+            .into_iter()
+            .map(|p| (p.name, p.parent_title))
+            .collect(),
+    )
+}
+
+/// Names of cached activities of any project, for `--activity`.
+pub fn activities() -> Vec<CompletionCandidate> {
+    candidates(
+        cached::<Activity>("activities")
+            .into_iter()
+            .map(|a| (a.name, a.parent_title))
+            .collect(),
+    )
+}
@@ -1,26 +1,698 @@
-use chrono::NaiveTime;
+use chrono::{NaiveDate, NaiveTime, Weekday};
 use figment::{
     providers::{Env, Format, Toml},
     Figment,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::api::{Customer, Project};
+use crate::integrations::Integrations;
+use crate::rounding;
+
+/// Defaults for entries on one project, from `[projects."<name or id>"]`.
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct ProjectDefaults {
+    /// Activity (name or id) to preselect.
+    pub activity: Option<String>,
+    /// Description to pre-fill.
+    pub description: Option<String>,
+    /// Hours to log on the project per month at most.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monthly_cap: Option<f64>,
+    /// Regular expression new descriptions must match, e.g. `^[A-Z]+-\d+`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description_pattern: Option<String>,
+}
+
+/// Settings for one customer, from `[customers."<name or id>"]`.
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct CustomerSettings {
+    /// Hours to log on all the customer's projects per month at most.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monthly_cap: Option<f64>,
+}
+
+/// Picks a project for work on branches matching a pattern, from
+/// `[[git_rules]]`.
+#[derive(Deserialize, Serialize, Debug)]
+pub struct GitRule {
+    /// Regular expression matched against the branch name, e.g. `^ACME-\d+`.
+    pub branch: String,
+    /// Project name or id.
+    pub project: String,
+    /// Activity name or id.
+    pub activity: Option<String>,
+}
+
+/// Where meetings imported from a calendar are logged, from `[calendar]`.
+#[derive(Deserialize, Serialize, Debug)]
+pub struct Calendar {
+    /// Project name or id.
+    pub project: String,
+    /// Activity name or id.
+    pub activity: Option<String>,
+}
+
+/// Which time between entries `kimai gaps` looks at, and where breaks are
+/// logged, from `[gaps]`.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Gaps {
+    /// Shorter gaps are left alone, e.g. for getting coffee.
+    #[serde(default = "default_gap_minutes")]
+    pub min_minutes: i64,
+    /// Project name or id for breaks.
+    pub project: Option<String>,
+    /// Activity name or id for breaks.
+    pub activity: Option<String>,
+}
+
+impl Default for Gaps {
+    fn default() -> Self {
+        Gaps {
+            min_minutes: default_gap_minutes(),
+            project: None,
+            activity: None,
+        }
+    }
+}
+
+/// Where `kimai suggest` reads the tracked activity from, and which project
+/// it belongs to, from `[suggest]`.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Suggest {
+    /// Address of the local ActivityWatch server.
+    #[serde(default = "default_activitywatch")]
+    pub activitywatch: String,
+    /// API key for WakaTime.
+    pub wakatime_key: Option<String>,
+    /// Shorter blocks of activity aren't suggested.
+    #[serde(default = "default_suggest_minutes")]
+    pub min_minutes: i64,
+    #[serde(default)]
+    pub rules: Vec<SuggestRule>,
+}
+
+impl Default for Suggest {
+    fn default() -> Self {
+        Suggest {
+            activitywatch: default_activitywatch(),
+            wakatime_key: None,
+            min_minutes: default_suggest_minutes(),
+            rules: Vec::new(),
+        }
+    }
+}
+
+/// Time on windows, files or repositories matching `pattern` goes to
+/// `project`, from `[[suggest.rules]]`.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SuggestRule {
+    /// Regular expression matched against window titles and apps, and the
+    /// project and file names of editors.
+    pub pattern: String,
+    /// Project name or id.
+    pub project: String,
+    /// Activity name or id.
+    pub activity: Option<String>,
+    pub description: Option<String>,
+}
+
+/// Lengths of the work intervals and breaks of `kimai pomodoro`, from
+/// `[pomodoro]`.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Pomodoro {
+    #[serde(default = "default_work_minutes")]
+    pub work_minutes: i64,
+    #[serde(default = "default_break_minutes")]
+    pub break_minutes: i64,
+    /// Log a session as one entry, breaks included, instead of one per work
+    /// interval.
+    #[serde(default)]
+    pub merge: bool,
+}
+
+impl Default for Pomodoro {
+    fn default() -> Self {
+        Pomodoro {
+            work_minutes: default_work_minutes(),
+            break_minutes: default_break_minutes(),
+            merge: false,
+        }
+    }
+}
+
+/// Which Kimai project and activity Taskwarrior tasks go to, from
+/// `[taskwarrior]`.
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct Taskwarrior {
+    /// Taskwarrior project to Kimai project name or id. A key like `acme`
+    /// covers `acme.shop` as well, the longest matching key wins.
+    #[serde(default)]
+    pub projects: BTreeMap<String, String>,
+    /// Tag to Kimai activity name or id, for the first tag of a task that
+    /// has one.
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
+}
+
+/// What to run whenever an entry was created, from `[hooks]`.
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct Hooks {
+    /// Shell command, with placeholders like `{project}` and `{duration}`
+    /// filled in.
+    pub on_entry_created: Option<String>,
+    /// URL to POST the entry to as JSON.
+    pub webhook: Option<String>,
+}
+
+/// How times are shown, from `time_format`.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, Copy, PartialEq)]
+pub enum TimeFormat {
+    #[default]
+    #[serde(rename = "24h")]
+    Hours24,
+    /// With AM and PM, e.g. `2:30 PM`.
+    #[serde(rename = "12h")]
+    Hours12,
+}
+
+/// When `kimai watch` reminds of untracked time, from `[watch]`.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Watch {
+    /// Minutes between checks.
+    #[serde(default = "default_watch_interval")]
+    pub interval: u64,
+    /// Remind after this many minutes of work hours without a timer.
+    #[serde(default = "default_watch_untracked")]
+    pub untracked: i64,
+    /// Remind when a timer has been running for this many hours, by default
+    /// `expected_hours_per_day`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timer_limit: Option<f64>,
+    /// Work hours on days with expected hours.
+    #[serde(default = "default_start_time")]
+    pub work_start: NaiveTime,
+    #[serde(default = "default_work_end")]
+    pub work_end: NaiveTime,
+}
+
+impl Default for Watch {
+    fn default() -> Self {
+        Watch {
+            interval: default_watch_interval(),
+            untracked: default_watch_untracked(),
+            timer_limit: None,
+            work_start: default_start_time(),
+            work_end: default_work_end(),
+        }
+    }
+}
+
+/// The working-time account kept by `kimai balance`, from `[balance]`.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Balance {
+    /// First day counted, e.g. when the account was last settled.
+    #[serde(deserialize_with = "deserialize_date")]
+    pub start: NaiveDate,
+    /// Hours of overtime from before `start`, negative for hours owed.
+    #[serde(default)]
+    pub carry_over: f64,
+}
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct Config {
-    pub endpoint: String,
-    pub token: String,
+    /// The server and how to reach it: `endpoint`, `token`, timeouts and
+    /// page sizes.
+    #[serde(flatten)]
+    pub server: kimai_api::Settings,
+    /// Prefer a token stored with `kimai auth login` over `token`.
+    #[serde(default)]
+    pub use_keyring: bool,
+    /// Command printing the token, e.g. `pass show kimai/token`, run once the
+    /// first request needs the token and at most once per run. Takes
+    /// precedence over `token`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_cmd: Option<String>,
     #[serde(default = "default_start_time")]
     pub default_start_time: NaiveTime,
+    /// First day of the week in reports and the calendar picker.
+    #[serde(default = "default_week_start")]
+    pub week_start: Weekday,
+    #[serde(default)]
+    pub time_format: TimeFormat,
+    /// chrono format for dates in output, e.g. `%d.%m.%Y`. Dates can be
+    /// entered this way too.
+    #[serde(default = "default_date_format")]
+    pub date_format: String,
+    /// Pick a customer before picking a project. Small installations with
+    /// only a handful of projects can turn this off.
+    #[serde(default = "default_true")]
+    pub select_customer: bool,
+    /// Timezone to enter and show times in, e.g. `Europe/Berlin`. Defaults to
+    /// the timezone of the user's Kimai profile.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    /// Show how much of a project's budget is left when logging time on it,
+    /// which takes a few extra requests.
+    #[serde(default = "default_true")]
+    pub show_budget: bool,
+    /// Used by reports to tell how much time is missing on workdays.
+    #[serde(default = "default_expected_hours_per_day")]
+    pub expected_hours_per_day: f64,
+    /// Public holidays, on which no hours are expected.
+    #[serde(default, deserialize_with = "deserialize_dates")]
+    pub holidays: Vec<NaiveDate>,
+    /// Also take absences and public holidays from Kimai. They are provided
+    /// by the work contract plugin, so this is off by default.
+    #[serde(default)]
+    pub absences: bool,
+    /// Seconds to keep projects, activities, customers and tags cached on
+    /// disk; 0 disables the cache.
+    #[serde(default = "default_cache_ttl")]
+    pub cache_ttl: u64,
+    /// Offer the branch name and recent commit subjects as descriptions when
+    /// run inside a git repository, as with `--from-git`.
+    #[serde(default)]
+    pub from_git: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub git_rules: Vec<GitRule>,
+    #[serde(default, skip_serializing_if = "Integrations::is_empty")]
+    pub integrations: Integrations,
+    /// Round the begin of new entries, e.g.
+    /// `round_begin = { minutes = 15, direction = "down" }`. Kimai can round
+    /// on the server too; matching its rules shows the final times up front.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub round_begin: Option<rounding::Rule>,
+    /// Round the end of new and stopped entries.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub round_end: Option<rounding::Rule>,
+    /// Round the duration of new and stopped entries, by moving their end.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub round_duration: Option<rounding::Rule>,
+    /// Defaults per project, keyed by project name or id.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub projects: BTreeMap<String, ProjectDefaults>,
+    /// Settings per customer, keyed by customer name or id.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub customers: BTreeMap<String, CustomerSettings>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calendar: Option<Calendar>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub watch: Option<Watch>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance: Option<Balance>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gaps: Option<Gaps>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hooks: Option<Hooks>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub taskwarrior: Option<Taskwarrior>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggest: Option<Suggest>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pomodoro: Option<Pomodoro>,
+    /// Ignore cached data for this invocation (`--refresh`).
+    #[serde(skip)]
+    pub refresh: bool,
+    /// Print requests that would change data instead of sending them (`--dry-run`).
+    #[serde(skip)]
+    pub dry_run: bool,
+    /// Name of the profile these settings were loaded from, if any.
+    #[serde(skip)]
+    pub profile: Option<String>,
+    /// The file these settings were loaded from, if one was found.
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            server: kimai_api::Settings::default(),
+            use_keyring: false,
+            token_cmd: None,
+            default_start_time: default_start_time(),
+            week_start: default_week_start(),
+            time_format: TimeFormat::default(),
+            date_format: default_date_format(),
+            select_customer: true,
+            timezone: None,
+            show_budget: true,
+            expected_hours_per_day: default_expected_hours_per_day(),
+            holidays: Vec::new(),
+            absences: false,
+            cache_ttl: default_cache_ttl(),
+            from_git: false,
+            git_rules: Vec::new(),
+            integrations: Integrations::default(),
+            round_begin: None,
+            round_end: None,
+            round_duration: None,
+            projects: BTreeMap::new(),
+            customers: BTreeMap::new(),
+            calendar: None,
+            watch: None,
+            balance: None,
+            gaps: None,
+            hooks: None,
+            taskwarrior: None,
+            suggest: None,
+            pomodoro: None,
+            refresh: false,
+            dry_run: false,
+            profile: None,
+            path: None,
+        }
+    }
+}
+
+impl Config {
+    /// A file name friendly identifier of the configured Kimai instance, so
+    /// profiles pointing at different servers keep their data apart.
+    pub fn instance_id(&self) -> String {
+        self.server
+            .endpoint
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect()
+    }
+
+    /// The configured defaults for `project`, looked up by id first and then
+    /// by name, ignoring case.
+    pub fn project_defaults(&self, project: &Project) -> Option<&ProjectDefaults> {
+        self.projects.get(&project.id.to_string()).or_else(|| {
+            self.projects
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(&project.name))
+                .map(|(_, defaults)| defaults)
+        })
+    }
+
+    /// The configured settings for `customer`, looked up like
+    /// [`Config::project_defaults`].
+    pub fn customer_settings(&self, customer: &Customer) -> Option<&CustomerSettings> {
+        self.customers.get(&customer.id.to_string()).or_else(|| {
+            self.customers
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(&customer.name))
+                .map(|(_, settings)| settings)
+        })
+    }
+}
+
+/// A date written as a TOML date or as a string, e.g. in environment
+/// variables.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Date {
+    Text(NaiveDate),
+    Toml(toml::value::Datetime),
+}
+
+impl Date {
+    fn into_date<E: serde::de::Error>(self) -> Result<NaiveDate, E> {
+        match self {
+            Date::Text(date) => Ok(date),
+            Date::Toml(datetime) => match datetime.date {
+                Some(date) if datetime.time.is_none() => {
+                    NaiveDate::from_ymd_opt(date.year.into(), date.month.into(), date.day.into())
+                        .ok_or_else(|| E::custom("invalid date"))
+                }
+                _ => Err(E::custom(format!("expected a date, found {}", datetime))),
+            },
+        }
+    }
+}
+
+fn deserialize_date<'de, D>(deserializer: D) -> Result<NaiveDate, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Date::deserialize(deserializer)?.into_date()
+}
+
+fn deserialize_dates<'de, D>(deserializer: D) -> Result<Vec<NaiveDate>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Vec::<Date>::deserialize(deserializer)?
+        .into_iter()
+        .map(Date::into_date)
+        .collect()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_expected_hours_per_day() -> f64 {
+    8.0
+}
+
+fn default_gap_minutes() -> i64 {
+    15
+}
+
+fn default_activitywatch() -> String {
+    "http://localhost:5600".to_string()
+}
+
+fn default_suggest_minutes() -> i64 {
+    10
+}
+
+fn default_work_minutes() -> i64 {
+    25
+}
+
+fn default_break_minutes() -> i64 {
+    5
+}
+
+fn default_cache_ttl() -> u64 {
+    60 * 60
+}
+
+fn default_watch_interval() -> u64 {
+    5
+}
+
+fn default_watch_untracked() -> i64 {
+    30
+}
+
+fn default_work_end() -> NaiveTime {
+    NaiveTime::from_hms_opt(17, 0, 0).unwrap()
+}
+
+fn default_week_start() -> Weekday {
+    Weekday::Mon
+}
+
+fn default_date_format() -> String {
+    "%Y-%m-%d".to_string()
 }
 
 fn default_start_time() -> NaiveTime {
     NaiveTime::from_hms_opt(9, 0, 0).unwrap()
 }
 
-pub fn load_config() -> anyhow::Result<Config> {
-    let config = Figment::new()
-        .merge(Toml::file("kimai.toml"))
-        .merge(Env::prefixed("KIMAI_"))
+/// Places to look for the configuration file, in order of preference.
+fn candidate_paths() -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    if let Some(xdg) = std::env::var_os("XDG_CONFIG_HOME").filter(|v| !v.is_empty()) {
+        paths.push(PathBuf::from(xdg).join("kimai/config.toml"));
+    }
+    if let Some(home) = dirs::home_dir() {
+        paths.push(home.join(".config/kimai/config.toml"));
+    }
+    // ~/Library/Application Support on macOS, %APPDATA% on Windows
+    if let Some(config_dir) = dirs::config_dir() {
+        paths.push(config_dir.join("kimai/config.toml"));
+    }
+    paths.push(PathBuf::from("kimai.toml"));
+    paths
+}
+
+/// Where `kimai config init` writes a new configuration file.
+pub fn default_config_path() -> PathBuf {
+    candidate_paths().remove(0)
+}
+
+/// The configuration file to use: `path` if given, otherwise the first
+/// existing candidate.
+pub fn find_config_file(path: Option<&Path>) -> Option<PathBuf> {
+    match path {
+        Some(path) => Some(path.to_path_buf()),
+        None => candidate_paths().into_iter().find(|p| p.is_file()),
+    }
+}
+
+/// Load the configuration. Settings at the top level of the file apply to
+/// every profile; a selected profile's `[profiles.<name>]` table overrides
+/// them, and environment variables override both.
+pub fn load_config(profile: Option<&str>, path: Option<&Path>) -> anyhow::Result<Config> {
+    let path = find_config_file(path);
+    if let Some(path) = path.as_ref().filter(|p| !p.is_file()) {
+        anyhow::bail!("Config file {} does not exist", path.display());
+    }
+
+    let file = match &path {
+        Some(path) => Figment::new().merge(Toml::file(path)),
+        None => Figment::new(),
+    };
+    let mut figment = file.clone();
+
+    if let Some(profile) = profile {
+        let key = format!("profiles.{}", profile);
+        if file.find_value(&key).is_err() {
+            match &path {
+                Some(path) => {
+                    anyhow::bail!("Profile '{}' not found in {}", profile, path.display())
+                }
+                None => anyhow::bail!("Profile '{}' not found, no config file", profile),
+            }
+        }
+        figment = figment.merge(file.focus(&key));
+    }
+
+    let mut config: Config = figment
+        .merge(Env::prefixed("KIMAI_").ignore(&["PROFILE", "CONFIG"]))
         .extract()?;
+    config.profile = profile.map(String::from);
+    config.path = path;
+
     Ok(config)
 }
+
+/// Tokens looked up from the keyring or `token_cmd`, by profile and command.
+static TOKENS: std::sync::Mutex<BTreeMap<String, String>> = std::sync::Mutex::new(BTreeMap::new());
+
+/// The API token: from the keyring with `use_keyring`, or else from
+/// `token_cmd`, or else as configured. Only asked for once a request needs
+/// it, so completions and prompts don't wait for a password manager, and
+/// then only once per run.
+pub fn token(config: &Config) -> anyhow::Result<String> {
+    if !config.use_keyring && config.token_cmd.is_none() {
+        return Ok(config.server.token.clone());
+    }
+    let key = format!("{:?} {:?}", config.profile, config.token_cmd);
+    let mut tokens = TOKENS.lock().unwrap();
+    if let Some(token) = tokens.get(&key) {
+        return Ok(token.clone());
+    }
+    let mut token = None;
+    if config.use_keyring {
+        match keyring_entry(config.profile.as_deref())?.get_password() {
+            Ok(stored) => token = Some(stored),
+            // Fall back to token_cmd or the configured token, if any
+            Err(keyring::Error::NoEntry) => {}
+            Err(e) => anyhow::bail!("Could not read token from keyring: {}", e),
+        }
+    }
+    let token = match (token, &config.token_cmd) {
+        (Some(token), _) => token,
+        (None, Some(command)) => run_token_cmd(command)?,
+        (None, None) => config.server.token.clone(),
+    };
+    tokens.insert(key, token.clone());
+    Ok(token)
+}
+
+/// Run `token_cmd` through the shell and take its output as the token.
+fn run_token_cmd(command: &str) -> anyhow::Result<String> {
+    let output = if cfg!(windows) {
+        std::process::Command::new("cmd")
+            .args(["/C", command])
+            .output()
+    } else {
+        std::process::Command::new("sh")
+            .args(["-c", command])
+            .output()
+    }
+    .map_err(|e| anyhow::anyhow!("Could not run token_cmd `{}`: {}", command, e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!(
+            "token_cmd `{}` failed with {}\n{}",
+            command,
+            output.status,
+            stderr.trim()
+        );
+    }
+    let token = String::from_utf8(output.stdout)
+        .map_err(|_| anyhow::anyhow!("token_cmd `{}` printed invalid UTF-8", command))?
+        .trim()
+        .to_string();
+    if token.is_empty() {
+        anyhow::bail!("token_cmd `{}` printed nothing", command);
+    }
+    Ok(token)
+}
+
+/// The keyring entry holding the token for a profile.
+pub fn keyring_entry(profile: Option<&str>) -> anyhow::Result<keyring::Entry> {
+    Ok(keyring::Entry::new(
+        "kimai-cli",
+        profile.unwrap_or("default"),
+    )?)
+}
+
+/// Write `values` to `path`, either at the top level or as a profile. Other
+/// settings and profiles already in the file are kept. The file may hold the
+/// token, so only its owner gets to read it.
+pub fn save_config(values: toml::Table, path: &Path, profile: Option<&str>) -> anyhow::Result<()> {
+    let mut document = read_document(path)?;
+    match profile {
+        Some(profile) => {
+            let profiles = document
+                .entry("profiles")
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+            match profiles {
+                toml::Value::Table(profiles) => {
+                    profiles.insert(profile.to_string(), toml::Value::Table(values));
+                }
+                _ => anyhow::bail!("`profiles` in {} is not a table", path.display()),
+            }
+        }
+        None => document.extend(values),
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        // An existing file keeps its mode otherwise
+        if path.is_file() {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        }
+    }
+    let mut file = options.open(path)?;
+    std::io::Write::write_all(&mut file, toml::to_string_pretty(&document)?.as_bytes())?;
+    Ok(())
+}
+
+/// The config file at `path` as a table, empty if there is none yet.
+fn read_document(path: &Path) -> anyhow::Result<toml::Table> {
+    Ok(if path.is_file() {
+        std::fs::read_to_string(path)?.parse()?
+    } else {
+        toml::Table::new()
+    })
+}
+
+/// Whether the file at `path` has a `[profiles.<profile>]` section.
+pub fn has_profile(path: &Path, profile: &str) -> anyhow::Result<bool> {
+    Ok(read_document(path)?
+        .get("profiles")
+        .and_then(|profiles| profiles.get(profile))
+        .is_some())
+}
@@ -0,0 +1,50 @@
+//! Patterns the descriptions on a project must match, e.g. a ticket number
+//! for the customer's billing, from `description_pattern` in `[projects]`.
+use anyhow::Result;
+use regex::Regex;
+
+use crate::invalid::invalid;
+use crate::{api, config};
+
+/// The pattern descriptions on `project` must match, if it has one.
+pub fn pattern(config: &config::Config, project: i32) -> Result<Option<Regex>> {
+    // Most configurations have none, and then need no projects either
+    if config
+        .projects
+        .values()
+        .all(|p| p.description_pattern.is_none())
+    {
+        return Ok(None);
+    }
+    let Some(project) = api::get_projects(config)?
+        .into_iter()
+        .find(|p| p.id == project)
+    else {
+        return Ok(None);
+    };
+    let Some(pattern) = config
+        .project_defaults(&project)
+        .and_then(|p| p.description_pattern.as_deref())
+    else {
+        return Ok(None);
+    };
+    Regex::new(pattern)
+        .map(Some)
+        .map_err(|e| anyhow::anyhow!("Invalid description pattern '{}': {}", pattern, e))
+}
+
+/// Fail if `description` doesn't match the pattern of `project`.
+pub fn check(config: &config::Config, project: i32, description: Option<&str>) -> Result<()> {
+    let Some(pattern) = pattern(config, project)? else {
+        return Ok(());
+    };
+    let description = description.unwrap_or_default();
+    if !pattern.is_match(description) {
+        return Err(invalid!(
+            "Descriptions on this project must match '{}', '{}' doesn't",
+            pattern,
+            description
+        ));
+    }
+    Ok(())
+}
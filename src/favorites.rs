@@ -0,0 +1,34 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{config, state};
+
+/// Projects and activities to list first in pickers, by id.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Favorites {
+    #[serde(default)]
+    pub projects: Vec<i32>,
+    #[serde(default)]
+    pub activities: Vec<i32>,
+}
+
+const FAVORITES: &str = "favorites";
+
+pub fn load(config: &config::Config) -> Result<Favorites> {
+    state::load(config, FAVORITES)
+}
+
+pub fn save(config: &config::Config, favorites: &Favorites) -> Result<()> {
+    state::save(config, FAVORITES, favorites)
+}
+
+/// Move the items whose id is in `favorites` to the front, in the order
+/// they were added, keeping the order of everything else.
+pub fn sort_first<T>(items: Vec<T>, favorites: &[i32], id: impl Fn(&T) -> i32) -> Vec<T> {
+    let (mut first, rest): (Vec<T>, Vec<T>) = items
+        .into_iter()
+        .partition(|item| favorites.contains(&id(item)));
+    first.sort_by_key(|item| favorites.iter().position(|f| *f == id(item)));
+    first.extend(rest);
+    first
+}
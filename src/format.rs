@@ -0,0 +1,259 @@
+use anyhow::Result;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, TimeZone};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+use crate::api::TimesheetExpanded;
+use crate::{clock, config};
+
+/// The configured `time_format` and `date_format`.
+struct Formats {
+    time: &'static str,
+    date: String,
+}
+
+static FORMATS: OnceLock<Formats> = OnceLock::new();
+
+/// Take the formats from `config`, checking the date format up front rather
+/// than failing halfway through printing.
+pub fn init(config: &config::Config) -> Result<()> {
+    if StrftimeItems::new(&config.date_format).any(|item| item == Item::Error) {
+        anyhow::bail!(
+            "Invalid date_format '{}', expected e.g. %d.%m.%Y",
+            config.date_format
+        );
+    }
+    let time = match config.time_format {
+        config::TimeFormat::Hours24 => "%H:%M",
+        config::TimeFormat::Hours12 => "%-I:%M %p",
+    };
+    let _ = FORMATS.set(Formats {
+        time,
+        date: config.date_format.clone(),
+    });
+    Ok(())
+}
+
+pub fn time_format() -> &'static str {
+    FORMATS.get().map_or("%H:%M", |f| f.time)
+}
+
+pub fn date_format() -> &'static str {
+    FORMATS.get().map_or("%Y-%m-%d", |f| f.date.as_str())
+}
+
+/// A time of day, e.g. `14:30` or `2:30 PM`.
+pub fn time(time: NaiveTime) -> String {
+    time.format(time_format()).to_string()
+}
+
+/// A date, e.g. `2024-05-01`.
+pub fn date(date: NaiveDate) -> String {
+    date.format(date_format()).to_string()
+}
+
+/// A date with its weekday, e.g. `Wed 2024-05-01`.
+pub fn day(date: NaiveDate) -> String {
+    format!("{} {}", date.format("%a"), self::date(date))
+}
+
+static COLOR: AtomicBool = AtomicBool::new(false);
+
+/// Turn colored output on or off; it's off until the command line is read.
+pub fn set_color(enabled: bool) {
+    COLOR.store(enabled, Ordering::Relaxed);
+}
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Leave out messages about what was done, for `--quiet`.
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+pub fn quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// `println!` for messages about what was done, e.g. that a timer was
+/// started, which `--quiet` leaves out. What was asked for, like a list of
+/// entries, is printed regardless.
+macro_rules! note {
+    ($($arg:tt)*) => {
+        if !$crate::format::quiet() {
+            println!($($arg)*);
+        }
+    };
+}
+pub(crate) use note;
+
+#[derive(Clone, Copy, Debug)]
+pub enum Style {
+    Bold,
+    Dim,
+    Red,
+    Green,
+    Yellow,
+}
+
+/// `text` in `style`, if colors are on.
+pub fn paint(text: &str, style: Style) -> String {
+    if !COLOR.load(Ordering::Relaxed) || text.is_empty() {
+        return text.to_string();
+    }
+    let code = match style {
+        Style::Bold => "1",
+        Style::Dim => "2",
+        Style::Red => "31",
+        Style::Green => "32",
+        Style::Yellow => "33",
+    };
+    format!("\x1b[{}m{}\x1b[0m", code, text)
+}
+
+/// A project name in a color picked from the name, so a project looks the
+/// same in every table.
+pub fn project(name: &str) -> String {
+    if !COLOR.load(Ordering::Relaxed) {
+        return name.to_string();
+    }
+    // Blue, magenta, cyan and their bright variants; red, green and yellow
+    // already mean something
+    const COLORS: [&str; 6] = ["34", "35", "36", "94", "95", "96"];
+    let hash = name
+        .bytes()
+        .fold(0u32, |hash, b| hash.wrapping_mul(31).wrapping_add(b.into()));
+    let code = COLORS[hash as usize % COLORS.len()];
+    format!("\x1b[{}m{}\x1b[0m", code, name)
+}
+
+/// Characters a terminal shows for `text`, leaving out color codes.
+fn width(text: &str) -> usize {
+    let mut width = 0;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().find(|c| *c == 'm');
+        } else {
+            width += 1;
+        }
+    }
+    width
+}
+
+/// Format a duration as `H:MM`, e.g. `1:05`.
+pub fn duration(duration: Duration) -> String {
+    let minutes = duration.num_minutes();
+    let sign = if minutes < 0 { "-" } else { "" };
+    let minutes = minutes.abs();
+    format!("{}{}:{:02}", sign, minutes / 60, minutes % 60)
+}
+
+/// A bar `width` characters wide, filled by `fraction` (0 to 1), e.g.
+/// `█████░░░░░`.
+pub fn bar(fraction: f64, width: usize) -> String {
+    let filled = ((fraction.clamp(0.0, 1.0) * width as f64).round() as usize).min(width);
+    format!("{}{}", "█".repeat(filled), "░".repeat(width - filled))
+}
+
+/// Format the time between two points as `09:00–10:30 (1:30)`.
+pub fn span<Tz: TimeZone>(begin: DateTime<Tz>, end: DateTime<Tz>) -> String {
+    format!(
+        "{}–{} ({})",
+        time(begin.with_timezone(&clock::zone()).time()),
+        time(end.with_timezone(&clock::zone()).time()),
+        duration(end - begin)
+    )
+}
+
+/// Format an amount of money with two decimals, e.g. `1250.00`.
+pub fn money(amount: f64) -> String {
+    format!("{:.2}", amount)
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+/// Print rows as aligned columns below a header line.
+pub fn table<H: AsRef<str>>(headers: &[(H, Align)], rows: &[Vec<String>]) {
+    let mut widths: Vec<usize> = headers.iter().map(|(h, _)| width(h.as_ref())).collect();
+    for row in rows {
+        for (column, cell) in widths.iter_mut().zip(row) {
+            *column = (*column).max(width(cell));
+        }
+    }
+
+    // Padding by hand, as format! would count the color codes
+    let print_row = |cells: Vec<String>| {
+        let line: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .zip(headers)
+            .map(|((cell, &column), (_, align))| {
+                let padding = " ".repeat(column - width(cell));
+                match align {
+                    Align::Left => format!("{}{}", cell, padding),
+                    Align::Right => format!("{}{}", padding, cell),
+                }
+            })
+            .collect();
+        println!("{}", line.join("  ").trim_end());
+    };
+
+    print_row(
+        headers
+            .iter()
+            .map(|(h, _)| paint(h.as_ref(), Style::Bold))
+            .collect(),
+    );
+    for row in rows {
+        print_row(row.clone());
+    }
+}
+
+/// One-line summary of an entry, used in pickers.
+pub fn entry(t: &TimesheetExpanded) -> String {
+    format!(
+        "#{} {} {} {} {} / {} {}",
+        t.id,
+        day(t.begin.date_naive()),
+        time(t.begin.time()),
+        duration(Duration::seconds(t.duration)),
+        t.project.name,
+        t.activity.name,
+        first_line(t.description.as_deref().unwrap_or_default())
+    )
+}
+
+/// The first line of a description written in an editor, marked as cut short
+/// if there are more.
+pub fn first_line(text: &str) -> String {
+    match text.split_once('\n') {
+        Some((first, _)) => format!("{}…", first.trim_end()),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn width_without_color_codes() {
+        assert_eq!(width("Acme"), 4);
+        assert_eq!(width("\x1b[34mAcme\x1b[0m"), 4);
+        assert_eq!(width("09:00–10:30"), 11);
+    }
+
+    #[test]
+    fn bar_fills_by_fraction() {
+        assert_eq!(bar(0.0, 4), "░░░░");
+        assert_eq!(bar(0.5, 4), "██░░");
+        assert_eq!(bar(1.5, 4), "████");
+        assert_eq!(bar(-1.0, 4), "░░░░");
+    }
+}
@@ -0,0 +1,80 @@
+use anyhow::Result;
+use regex::Regex;
+use std::process::Command;
+
+use crate::config::{self, GitRule};
+
+/// Number of commit subjects offered as descriptions.
+const COMMITS: usize = 5;
+
+/// Run git in the current directory, `None` if that fails for any reason.
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// What the repository in the current directory tells about the work.
+#[derive(Debug)]
+pub struct Context {
+    /// The checked out branch, unless HEAD is detached.
+    pub branch: Option<String>,
+    /// Subjects of the most recent commits, newest first.
+    pub commits: Vec<String>,
+}
+
+impl Context {
+    /// `None` outside of a git repository or without git installed.
+    pub fn current() -> Option<Context> {
+        git(&["rev-parse", "--is-inside-work-tree"]).filter(|inside| inside == "true")?;
+        let branch = git(&["rev-parse", "--abbrev-ref", "HEAD"]).filter(|b| b != "HEAD");
+        let commits = git(&["log", &format!("-{}", COMMITS), "--format=%s"])
+            .map(|log| log.lines().map(String::from).collect())
+            .unwrap_or_default();
+        Some(Context { branch, commits })
+    }
+
+    /// The branch name and commit subjects, without duplicates.
+    pub fn descriptions(&self) -> Vec<String> {
+        let mut descriptions: Vec<String> = Vec::new();
+        for candidate in self.branch.iter().chain(&self.commits) {
+            if !candidate.is_empty() && !descriptions.contains(candidate) {
+                descriptions.push(candidate.clone());
+            }
+        }
+        descriptions
+    }
+
+    /// The first of the configured rules matching the branch.
+    pub fn rule<'a>(&self, config: &'a config::Config) -> Result<Option<&'a GitRule>> {
+        let Some(branch) = &self.branch else {
+            return Ok(None);
+        };
+        for rule in &config.git_rules {
+            let pattern = Regex::new(&rule.branch)
+                .map_err(|e| anyhow::anyhow!("Invalid branch pattern '{}': {}", rule.branch, e))?;
+            if pattern.is_match(branch) {
+                return Ok(Some(rule));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Project and activity to look for: those given on the command line, or
+/// else those of the rule matching the branch.
+pub fn needles<'a>(
+    rule: Option<&'a GitRule>,
+    project: Option<&'a str>,
+    activity: Option<&'a str>,
+) -> (Option<&'a str>, Option<&'a str>) {
+    match (project, rule) {
+        (None, Some(rule)) => (
+            Some(rule.project.as_str()),
+            activity.or(rule.activity.as_deref()),
+        ),
+        _ => (project, activity),
+    }
+}
@@ -0,0 +1,269 @@
+//! Commands and webhooks run after an entry was created, from `[hooks]`, e.g.
+//! for a desktop notification or a chat message.
+use anyhow::Result;
+use serde::Serialize;
+use std::process::Command;
+
+use crate::api::{self, Timesheet};
+use crate::{clock, config, format};
+
+/// What a hook gets to know about the entry.
+#[derive(Debug, Serialize)]
+struct Created {
+    id: i32,
+    date: String,
+    begin: String,
+    end: String,
+    duration: String,
+    customer: String,
+    project: String,
+    activity: String,
+    description: String,
+    tags: String,
+    /// Summary for webhooks that show a message, like Slack's.
+    text: String,
+}
+
+impl Created {
+    fn new(config: &config::Config, entry: &Timesheet) -> Created {
+        // Names are only for reading, an id will do when they can't be found
+        let project = api::get_projects(config)
+            .ok()
+            .and_then(|projects| projects.into_iter().find(|p| p.id == entry.project));
+        let activity = api::get_activities(config)
+            .ok()
+            .and_then(|activities| activities.into_iter().find(|a| a.id == entry.activity))
+            .map(|a| a.name)
+            .unwrap_or_else(|| entry.activity.to_string());
+        let (customer, project) = match project {
+            Some(project) => (project.parent_title.unwrap_or_default(), project.name),
+            None => (String::new(), entry.project.to_string()),
+        };
+
+        let zone = clock::zone();
+        let begin = entry.begin.with_timezone(&zone);
+        let end = entry.end.map(|end| end.with_timezone(&zone));
+        let duration = end
+            .map(|end| format::duration(end - begin))
+            .unwrap_or_default();
+        let description = entry.description.clone().unwrap_or_default();
+        let text = match &end {
+            Some(_) => format!("Logged {} on {} ({})", duration, project, activity),
+            None => format!("Started a timer on {} ({})", project, activity),
+        };
+        let text = if description.is_empty() {
+            text
+        } else {
+            format!("{}: {}", text, description)
+        };
+        Created {
+            id: entry.id,
+            date: begin.date_naive().to_string(),
+            begin: format::time(begin.time()),
+            end: end.map(|end| format::time(end.time())).unwrap_or_default(),
+            duration,
+            customer,
+            project,
+            activity,
+            description,
+            tags: entry.tags.join(", "),
+            text,
+        }
+    }
+
+    fn values(&self) -> [(&str, String); 11] {
+        [
+            ("id", self.id.to_string()),
+            ("date", self.date.clone()),
+            ("begin", self.begin.clone()),
+            ("end", self.end.clone()),
+            ("duration", self.duration.clone()),
+            ("customer", self.customer.clone()),
+            ("project", self.project.clone()),
+            ("activity", self.activity.clone()),
+            ("description", self.description.clone()),
+            ("tags", self.tags.clone()),
+            ("text", self.text.clone()),
+        ]
+    }
+}
+
+/// `template` with each `{name}` of `values` replaced by its value; unknown
+/// placeholders are left as they are.
+// Commands on Unix are filled with quoting, by `fill_shell`
+#[cfg_attr(not(any(windows, feature = "slack")), allow(dead_code))]
+pub fn fill(template: &str, values: &[(&str, String)]) -> String {
+    let mut filled = template.to_string();
+    for (name, value) in values {
+        filled = filled.replace(&format!("{{{}}}", name), value);
+    }
+    filled
+}
+
+/// `value` quoted for sh where it's substituted: outside quotes as a single
+/// quoted word, and escaped within the quotes around it otherwise.
+#[cfg(not(windows))]
+fn quoted(value: &str, single: bool, double: bool) -> String {
+    if single {
+        // Close the quotes, add an escaped quote, open them again
+        value.replace('\'', "'\\''")
+    } else if double {
+        let mut escaped = String::new();
+        for c in value.chars() {
+            if matches!(c, '\\' | '"' | '$' | '`') {
+                escaped.push('\\');
+            }
+            escaped.push(c);
+        }
+        escaped
+    } else {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+}
+
+/// `fill` for a command run by sh, with every value quoted so whatever is
+/// in a description stays text for the command, e.g. a calendar event
+/// titled `'; rm -rf ~; '`.
+#[cfg(not(windows))]
+fn fill_shell(template: &str, values: &[(&str, String)]) -> String {
+    let mut filled = String::new();
+    let (mut single, mut double) = (false, false);
+    let mut rest = template;
+    while let Some(c) = rest.chars().next() {
+        if c == '{' {
+            let value = values.iter().find(|(name, _)| {
+                rest[1..]
+                    .strip_prefix(name)
+                    .is_some_and(|r| r.starts_with('}'))
+            });
+            if let Some((name, value)) = value {
+                filled.push_str(&quoted(value, single, double));
+                rest = &rest[name.len() + 2..];
+                continue;
+            }
+        }
+        match c {
+            '\'' if !double => single = !single,
+            '"' if !single => double = !double,
+            // The escaped character is taken as it is
+            '\\' if !single => {
+                filled.push(c);
+                rest = &rest[1..];
+                let Some(next) = rest.chars().next() else {
+                    break;
+                };
+                filled.push(next);
+                rest = &rest[next.len_utf8()..];
+                continue;
+            }
+            _ => {}
+        }
+        filled.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    filled
+}
+
+/// The process for `command`: sh with the values quoted in it. cmd can't
+/// quote reliably, so on Windows the command is split into words and run
+/// without a shell. Returns the process and the command to show in errors.
+#[cfg(not(windows))]
+fn process(command: &str, values: &[(&str, String)]) -> Result<(Command, String)> {
+    let command = fill_shell(command, values);
+    let mut process = Command::new("sh");
+    process.args(["-c", &command]);
+    Ok((process, command))
+}
+
+#[cfg(windows)]
+fn process(command: &str, values: &[(&str, String)]) -> Result<(Command, String)> {
+    let words = shlex::split(command)
+        .filter(|words| !words.is_empty())
+        .ok_or_else(|| anyhow::anyhow!("Could not split `{}` into words", command))?;
+    let words: Vec<String> = words.iter().map(|word| fill(word, values)).collect();
+    let mut process = Command::new(&words[0]);
+    process.args(&words[1..]);
+    Ok((process, words.join(" ")))
+}
+
+/// Run `command` for `created`, with its values in `KIMAI_*` variables as
+/// well.
+fn run(command: &str, created: &Created) -> Result<()> {
+    let values = created.values();
+    let (mut process, shown) = process(command, &values)?;
+    for (name, value) in &values {
+        process.env(format!("KIMAI_{}", name.to_uppercase()), value);
+    }
+    let status = process
+        .status()
+        .map_err(|e| anyhow::anyhow!("Could not run `{}`: {}", shown, e))?;
+    if !status.success() {
+        anyhow::bail!("`{}` failed with {}", shown, status);
+    }
+    Ok(())
+}
+
+fn post(config: &config::Config, url: &str, created: &Created) -> Result<()> {
+    api::agent(config)?
+        .post(url)
+        .send_json(created)
+        .map_err(|e| anyhow::anyhow!("Webhook failed: {}", e))?;
+    Ok(())
+}
+
+/// Run the hooks for a newly created `entry`. The entry exists either way, so
+/// a failing hook is only reported.
+pub fn entry_created(config: &config::Config, entry: &Timesheet) {
+    let Some(hooks) = &config.hooks else {
+        return;
+    };
+    if hooks.on_entry_created.is_none() && hooks.webhook.is_none() {
+        return;
+    }
+    let created = Created::new(config, entry);
+    if let Some(command) = &hooks.on_entry_created {
+        if let Err(e) = run(command, &created) {
+            eprintln!("Warning: on_entry_created hook: {:#}", e);
+        }
+    }
+    if let Some(url) = &hooks.webhook {
+        if let Err(e) = post(config, url, &created) {
+            eprintln!("Warning: {:#}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fills_known_placeholders() {
+        let values = [
+            ("duration", "1:30".to_string()),
+            ("project", "Acme".to_string()),
+        ];
+        assert_eq!(
+            fill("Logged {duration} on {project} {unknown}", &values),
+            "Logged 1:30 on Acme {unknown}"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn descriptions_stay_text_for_the_shell() {
+        let description = "It's done'; echo pwned; '\"$(echo pwned)\"`echo pwned`";
+        let values = [("description", description.to_string())];
+        for template in [
+            "printf %s {description}",
+            "printf %s '{description}'",
+            "printf %s \"{description}\"",
+        ] {
+            let output = Command::new("sh")
+                .args(["-c", &fill_shell(template, &values)])
+                .output()
+                .unwrap();
+            assert_eq!(String::from_utf8_lossy(&output.stdout), description);
+        }
+    }
+}
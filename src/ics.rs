@@ -0,0 +1,469 @@
+//! Just enough of iCalendar (RFC 5545) to list the meetings on a few days.
+//!
+//! Daily and weekly recurrences are expanded, with `BYDAY`, `INTERVAL`,
+//! `COUNT`, `UNTIL`, `EXDATE` and moved occurrences (`RECURRENCE-ID`). Other
+//! recurrences only show up on their first day. All-day and cancelled events
+//! are left out, since there's no meeting time to log for them.
+use anyhow::{Context, Result};
+use chrono::{
+    DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday,
+};
+use std::collections::BTreeMap;
+
+use crate::clock;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    pub summary: String,
+    pub begin: DateTime<clock::Zone>,
+    pub end: DateTime<clock::Zone>,
+}
+
+/// How to read the wall clock time of a property.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Zone {
+    Utc,
+    Named(chrono_tz::Tz),
+    /// No zone given, or one we don't know, e.g. a Windows zone name. Such
+    /// times are most likely meant in the user's own zone.
+    Floating,
+}
+
+impl Zone {
+    fn resolve(self, time: NaiveDateTime) -> Option<DateTime<clock::Zone>> {
+        match self {
+            Zone::Utc => Some(Utc.from_utc_datetime(&time).with_timezone(&clock::zone())),
+            Zone::Named(tz) => tz
+                .from_local_datetime(&time)
+                .earliest()
+                .map(|t| t.with_timezone(&clock::zone())),
+            Zone::Floating => clock::zone().from_local_datetime(&time).earliest(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Stamp {
+    time: NaiveDateTime,
+    zone: Zone,
+    all_day: bool,
+}
+
+impl Stamp {
+    fn resolve(&self) -> Option<DateTime<clock::Zone>> {
+        self.zone.resolve(self.time)
+    }
+}
+
+struct Property<'a> {
+    name: String,
+    params: BTreeMap<String, &'a str>,
+    value: &'a str,
+}
+
+#[derive(Default)]
+struct RawEvent {
+    uid: String,
+    summary: String,
+    start: Option<Stamp>,
+    end: Option<Stamp>,
+    duration: Option<Duration>,
+    rrule: Option<String>,
+    exdates: Vec<DateTime<clock::Zone>>,
+    recurrence_id: Option<DateTime<clock::Zone>>,
+    cancelled: bool,
+}
+
+/// Undo line folding: a line starting with a space or tab continues the
+/// previous one.
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+fn property(line: &str) -> Option<Property<'_>> {
+    // The value starts at the first colon outside of quoted parameter values
+    let mut quoted = false;
+    let colon = line.char_indices().find_map(|(i, c)| match c {
+        '"' => {
+            quoted = !quoted;
+            None
+        }
+        ':' if !quoted => Some(i),
+        _ => None,
+    })?;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+    let mut parts = head.split(';');
+    let name = parts.next()?.to_ascii_uppercase();
+    let params = parts
+        .filter_map(|param| param.split_once('='))
+        .map(|(key, value)| (key.to_ascii_uppercase(), value.trim_matches('"')))
+        .collect();
+    Some(Property {
+        name,
+        params,
+        value,
+    })
+}
+
+fn unescape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => result.push(' '),
+            Some(other) => result.push(other),
+            None => {}
+        }
+    }
+    result
+}
+
+fn zone(params: &BTreeMap<String, &str>, value: &str) -> Zone {
+    if value.ends_with('Z') {
+        Zone::Utc
+    } else {
+        params
+            .get("TZID")
+            .and_then(|tzid| tzid.parse().ok())
+            .map_or(Zone::Floating, Zone::Named)
+    }
+}
+
+fn stamp(params: &BTreeMap<String, &str>, value: &str) -> Option<Stamp> {
+    let zone = zone(params, value);
+    let value = value.trim_end_matches('Z');
+    if let Ok(time) = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S") {
+        return Some(Stamp {
+            time,
+            zone,
+            all_day: false,
+        });
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+    Some(Stamp {
+        time: date.and_time(NaiveTime::MIN),
+        zone,
+        all_day: true,
+    })
+}
+
+/// Parse a duration value like `PT1H30M` or `P1D`.
+fn duration(value: &str) -> Option<Duration> {
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.trim_start_matches('+')),
+    };
+    let value = value.strip_prefix('P')?;
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    for c in value.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            unit => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                total += match unit {
+                    'W' => Duration::weeks(n),
+                    'D' => Duration::days(n),
+                    'H' => Duration::hours(n),
+                    'M' => Duration::minutes(n),
+                    'S' => Duration::seconds(n),
+                    _ => return None,
+                };
+            }
+        }
+    }
+    Some(if negative { -total } else { total })
+}
+
+fn weekday(code: &str) -> Option<Weekday> {
+    // Monthly rules may prefix the day with its position, e.g. 2TU
+    let code = code.trim_start_matches(|c: char| c.is_ascii_digit() || c == '-' || c == '+');
+    Some(match code {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+/// Starts of the occurrences of a recurring event, in its own zone, up to and
+/// including `last`.
+fn occurrences(start: Stamp, rule: &str, last: NaiveDate) -> Vec<NaiveDateTime> {
+    let parts: BTreeMap<&str, &str> = rule.split(';').filter_map(|p| p.split_once('=')).collect();
+    let interval: i64 = parts
+        .get("INTERVAL")
+        .and_then(|i| i.parse().ok())
+        .filter(|i| *i > 0)
+        .unwrap_or(1);
+    let count: Option<usize> = parts.get("COUNT").and_then(|c| c.parse().ok());
+    let until = parts.get("UNTIL").and_then(|until| {
+        let until = stamp(&BTreeMap::new(), until)?;
+        if until.all_day {
+            // A date includes that whole day
+            Some(until.time + Duration::days(1) - Duration::seconds(1))
+        } else {
+            // Compare in the zone the occurrences are counted in
+            Some(match start.zone {
+                Zone::Named(tz) if until.zone == Zone::Utc => {
+                    tz.from_utc_datetime(&until.time).naive_local()
+                }
+                Zone::Floating if until.zone == Zone::Utc => {
+                    clock::zone().from_utc_datetime(&until.time).naive_local()
+                }
+                _ => until.time,
+            })
+        }
+    });
+    let days: Vec<Weekday> = parts
+        .get("BYDAY")
+        .map(|days| days.split(',').filter_map(weekday).collect())
+        .unwrap_or_default();
+
+    let candidates: Box<dyn Iterator<Item = NaiveDateTime>> = match parts.get("FREQ").copied() {
+        Some("DAILY") => Box::new(
+            (0..)
+                .map(move |n| start.time + Duration::days(n * interval))
+                .filter(move |t| days.is_empty() || days.contains(&t.weekday())),
+        ),
+        Some("WEEKLY") => {
+            let days = if days.is_empty() {
+                vec![start.time.weekday()]
+            } else {
+                days
+            };
+            let monday = start.time.date()
+                - Duration::days(start.time.weekday().num_days_from_monday().into());
+            Box::new(
+                (0..)
+                    .flat_map(move |week| {
+                        let mut dates: Vec<NaiveDate> = days
+                            .iter()
+                            .map(|day| {
+                                monday
+                                    + Duration::weeks(week * interval)
+                                    + Duration::days(day.num_days_from_monday().into())
+                            })
+                            .collect();
+                        dates.sort();
+                        dates
+                    })
+                    .map(move |date| date.and_time(start.time.time()))
+                    .filter(move |t| *t >= start.time),
+            )
+        }
+        _ => Box::new(std::iter::once(start.time)),
+    };
+
+    let mut result = Vec::new();
+    for (n, time) in candidates.enumerate() {
+        if time.date() > last
+            || count.is_some_and(|count| n >= count)
+            || until.is_some_and(|until| time > until)
+        {
+            break;
+        }
+        result.push(time);
+    }
+    result
+}
+
+/// The events in `text` that begin between `from` and `to`, sorted by their
+/// start.
+pub fn events(text: &str, from: NaiveDate, to: NaiveDate) -> Result<Vec<Event>> {
+    let mut raw = Vec::new();
+    let mut current: Option<RawEvent> = None;
+    for line in unfold(text) {
+        let Some(property) = property(&line) else {
+            continue;
+        };
+        match (property.name.as_str(), property.value, current.as_mut()) {
+            ("BEGIN", "VEVENT", _) => current = Some(RawEvent::default()),
+            ("END", "VEVENT", Some(_)) => raw.extend(current.take()),
+            (_, _, None) => {}
+            ("UID", value, Some(event)) => event.uid = value.to_string(),
+            ("SUMMARY", value, Some(event)) => event.summary = unescape(value).trim().to_string(),
+            ("DTSTART", value, Some(event)) => event.start = stamp(&property.params, value),
+            ("DTEND", value, Some(event)) => event.end = stamp(&property.params, value),
+            ("DURATION", value, Some(event)) => event.duration = duration(value),
+            ("RRULE", value, Some(event)) => event.rrule = Some(value.to_string()),
+            ("EXDATE", value, Some(event)) => event.exdates.extend(
+                value
+                    .split(',')
+                    .filter_map(|v| stamp(&property.params, v))
+                    .filter_map(|s| s.resolve()),
+            ),
+            ("RECURRENCE-ID", value, Some(event)) => {
+                event.recurrence_id = stamp(&property.params, value).and_then(|s| s.resolve())
+            }
+            ("STATUS", value, Some(event)) => {
+                event.cancelled = value.eq_ignore_ascii_case("CANCELLED")
+            }
+            _ => {}
+        }
+    }
+    if raw.is_empty() && !text.contains("BEGIN:VCALENDAR") {
+        anyhow::bail!("This does not look like an iCalendar file");
+    }
+
+    // Occurrences that were moved or changed are listed as events of their own
+    let moved: Vec<(&str, DateTime<clock::Zone>)> = raw
+        .iter()
+        .filter_map(|e| e.recurrence_id.map(|id| (e.uid.as_str(), id)))
+        .collect();
+
+    let mut events = Vec::new();
+    for event in &raw {
+        let Some(start) = event.start.filter(|s| !s.all_day) else {
+            continue;
+        };
+        if event.cancelled {
+            continue;
+        }
+        let length = match (event.end, event.duration) {
+            (Some(end), _) => {
+                let end = end.resolve().context("Invalid end time")?;
+                end - start.resolve().context("Invalid start time")?
+            }
+            (None, Some(duration)) => duration,
+            (None, None) => Duration::zero(),
+        };
+        if length <= Duration::zero() {
+            continue;
+        }
+
+        let starts = match (&event.rrule, event.recurrence_id) {
+            (Some(rule), None) => occurrences(start, rule, to + Duration::days(1)),
+            _ => vec![start.time],
+        };
+        for time in starts {
+            let Some(begin) = start.zone.resolve(time) else {
+                continue;
+            };
+            let date = begin.date_naive();
+            if date < from || date > to || event.exdates.contains(&begin) {
+                continue;
+            }
+            if event.recurrence_id.is_none() && moved.contains(&(event.uid.as_str(), begin)) {
+                continue;
+            }
+            events.push(Event {
+                summary: event.summary.clone(),
+                begin,
+                end: begin + length,
+            });
+        }
+    }
+    events.sort_by_key(|e| e.begin);
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CALENDAR: &str = "BEGIN:VCALENDAR\r
+VERSION:2.0\r
+BEGIN:VEVENT\r
+UID:standup\r
+DTSTART;TZID=Europe/Berlin:20240506T093000\r
+DTEND;TZID=Europe/Berlin:20240506T094500\r
+RRULE:FREQ=DAILY;BYDAY=MO,TU,WE,TH,FR;COUNT=10\r
+EXDATE;TZID=Europe/Berlin:20240508T093000\r
+SUMMARY:Stand\r
+  up\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:standup\r
+RECURRENCE-ID;TZID=Europe/Berlin:20240509T093000\r
+DTSTART;TZID=Europe/Berlin:20240509T100000\r
+DURATION:PT30M\r
+SUMMARY:Stand up (moved)\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:review\r
+DTSTART:20240507T130000Z\r
+DTEND:20240507T140000Z\r
+SUMMARY:Review\\, planning\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:offsite\r
+DTSTART;VALUE=DATE:20240507\r
+DTEND;VALUE=DATE:20240508\r
+SUMMARY:Offsite\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:cancelled\r
+DTSTART:20240507T150000Z\r
+DTEND:20240507T160000Z\r
+STATUS:CANCELLED\r
+SUMMARY:Cancelled\r
+END:VEVENT\r
+END:VCALENDAR\r
+";
+
+    fn utc(text: &str) -> DateTime<Utc> {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M")
+            .unwrap()
+            .and_utc()
+    }
+
+    #[test]
+    fn calendar_events() {
+        let from = NaiveDate::from_ymd_opt(2024, 5, 6).unwrap();
+        let to = NaiveDate::from_ymd_opt(2024, 5, 10).unwrap();
+        let events: Vec<(String, DateTime<Utc>, DateTime<Utc>)> = events(CALENDAR, from, to)
+            .unwrap()
+            .into_iter()
+            .map(|e| (e.summary, e.begin.to_utc(), e.end.to_utc()))
+            .collect();
+
+        let event = |summary: &str, begin, end| (summary.to_string(), utc(begin), utc(end));
+        assert_eq!(
+            events,
+            vec![
+                event("Stand up", "2024-05-06 07:30", "2024-05-06 07:45"),
+                event("Stand up", "2024-05-07 07:30", "2024-05-07 07:45"),
+                event("Review, planning", "2024-05-07 13:00", "2024-05-07 14:00"),
+                event("Stand up (moved)", "2024-05-09 08:00", "2024-05-09 08:30"),
+                event("Stand up", "2024-05-10 07:30", "2024-05-10 07:45"),
+            ]
+        );
+    }
+
+    #[test]
+    fn weekly_until() {
+        let start = Stamp {
+            time: NaiveDateTime::parse_from_str("20240501T100000", "%Y%m%dT%H%M%S").unwrap(),
+            zone: Zone::Utc,
+            all_day: false,
+        };
+        let last = NaiveDate::from_ymd_opt(2024, 6, 30).unwrap();
+        let starts = occurrences(
+            start,
+            "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE;UNTIL=20240520T235959Z",
+            last,
+        );
+        let days: Vec<String> = starts
+            .iter()
+            .map(|t| t.format("%m-%d").to_string())
+            .collect();
+        assert_eq!(days, ["05-01", "05-13", "05-15"]);
+    }
+}
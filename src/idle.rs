@@ -0,0 +1,71 @@
+//! How long the keyboard and mouse have been left alone, asked from the tools
+//! each desktop comes with: `xprintidle` on X11, GNOME's idle monitor (which
+//! also works on Wayland) and `ioreg` on macOS.
+use chrono::Duration;
+use std::process::Command;
+
+fn output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// `xprintidle` prints milliseconds.
+fn parse_xprintidle(output: &str) -> Option<Duration> {
+    Some(Duration::milliseconds(output.trim().parse().ok()?))
+}
+
+/// GNOME answers with milliseconds in a GVariant, e.g. `(uint64 5000,)`.
+fn parse_gnome(output: &str) -> Option<Duration> {
+    let millis = output.trim().strip_prefix("(uint64 ")?.strip_suffix(",)")?;
+    Some(Duration::milliseconds(millis.parse().ok()?))
+}
+
+/// `ioreg` lists `"HIDIdleTime" = <nanoseconds>` among many other properties.
+fn parse_ioreg(output: &str) -> Option<Duration> {
+    let line = output.lines().find(|l| l.contains("\"HIDIdleTime\""))?;
+    let nanos: i64 = line.split('=').nth(1)?.trim().parse().ok()?;
+    Some(Duration::nanoseconds(nanos))
+}
+
+/// Time since the last input, if the desktop tells.
+pub fn time() -> Option<Duration> {
+    if cfg!(target_os = "macos") {
+        return parse_ioreg(&output("ioreg", &["-c", "IOHIDSystem", "-d", "4"])?);
+    }
+    let gnome = || {
+        output(
+            "gdbus",
+            &[
+                "call",
+                "--session",
+                "--dest",
+                "org.gnome.Mutter.IdleMonitor",
+                "--object-path",
+                "/org/gnome/Mutter/IdleMonitor/Core",
+                "--method",
+                "org.gnome.Mutter.IdleMonitor.GetIdletime",
+            ],
+        )
+        .and_then(|o| parse_gnome(&o))
+    };
+    output("xprintidle", &[])
+        .and_then(|o| parse_xprintidle(&o))
+        .or_else(gnome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn idle_outputs() {
+        assert_eq!(parse_xprintidle("61000\n"), Some(Duration::seconds(61)));
+        assert_eq!(parse_gnome("(uint64 5000,)\n"), Some(Duration::seconds(5)));
+        assert_eq!(parse_gnome("Error: no such name"), None);
+        let ioreg = "    | |   \"HIDIdleTime\" = 120000000000\n    | |   \"HIDKeyboard\" = 1";
+        assert_eq!(parse_ioreg(ioreg), Some(Duration::minutes(2)));
+    }
+}
@@ -0,0 +1,164 @@
+use anyhow::Result;
+use base64::Engine;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+
+/// Issue trackers to look up issue titles in, and Slack to show the running
+/// timer in, from `[integrations]`.
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct Integrations {
+    pub jira: Option<Jira>,
+    pub gitlab: Option<GitLab>,
+    pub slack: Option<Slack>,
+}
+
+impl Integrations {
+    pub fn is_empty(&self) -> bool {
+        self.jira.is_none() && self.gitlab.is_none() && self.slack.is_none()
+    }
+}
+
+/// Resolves keys like `PROJ-42`.
+#[derive(Deserialize, Serialize, Debug)]
+pub struct Jira {
+    /// E.g. `https://example.atlassian.net`
+    pub url: String,
+    /// Account email for Jira Cloud; leave out to send `token` as a personal
+    /// access token instead.
+    pub user: Option<String>,
+    pub token: String,
+}
+
+/// Sets the Slack status while a timer runs. Only used when built with the
+/// `slack` feature.
+#[derive(Deserialize, Serialize, Debug)]
+pub struct Slack {
+    /// User token with the `users.profile:write` scope, e.g. `xoxp-...`.
+    pub token: String,
+    /// Status text, with `{project}`, `{activity}`, `{customer}` and
+    /// `{description}` filled in from the running timer.
+    #[serde(default = "default_slack_status")]
+    pub status: String,
+    #[serde(default = "default_slack_emoji")]
+    pub emoji: String,
+}
+
+fn default_slack_status() -> String {
+    "Working on {project}".to_string()
+}
+
+fn default_slack_emoji() -> String {
+    ":red_circle:".to_string()
+}
+
+/// Resolves references like `#1234` in one project.
+#[derive(Deserialize, Serialize, Debug)]
+pub struct GitLab {
+    /// E.g. `https://gitlab.com`
+    pub url: String,
+    pub token: String,
+    /// Path of the project the issues belong to, e.g. `group/project`.
+    pub project: String,
+}
+
+fn agent(config: &config::Config) -> ureq::Agent {
+    ureq::AgentBuilder::new()
+        .timeout_connect(std::time::Duration::from_secs(
+            config.server.connect_timeout,
+        ))
+        .timeout_read(std::time::Duration::from_secs(config.server.read_timeout))
+        .build()
+}
+
+#[derive(Deserialize)]
+struct JiraIssue {
+    fields: JiraFields,
+}
+
+#[derive(Deserialize)]
+struct JiraFields {
+    summary: String,
+}
+
+fn jira_title(config: &config::Config, jira: &Jira, key: &str) -> Result<String> {
+    let url = format!(
+        "{}/rest/api/2/issue/{}?fields=summary",
+        jira.url.trim_end_matches('/'),
+        key
+    );
+    let authorization = match &jira.user {
+        Some(user) => format!(
+            "Basic {}",
+            base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", user, jira.token))
+        ),
+        None => format!("Bearer {}", jira.token),
+    };
+    let issue: JiraIssue = agent(config)
+        .get(&url)
+        .set("Authorization", &authorization)
+        .call()?
+        .into_json()?;
+    Ok(issue.fields.summary)
+}
+
+#[derive(Deserialize)]
+struct GitLabIssue {
+    title: String,
+}
+
+fn gitlab_title(config: &config::Config, gitlab: &GitLab, iid: &str) -> Result<String> {
+    let url = format!(
+        "{}/api/v4/projects/{}/issues/{}",
+        gitlab.url.trim_end_matches('/'),
+        gitlab.project.replace('/', "%2F"),
+        iid
+    );
+    let issue: GitLabIssue = agent(config)
+        .get(&url)
+        .set("PRIVATE-TOKEN", &gitlab.token)
+        .call()?
+        .into_json()?;
+    Ok(issue.title)
+}
+
+/// The issue referenced at the start of `description`, if a tracker for it
+/// is configured, e.g. `("PROJ-42", title)`.
+fn lookup(config: &config::Config, description: &str) -> Result<Option<(String, String)>> {
+    let integrations = &config.integrations;
+    if let Some(jira) = &integrations.jira {
+        let key = Regex::new(r"\b[A-Z][A-Z0-9]+-\d+\b").unwrap();
+        if let Some(key) = key.find(description) {
+            let title = jira_title(config, jira, key.as_str())?;
+            return Ok(Some((key.as_str().to_string(), title)));
+        }
+    }
+    if let Some(gitlab) = &integrations.gitlab {
+        let reference = Regex::new(r"(?:^|\s)#(\d+)\b").unwrap();
+        if let Some(captures) = reference.captures(description) {
+            let title = gitlab_title(config, gitlab, &captures[1])?;
+            return Ok(Some((format!("#{}", &captures[1]), title)));
+        }
+    }
+    Ok(None)
+}
+
+/// Append the title of the first issue referenced in `description`, unless
+/// it is already there. Failing to look it up only warrants a warning.
+pub fn expand(config: &config::Config, description: String) -> String {
+    if config.integrations.is_empty() {
+        return description;
+    }
+    match lookup(config, &description) {
+        Ok(Some((reference, title))) if !description.contains(&title) => {
+            println!("{}: {}", reference, title);
+            format!("{} {}", description.trim_end(), title)
+        }
+        Ok(_) => description,
+        Err(e) => {
+            eprintln!("Could not look up the issue title: {}", e);
+            description
+        }
+    }
+}
@@ -0,0 +1,27 @@
+//! Mistakes in what was asked for, found before anything is sent, e.g. an
+//! unknown project. They exit with the same code as the server rejecting a
+//! request.
+
+#[derive(Debug)]
+pub struct Invalid(pub String);
+
+impl std::fmt::Display for Invalid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Invalid {}
+
+/// `anyhow!` for an [`Invalid`] error.
+macro_rules! invalid {
+    ($($arg:tt)*) => {
+        ::anyhow::Error::new($crate::invalid::Invalid(format!($($arg)*)))
+    };
+}
+pub(crate) use invalid;
+
+/// Whether `error`, or anything it was caused by, is [`Invalid`].
+pub fn is_invalid(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| cause.is::<Invalid>())
+}
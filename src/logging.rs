@@ -0,0 +1,44 @@
+//! What `-v` prints: the requests sent to Kimai and, with `-vv`, what was
+//! sent and received. It goes to stderr, so output can still be piped.
+//!
+//! This is the `log` facade rather than `tracing`: ureq, rustls and keyring
+//! already log through it, so it costs no further dependencies, while
+//! `tracing` would need `tracing-subscriber` and `tracing-log` on top to
+//! print the same lines.
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+struct Stderr;
+
+impl Log for Stderr {
+    /// Only this tool's own messages; its dependencies are chatty.
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.target().starts_with("kimai")
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            let level = match record.level() {
+                Level::Error => "error",
+                Level::Warn => "warn",
+                Level::Info => "info",
+                Level::Debug => "debug",
+                Level::Trace => "trace",
+            };
+            eprintln!("[{}] {}", level, record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Start logging for the number of `-v` given.
+pub fn init(verbosity: u8) {
+    let level = match verbosity {
+        0 => return,
+        1 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    };
+    if log::set_logger(&Stderr).is_ok() {
+        log::set_max_level(level);
+    }
+}
@@ -1,185 +1,386 @@
-use anyhow::Result;
-use chrono::{DateTime, Duration, Local, NaiveTime, Utc, Weekday};
-use regex::Regex;
-use std::fmt::{Display, Formatter};
-
-use inquire::{required, CustomType, DateSelect, Select, Text};
-
-use serde::{de::DeserializeOwned, Deserialize, Serialize};
-
+use anyhow::{Context, Result};
+use clap::{CommandFactory, Parser, Subcommand};
+use inquire::InquireError;
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+mod api;
+mod budget;
+mod cache;
+mod caps;
+mod clock;
+mod commands;
+mod complete;
 mod config;
-
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct Activity {
-    id: i32,
-    parent_title: Option<String>,
-    name: String,
+mod descriptions;
+mod favorites;
+mod format;
+mod git;
+mod hooks;
+mod ics;
+#[cfg(feature = "idle")]
+mod idle;
+mod integrations;
+mod invalid;
+mod logging;
+mod parse;
+mod prompts;
+mod queue;
+mod report;
+mod resolve;
+mod rounding;
+#[cfg(feature = "slack")]
+mod slack;
+mod state;
+mod taskwarrior;
+mod tracked;
+
+#[derive(Parser, Debug)]
+#[command(name = "kimai", version, about = "Command line client for Kimai")]
+struct Cli {
+    /// Configuration profile to use, from `[profiles.<name>]` in the config file
+    #[arg(long, global = true, env = "KIMAI_PROFILE")]
+    profile: Option<String>,
+
+    /// Path to the configuration file, instead of searching the usual places
+    #[arg(long, global = true, env = "KIMAI_CONFIG")]
+    config: Option<PathBuf>,
+
+    /// Fetch projects, activities, customers and tags from the server even if cached
+    #[arg(long, global = true)]
+    refresh: bool,
+
+    /// Print the requests that would change data instead of sending them
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    /// Print without colors, also set by a non-empty `NO_COLOR`
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Log the requests sent to Kimai, -vv also what was sent and received
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Only print what was asked for and errors, not what was done
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-impl Display for Activity {
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
-        if let Some(parent_title) = &self.parent_title {
-            write!(f, "{} | {}", parent_title, self.name)
-        } else {
-            write!(f, "{}", self.name)
-        }
-    }
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Log a new timesheet entry (the default when no command is given)
+    Log(commands::log::LogArgs),
+    /// List timesheet entries
+    List(commands::list::ListArgs),
+    /// Delete timesheet entries
+    Delete(commands::delete::DeleteArgs),
+    /// Go through the entries of a day and fix their times, descriptions
+    /// or split them
+    Day(commands::day::DayArgs),
+    /// Edit an existing timesheet entry
+    Edit(commands::edit::EditArgs),
+    /// Split an entry in two, e.g. after forgetting to switch tasks
+    Split(commands::split::SplitArgs),
+    /// Copy an entry to another day, or every day of a range
+    Copy(commands::copy::CopyArgs),
+    /// Manage projects and activities listed first in pickers
+    Favorite {
+        #[command(subcommand)]
+        command: commands::favorite::FavoriteCommand,
+    },
+    /// Entries that repeat, like a daily standup, logged once they're due
+    Recur {
+        #[command(subcommand)]
+        command: commands::recur::RecurCommand,
+    },
+    /// Log entries back to back until the day's target hours are reached
+    Fill(commands::fill::FillArgs),
+    /// Find the time between entries that isn't logged, and fill it
+    Gaps(commands::gaps::GapsArgs),
+    /// Suggest entries from what ActivityWatch or WakaTime recorded, to
+    /// accept or adjust
+    Suggest(commands::suggest::SuggestArgs),
+    /// List workdays with fewer hours logged than expected
+    Missing(commands::missing::MissingArgs),
+    /// Go over a week or month with the days that look wrong flagged, and fix
+    /// them before it's locked
+    Review(commands::review::ReviewArgs),
+    /// Show the overtime or hours owed since the working-time account started
+    Balance(commands::balance::BalanceArgs),
+    /// Summarize logged time per project and day
+    Report(commands::report::ReportArgs),
+    /// Write entries to a CSV or XLSX file, one row per entry, or as timeclock
+    /// entries for plain-text accounting
+    Export(commands::export::ExportArgs),
+    /// Create entries from other sources
+    Import {
+        #[command(subcommand)]
+        command: commands::import::ImportCommand,
+    },
+    /// List invoices and preview what would be invoiced
+    Invoice {
+        #[command(subcommand)]
+        command: commands::invoice::InvoiceCommand,
+    },
+    /// Record and list expenses, with Kimai's expenses plugin
+    Expense {
+        #[command(subcommand)]
+        command: commands::expense::ExpenseCommand,
+    },
+    /// Submit weeks for approval and see how far they got, with Kimai's
+    /// approval plugin
+    Approve {
+        #[command(subcommand)]
+        command: commands::approve::ApproveCommand,
+    },
+    /// Create projects
+    Project {
+        #[command(subcommand)]
+        command: commands::create::ProjectCommand,
+    },
+    /// Create activities
+    Activity {
+        #[command(subcommand)]
+        command: commands::create::ActivityCommand,
+    },
+    /// List the projects the token can see
+    Projects(commands::catalog::ProjectsArgs),
+    /// List the activities the token can see
+    Activities(commands::catalog::ActivitiesArgs),
+    /// List the customers the token can see
+    Customers(commands::catalog::CatalogArgs),
+    /// List the tags in use
+    Tags(commands::catalog::CatalogArgs),
+    /// List the users the token may log for, e.g. as a team lead
+    Users(commands::catalog::CatalogArgs),
+    /// Delete the entry created last, e.g. after a typo in the duration
+    Undo(commands::undo::UndoArgs),
+    /// Start a running timer
+    Start(commands::timer::StartArgs),
+    /// Start a new timer copying a previous entry
+    Restart(commands::timer::RestartArgs),
+    /// A local stopwatch for time that gets a project only when it's stopped
+    Track {
+        #[command(subcommand)]
+        command: commands::track::TrackCommand,
+    },
+    /// Work in intervals with breaks in between, logging each interval
+    Pomodoro(commands::pomodoro::PomodoroArgs),
+    /// Keep running and send a desktop notification when no timer runs
+    /// during work hours, or one runs for too long
+    Watch(commands::watch::WatchArgs),
+    /// Print this week's tracked time and the running timers as Prometheus
+    /// metrics, or serve them for scraping
+    Metrics(commands::metrics::MetricsArgs),
+    /// Stop the running timer
+    Stop(commands::timer::StopArgs),
+    /// Show the running timer
+    Status(commands::timer::StatusArgs),
+    /// Today's entries as a timeline with the gaps between them, and the totals
+    Today,
+    /// Full screen dashboard of today's entries, the running timer and the week
+    Tui,
+    /// Submit entries queued while Kimai was unreachable
+    Sync(commands::sync::SyncArgs),
+    /// Manage the API token stored in the keyring
+    Auth {
+        #[command(subcommand)]
+        command: commands::auth::AuthCommand,
+    },
+    /// Print a shell completion script
+    Completions(commands::completions::CompletionsArgs),
+    /// Open Kimai's web UI in the browser, at an entry, a project or a week
+    Open {
+        #[command(subcommand)]
+        page: Option<commands::open::OpenCommand>,
+    },
+    /// Check the configuration and the connection to Kimai
+    Doctor,
+    /// Inspect the configuration
+    Config {
+        #[command(subcommand)]
+        command: commands::config::ConfigCommand,
+    },
 }
 
-#[derive(Debug, Deserialize)]
-struct Project {
-    id: i32,
-    name: String,
+/// Exit codes for scripts to tell failures apart, besides 1 for any other
+/// error. Invalid arguments exit with 2 as well, from clap.
+mod exit {
+    pub const INVALID: u8 = 2;
+    pub const UNAUTHORIZED: u8 = 3;
+    pub const UNREACHABLE: u8 = 4;
+    pub const ABORTED: u8 = 5;
 }
 
-impl Display for Project {
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
-        write!(f, "{}", self.name)
+fn exit_code(error: &anyhow::Error) -> u8 {
+    if api::is_unauthorized(error) {
+        exit::UNAUTHORIZED
+    } else if api::is_unreachable(error) {
+        exit::UNREACHABLE
+    } else if api::is_invalid(error) || invalid::is_invalid(error) {
+        exit::INVALID
+    } else if matches!(
+        error.downcast_ref(),
+        Some(InquireError::OperationCanceled | InquireError::OperationInterrupted)
+    ) {
+        exit::ABORTED
+    } else {
+        1
     }
 }
 
-#[derive(Serialize, Debug)]
-pub struct TimesheetEditForm {
-    pub begin: DateTime<Utc>,
-    pub project: i32,
-    pub activity: i32,
-    pub end: DateTime<Utc>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub description: Option<String>,
-}
+fn main() -> ExitCode {
+    // Answers the shell when called from a completion script
+    clap_complete::CompleteEnv::with_factory(Cli::command).complete();
 
-fn api_request<T: DeserializeOwned, B: Serialize>(
-    config: &config::Config,
-    url: &str,
-    body: Option<&B>,
-) -> Result<T> {
-    let url = config.endpoint.to_owned() + url;
-    let mut request = match body {
-        Some(_) => ureq::post(&url),
-        None => ureq::get(&url),
-    };
+    preload_formats();
+    let cli = Cli::parse();
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    format::set_color(!cli.no_color && !no_color && std::io::stdout().is_terminal());
+    format::set_quiet(cli.quiet);
+    logging::init(cli.verbose);
 
-    request = request
-        .set("Accept", "application/json")
-        .set("Authorization", &format!("Bearer {}", config.token));
-
-    let response = match body {
-        Some(data) => request.send_json(data),
-        None => request.call(),
-    };
-
-    let response = match response {
-        Ok(response) => Ok(response),
-        Err(ureq::Error::Status(code, response)) => {
-            /* the server returned an unexpected status
-            code (such as 400, 500 etc) */
-            Err(anyhow::anyhow!(
-                "Server returned status code {}: {}",
-                code,
-                response.into_string().unwrap()
-            ))
-        }
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
         Err(e) => {
-            anyhow::bail!("Request failed: {:?}", e)
+            eprintln!("Error: {:?}", e);
+            ExitCode::from(exit_code(&e))
         }
-    }?;
-
-    let data: T = serde_json::from_str(&response.into_string().unwrap())?;
-    Ok(data)
-}
-
-fn get_projects(config: &config::Config) -> Result<Vec<Project>> {
-    api_request(config, "/api/projects?visible=1", None::<&()>)
-}
-
-fn get_activities_by_project(config: &config::Config, project_id: i32) -> Result<Vec<Activity>> {
-    let url = format!("/api/activities?visible=1&projects[]={}", project_id);
-    api_request(config, &url, None::<&()>)
+    }
 }
 
-fn insert_timesheet_entry(config: &config::Config, form: TimesheetEditForm) -> Result<()> {
-    api_request(config, "/api/timesheets", Some(&form))
+/// `command` taking any value for its arguments but `--config` and
+/// `--profile`, so they are found wherever they are given.
+fn lenient(command: clap::Command) -> clap::Command {
+    command
+        .mut_args(|arg| {
+            let id = arg.get_id().as_str();
+            if arg.get_action().takes_values() && id != "config" && id != "profile" {
+                arg.value_parser(clap::builder::ValueParser::os_string())
+            } else {
+                arg
+            }
+        })
+        .mut_subcommands(lenient)
 }
 
-fn parse_duration(input: &str) -> Option<Duration> {
-    let decimal_re = Regex::new(r"^\d+(\.\d+)?$").unwrap();
-    let time_re = Regex::new(r"^(\d+):(\d+)$").unwrap();
-
-    if decimal_re.is_match(input) {
-        // Parse as decimal hours
-        let hours: f64 = input.parse().ok()?;
-        Some(Duration::seconds((hours * 3600.0) as i64))
-    } else if let Some(captures) = time_re.captures(input) {
-        // Parse as HH:MM format
-        let hours: i64 = captures.get(1)?.as_str().parse().ok()?;
-        let minutes: i64 = captures.get(2)?.as_str().parse().ok()?;
-        Some(Duration::hours(hours) + Duration::minutes(minutes))
-    } else {
-        None
+/// Take the configured `date_format` ahead of parsing the arguments, so dates
+/// given in it are understood. Anything wrong with the config is reported once
+/// it's loaded for real.
+fn preload_formats() {
+    let command = lenient(Cli::command()).ignore_errors(true);
+    let Ok(matches) = command.try_get_matches() else {
+        return;
+    };
+    let profile = matches.get_one::<String>("profile").map(String::as_str);
+    let path = matches.get_one::<PathBuf>("config").map(PathBuf::as_path);
+    if let Ok(config) = config::load_config(profile, path) {
+        let _ = format::init(&config);
     }
 }
 
-fn main() -> Result<()> {
-    // TODO Inform user errors from this are coming from loading the config
-    let config = config::load_config()?;
-
-    let projects = get_projects(&config)?;
-
-    let proj = Select::new("Project:", projects).prompt()?;
-
-    let activity =
-        Select::new("Activity:", get_activities_by_project(&config, proj.id)?).prompt()?;
+fn run(cli: Cli) -> Result<()> {
+    if let Some(Command::Completions(args)) = &cli.command {
+        return commands::completions::run(args);
+    }
 
-    let duration = Text::new("Duration:")
-        .with_validator(required!("This field is required"))
-        .with_help_message("E.g. 1.5 or 2:30")
-        .with_default("1")
-        .prompt()?;
+    // There is no config to load yet when creating one
+    if let Some(Command::Config {
+        command: commands::config::ConfigCommand::Init,
+    }) = &cli.command
+    {
+        return commands::config::init(cli.profile.as_deref(), cli.config.as_deref());
+    }
 
-    let duration = parse_duration(&duration).unwrap();
+    if let Some(Command::Doctor) = &cli.command {
+        return commands::doctor::run(cli.profile.as_deref(), cli.config.as_deref());
+    }
 
-    let date = DateSelect::new("Date:")
-        .with_week_start(Weekday::Mon)
-        .prompt()?;
+    let mut config = config::load_config(cli.profile.as_deref(), cli.config.as_deref())
+        .context("Could not load the configuration, `kimai doctor` may help")?;
+    config.refresh = cli.refresh;
+    config.dry_run = cli.dry_run;
+    format::init(&config)?;
 
-    // If the selected date is the current date, default to current time minus duration.
-    // Otherwise, use the configured value.
-    let default_time = if date == Local::now().date_naive() {
-        Local::now().time() - duration
-    } else {
-        config.default_start_time
+    let command = cli.command.unwrap_or(Command::Log(Default::default()));
+    if !matches!(command, Command::Auth { .. } | Command::Config { .. }) {
+        clock::init(&config)?;
+    }
+    let flush_queue = !config.dry_run
+        && !matches!(
+            command,
+            Command::Sync(_)
+                | Command::Auth { .. }
+                | Command::Config { .. }
+                // Runs on every shell prompt and must stay fast
+                | Command::Status(commands::timer::StatusArgs { short: true })
+        );
+
+    let result = match command {
+        Command::Log(args) => commands::log::run(&config, args),
+        Command::List(args) => commands::list::run(&config, args),
+        Command::Delete(args) => commands::delete::run(&config, args),
+        Command::Day(args) => commands::day::run(&config, args),
+        Command::Edit(args) => commands::edit::run(&config, args),
+        Command::Split(args) => commands::split::run(&config, args),
+        Command::Copy(args) => commands::copy::run(&config, args),
+        Command::Favorite { command } => commands::favorite::run(&config, command),
+        Command::Recur { command } => commands::recur::run(&config, command),
+        Command::Fill(args) => commands::fill::run(&config, args),
+        Command::Gaps(args) => commands::gaps::run(&config, args),
+        Command::Suggest(args) => commands::suggest::run(&config, args),
+        Command::Missing(args) => commands::missing::run(&config, args),
+        Command::Review(args) => commands::review::run(&config, args),
+        Command::Balance(args) => commands::balance::run(&config, args),
+        Command::Report(args) => commands::report::run(&config, args),
+        Command::Export(args) => commands::export::run(&config, args),
+        Command::Import { command } => commands::import::run(&config, command),
+        Command::Invoice { command } => commands::invoice::run(&config, command),
+        Command::Expense { command } => commands::expense::run(&config, command),
+        Command::Approve { command } => commands::approve::run(&config, command),
+        Command::Project { command } => commands::create::project(&config, command),
+        Command::Activity { command } => commands::create::activity(&config, command),
+        Command::Projects(args) => commands::catalog::projects(&config, args),
+        Command::Activities(args) => commands::catalog::activities(&config, args),
+        Command::Customers(args) => commands::catalog::customers(&config, args),
+        Command::Tags(args) => commands::catalog::tags(&config, args),
+        Command::Users(args) => commands::catalog::users(&config, args),
+        Command::Undo(args) => commands::undo::run(&config, args),
+        Command::Start(args) => commands::timer::start(&config, args),
+        Command::Restart(args) => commands::timer::restart(&config, args),
+        Command::Track { command } => commands::track::run(&config, command),
+        Command::Pomodoro(args) => commands::pomodoro::run(&config, args),
+        Command::Watch(args) => commands::watch::run(&config, args),
+        Command::Metrics(args) => commands::metrics::run(&config, args),
+        Command::Stop(args) => commands::timer::stop(&config, args),
+        Command::Status(args) => commands::timer::status(&config, args),
+        Command::Today => commands::today::run(&config),
+        Command::Tui => commands::tui::run(&config),
+        Command::Sync(args) => commands::sync::run(&config, args),
+        Command::Open { page } => commands::open::run(&config, page),
+        Command::Auth { command } => commands::auth::run(&config, &command),
+        Command::Config { command } => commands::config::run(&config, &command),
+        Command::Completions(_) => unreachable!("completions run before the config is loaded"),
+        Command::Doctor => unreachable!("doctor runs before the config is loaded"),
     };
+    match result {
+        Err(e) if api::is_dry_run(&e) => return Ok(()),
+        result => result?,
+    }
 
-    let time_prompt = CustomType::<NaiveTime>::new("Enter start time (HH:MM):")
-        .with_default_value_formatter(&|t| t.format("%H:%M").to_string())
-        .with_error_message("Please enter a valid time in HH:MM format")
-        .with_default(default_time)
-        .with_help_message("Enter the time in 24-hour format (e.g., 14:30 for 2:30 PM)")
-        .prompt()?;
-
-    let description = Text::new("Description:")
-        .with_help_message("optional")
-        .prompt()?;
-
-    let begin = date
-        .and_time(time_prompt)
-        .and_local_timezone(Local)
-        .earliest()
-        .unwrap();
-
-    let end = begin + duration;
-
-    insert_timesheet_entry(
-        &config,
-        TimesheetEditForm {
-            begin: begin.into(),
-            project: proj.id,
-            activity: activity.id,
-            end: end.into(),
-            description: Some(description),
-        },
-    )?;
-
+    // The server answered, so this is a good moment to submit queued entries
+    if flush_queue {
+        // The command itself succeeded, an unreadable queue must not change that
+        match queue::load(&config) {
+            Ok(queued) if queued.is_empty() => {}
+            Ok(_) => commands::sync::report(&queue::flush(&config)?),
+            Err(e) => eprintln!("Warning: can't read the queue: {:#}", e),
+        }
+    }
     Ok(())
 }
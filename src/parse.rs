@@ -0,0 +1,383 @@
+use chrono::{Datelike, Days, Duration, Local, NaiveDate, NaiveTime, Weekday};
+use regex::Regex;
+
+use crate::format;
+
+/// Longest duration accepted: far beyond any entry, but short enough for the
+/// times it's added to or taken from to stay in range.
+const LONGEST: Duration = Duration::days(100 * 365);
+
+/// Parse a duration given as decimal hours (`1.5`, `.5`, `2,5`), `HH:MM`
+/// (`1:30`) or with units (`90m`, `45min`, `1h`, `1h30m`).
+pub fn duration(input: &str) -> Option<Duration> {
+    parse_duration(input).filter(|d| *d <= LONGEST)
+}
+
+fn parse_duration(input: &str) -> Option<Duration> {
+    let decimal_re = Regex::new(r"^(\d+(\.\d*)?|\.\d+)$").unwrap();
+    let time_re = Regex::new(r"^(\d+):(\d+)$").unwrap();
+    let units_re =
+        Regex::new(r"^(?:(\d+(?:\.\d+)?)\s*h(?:ours?|rs?)?)?\s*(?:(\d+)\s*m(?:in(?:utes?|s)?)?)?$")
+            .unwrap();
+
+    // Accept a decimal comma as used in many European locales
+    let input = input.trim().to_lowercase().replace(',', ".");
+
+    if decimal_re.is_match(&input) {
+        // Parse as decimal hours
+        let hours: f64 = input.parse().ok()?;
+        Duration::try_seconds((hours * 3600.0).round() as i64)
+    } else if let Some(captures) = time_re.captures(&input) {
+        // Parse as HH:MM format
+        let hours: i64 = captures.get(1)?.as_str().parse().ok()?;
+        let minutes: i64 = captures.get(2)?.as_str().parse().ok()?;
+        Duration::try_hours(hours)?.checked_add(&Duration::try_minutes(minutes)?)
+    } else if let Some(captures) = units_re.captures(&input) {
+        // Parse as e.g. 1h30m
+        let hours = captures.get(1);
+        let minutes = captures.get(2);
+        if hours.is_none() && minutes.is_none() {
+            return None;
+        }
+        let hours: f64 = match hours {
+            Some(hours) => hours.as_str().parse().ok()?,
+            None => 0.0,
+        };
+        let minutes: i64 = match minutes {
+            Some(minutes) => minutes.as_str().parse().ok()?,
+            None => 0,
+        };
+        Duration::try_seconds((hours * 3600.0).round() as i64)?
+            .checked_add(&Duration::try_minutes(minutes)?)
+    } else {
+        None
+    }
+}
+
+/// [`duration`] as a clap value parser.
+pub fn duration_arg(input: &str) -> Result<Duration, String> {
+    duration(input).ok_or_else(|| format!("invalid duration '{}'", input))
+}
+
+/// A custom field value given as `name=value` on the command line.
+pub fn meta_arg(input: &str) -> Result<(String, String), String> {
+    match input.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.to_string()))
+        }
+        _ => Err(format!("expected name=value, got '{}'", input)),
+    }
+}
+
+/// How long an entry lasts: a plain duration, or the times it began and ended.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Length {
+    Duration(Duration),
+    Range(NaiveTime, NaiveTime),
+}
+
+/// Parse a [`duration`] or a time range like `9:15-11:40`.
+pub fn length(input: &str) -> Option<Length> {
+    let range_re = Regex::new(r"^(\d{1,2}:\d{2})\s*[-–]\s*(\d{1,2}:\d{2})$").unwrap();
+    match range_re.captures(input.trim()) {
+        Some(captures) => Some(Length::Range(
+            captures.get(1)?.as_str().parse().ok()?,
+            captures.get(2)?.as_str().parse().ok()?,
+        )),
+        None => duration(input).map(Length::Duration),
+    }
+}
+
+/// Parse a time of day in 24-hour (`14:30`, `9:05`) or 12-hour format
+/// (`2:30pm`, `2:30 PM`, `2pm`).
+pub fn time(input: &str) -> Option<NaiveTime> {
+    let time_re = Regex::new(r"^(\d{1,2})(?::(\d{2}))?\s*([ap]\.?m\.?)?$").unwrap();
+    let input = input.trim().to_lowercase();
+    let captures = time_re.captures(&input)?;
+    let mut hours: u32 = captures.get(1)?.as_str().parse().ok()?;
+    let minutes: u32 = match captures.get(2) {
+        Some(minutes) => minutes.as_str().parse().ok()?,
+        None => 0,
+    };
+    match captures.get(3) {
+        Some(suffix) => {
+            if !(1..=12).contains(&hours) {
+                return None;
+            }
+            hours %= 12;
+            if suffix.as_str().starts_with('p') {
+                hours += 12;
+            }
+        }
+        // A bare hour is too easily a duration typed into the wrong place
+        None if captures.get(2).is_none() => return None,
+        None => {}
+    }
+    NaiveTime::from_hms_opt(hours, minutes, 0)
+}
+
+/// [`time`] as a clap value parser.
+pub fn time_arg(input: &str) -> Result<NaiveTime, String> {
+    time(input).ok_or_else(|| format!("invalid time '{}', expected e.g. 14:30 or 2:30pm", input))
+}
+
+/// Time between `begin` and `end`, where an `end` before `begin` is on the
+/// next day. Fails if both are the same.
+pub fn elapsed(begin: NaiveTime, end: NaiveTime) -> Result<Duration, String> {
+    match end.signed_duration_since(begin) {
+        d if d > Duration::zero() => Ok(d),
+        d if d < Duration::zero() => Ok(d + Duration::days(1)),
+        _ => Err("The end time must be after the start time".to_string()),
+    }
+}
+
+fn weekday(input: &str) -> Option<Weekday> {
+    match input {
+        "mon" | "monday" => Some(Weekday::Mon),
+        "tue" | "tues" | "tuesday" => Some(Weekday::Tue),
+        "wed" | "wednesday" => Some(Weekday::Wed),
+        "thu" | "thur" | "thurs" | "thursday" => Some(Weekday::Thu),
+        "fri" | "friday" => Some(Weekday::Fri),
+        "sat" | "saturday" => Some(Weekday::Sat),
+        "sun" | "sunday" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+/// Parse the days something repeats on: `day` for every day, `weekday` for
+/// Monday to Friday, or weekdays like `mon,thu`.
+pub fn weekdays(input: &str) -> Option<Vec<Weekday>> {
+    let input = input.trim().to_lowercase();
+    let all = [
+        Weekday::Mon,
+        Weekday::Tue,
+        Weekday::Wed,
+        Weekday::Thu,
+        Weekday::Fri,
+        Weekday::Sat,
+        Weekday::Sun,
+    ];
+    match input.as_str() {
+        "day" | "daily" => Some(all.to_vec()),
+        "weekday" | "weekdays" => Some(all[..5].to_vec()),
+        _ => input.split(',').map(|day| weekday(day.trim())).collect(),
+    }
+}
+
+/// The most recent `weekday` on or before `today`.
+fn previous(today: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let back = (7 + today.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7;
+    today - Days::new(back.into())
+}
+
+/// Parse a date relative to `today`: `today`, `yesterday`, `tomorrow`, a
+/// weekday (`mon`, the most recent one, possibly today), `last fri` (the one
+/// before today), a day offset (`-2`, `+1`), an ISO date (`2024-05-01`) or
+/// a date in the configured `date_format`.
+pub fn date(input: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let input = input.trim().to_lowercase();
+    match input.as_str() {
+        "today" => return Some(today),
+        "yesterday" => return today.pred_opt(),
+        "tomorrow" => return today.succ_opt(),
+        _ => {}
+    }
+
+    if let Some(day) = weekday(&input) {
+        return Some(previous(today, day));
+    }
+    if let Some(day) = input
+        .strip_prefix("last ")
+        .and_then(|rest| weekday(rest.trim()))
+    {
+        return Some(previous(today.pred_opt()?, day));
+    }
+    if let Some(offset) = input.strip_prefix(['-', '+']) {
+        let days = Days::new(offset.parse().ok()?);
+        return if input.starts_with('-') {
+            today.checked_sub_days(days)
+        } else {
+            today.checked_add_days(days)
+        };
+    }
+    NaiveDate::parse_from_str(&input, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(&input, format::date_format()))
+        .ok()
+}
+
+/// [`date`] relative to the current day as a clap value parser. Arguments
+/// are parsed before the time zone is set up, so this is the system's day.
+pub fn date_arg(input: &str) -> Result<NaiveDate, String> {
+    date(input, Local::now().date_naive()).ok_or_else(|| format!("invalid date '{}'", input))
+}
+
+/// Parse a range of days like `mon..fri` or `2024-05-06..2024-05-10`. The
+/// start is a [`date`]; a weekday as the end is the first one on or after the
+/// start, so `mon..fri` is this workweek.
+pub fn date_range(input: &str, today: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+    let (from, to) = input.split_once("..")?;
+    let from = date(from, today)?;
+    let to = match weekday(&to.trim().to_lowercase()) {
+        Some(day) => {
+            let ahead =
+                (7 + day.num_days_from_monday() - from.weekday().num_days_from_monday()) % 7;
+            from + Days::new(ahead.into())
+        }
+        None => date(to, today)?,
+    };
+    (from <= to).then_some((from, to))
+}
+
+/// [`date_range`] as a clap value parser, like [`date_arg`].
+pub fn date_range_arg(input: &str) -> Result<(NaiveDate, NaiveDate), String> {
+    date_range(input, Local::now().date_naive())
+        .ok_or_else(|| format!("invalid date range '{}', expected e.g. mon..fri", input))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn minutes(input: &str) -> Option<i64> {
+        duration(input).map(|d| d.num_minutes())
+    }
+
+    #[test]
+    fn duration_decimal_hours() {
+        assert_eq!(minutes("1"), Some(60));
+        assert_eq!(minutes("1.5"), Some(90));
+        assert_eq!(minutes(".5"), Some(30));
+        assert_eq!(minutes("2,5"), Some(150));
+        assert_eq!(minutes("2."), Some(120));
+    }
+
+    #[test]
+    fn duration_hours_and_minutes() {
+        assert_eq!(minutes("2:30"), Some(150));
+        assert_eq!(minutes("0:45"), Some(45));
+    }
+
+    #[test]
+    fn duration_units() {
+        assert_eq!(minutes("90m"), Some(90));
+        assert_eq!(minutes("45min"), Some(45));
+        assert_eq!(minutes("45 mins"), Some(45));
+        assert_eq!(minutes("1h"), Some(60));
+        assert_eq!(minutes("1h30m"), Some(90));
+        assert_eq!(minutes("1h 30min"), Some(90));
+        assert_eq!(minutes("1.5h"), Some(90));
+        assert_eq!(minutes("2H"), Some(120));
+    }
+
+    #[test]
+    fn duration_invalid() {
+        assert_eq!(minutes(""), None);
+        assert_eq!(minutes("h"), None);
+        assert_eq!(minutes("abc"), None);
+        assert_eq!(minutes("1:"), None);
+        assert_eq!(minutes("1,5,5"), None);
+        assert_eq!(minutes("30m1h"), None);
+    }
+
+    #[test]
+    fn duration_too_long() {
+        assert_eq!(minutes("99999999999999"), None);
+        assert_eq!(minutes("99999999999999:00"), None);
+        assert_eq!(minutes("1:99999999999999999"), None);
+        assert_eq!(minutes("1h 999999999999999m"), None);
+        assert_eq!(minutes("99999999999999999999h"), None);
+        assert_eq!(minutes("9999999999h"), None);
+        assert_eq!(minutes("876000h"), Some(876000 * 60));
+    }
+
+    #[test]
+    fn meta_values() {
+        let pair = |name: &str, value: &str| Ok((name.to_string(), value.to_string()));
+        assert_eq!(meta_arg("ticket=T-1"), pair("ticket", "T-1"));
+        assert_eq!(meta_arg("note=a=b"), pair("note", "a=b"));
+        assert_eq!(meta_arg("empty="), pair("empty", ""));
+        assert!(meta_arg("ticket").is_err());
+        assert!(meta_arg("=1").is_err());
+    }
+
+    #[test]
+    fn times_of_day() {
+        let at = |h, m| NaiveTime::from_hms_opt(h, m, 0);
+        assert_eq!(time("14:30"), at(14, 30));
+        assert_eq!(time("9:05"), at(9, 5));
+        assert_eq!(time("2:30pm"), at(14, 30));
+        assert_eq!(time("2:30 PM"), at(14, 30));
+        assert_eq!(time("2pm"), at(14, 0));
+        assert_eq!(time("12am"), at(0, 0));
+        assert_eq!(time("12:15 p.m."), at(12, 15));
+        assert_eq!(time("13pm"), None);
+        assert_eq!(time("25:00"), None);
+        assert_eq!(time("14"), None);
+    }
+
+    #[test]
+    fn time_ranges() {
+        let time = |h, m| NaiveTime::from_hms_opt(h, m, 0).unwrap();
+        assert_eq!(
+            length("9:15-11:40"),
+            Some(Length::Range(time(9, 15), time(11, 40)))
+        );
+        assert_eq!(
+            length("22:00 – 01:30"),
+            Some(Length::Range(time(22, 0), time(1, 30)))
+        );
+        assert_eq!(length("1h"), Some(Length::Duration(Duration::hours(1))));
+        assert_eq!(length("9:15-"), None);
+
+        assert_eq!(
+            elapsed(time(9, 15), time(11, 40)),
+            Ok(Duration::minutes(145))
+        );
+        assert_eq!(
+            elapsed(time(22, 0), time(1, 30)),
+            Ok(Duration::minutes(210))
+        );
+        assert!(elapsed(time(9, 0), time(9, 0)).is_err());
+    }
+
+    #[test]
+    fn relative_dates() {
+        // A Wednesday
+        let today = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        let day = |d| NaiveDate::from_ymd_opt(2024, 4, d).unwrap();
+
+        assert_eq!(date("today", today), Some(today));
+        assert_eq!(date("yesterday", today), Some(day(30)));
+        assert_eq!(date("-2", today), Some(day(29)));
+        assert_eq!(date("mon", today), Some(day(29)));
+        assert_eq!(date("wed", today), Some(today));
+        assert_eq!(date("last wed", today), Some(day(24)));
+        assert_eq!(date("Last Friday", today), Some(day(26)));
+        assert_eq!(date("2024-04-01", today), Some(day(1)));
+        assert_eq!(date("someday", today), None);
+    }
+
+    #[test]
+    fn repeating_weekdays() {
+        assert_eq!(weekdays("weekday").map(|days| days.len()), Some(5));
+        assert_eq!(weekdays("Daily").map(|days| days.len()), Some(7));
+        assert_eq!(weekdays("mon, thu"), Some(vec![Weekday::Mon, Weekday::Thu]));
+        assert_eq!(weekdays("mon,someday"), None);
+    }
+
+    #[test]
+    fn date_ranges() {
+        // A Wednesday
+        let today = NaiveDate::from_ymd_opt(2024, 4, 3).unwrap();
+        let day = |d| NaiveDate::from_ymd_opt(2024, 4, d).unwrap();
+        assert_eq!(date_range("mon..fri", today), Some((day(1), day(5))));
+        let last_fri = NaiveDate::from_ymd_opt(2024, 3, 29).unwrap();
+        assert_eq!(date_range("fri..mon", today), Some((last_fri, day(1))));
+        assert_eq!(
+            date_range("2024-04-08..2024-04-10", today),
+            Some((day(8), day(10)))
+        );
+        assert_eq!(date_range("today..-1", today), None);
+        assert_eq!(date_range("mon", today), None);
+    }
+}
@@ -0,0 +1,735 @@
+use anyhow::{Context, Result};
+use chrono::{Duration, NaiveDate, NaiveTime};
+use inquire::autocompletion::{Autocomplete, Replacement};
+use inquire::validator::Validation;
+use inquire::{Confirm, CustomType, CustomUserError, DateSelect, MultiSelect, Select, Text};
+use serde::{Deserialize, Serialize};
+use std::io::IsTerminal;
+use std::process::Command;
+
+use crate::api::{self, Activity, Customer, MetaValue, Project, User};
+use crate::invalid::invalid;
+use crate::{clock, config, descriptions, favorites, format, parse, resolve, state};
+
+/// Match options containing every word of the input anywhere, ignoring case,
+/// keeping the original order of the list.
+fn substring_scorer<T>(input: &str, _option: &T, value: &str, index: usize) -> Option<i64> {
+    let value = value.to_lowercase();
+    input
+        .to_lowercase()
+        .split_whitespace()
+        .all(|word| value.contains(word))
+        .then_some(-(index as i64))
+}
+
+/// [`Select`] with the filtering used throughout the CLI.
+fn select<'a, T: std::fmt::Display>(message: &'a str, options: Vec<T>) -> Select<'a, T> {
+    Select::new(message, options).with_scorer(&substring_scorer)
+}
+
+/// Resolve a user given with `--user`.
+pub fn user(config: &config::Config, needle: &str) -> Result<User> {
+    resolve::user(api::get_users(config)?, needle)
+}
+
+/// What time gets logged on.
+pub struct Task {
+    pub project: Project,
+    pub activity: Activity,
+    /// Description and tags of the recent entry this was picked from.
+    pub description: String,
+    pub tags: Vec<String>,
+}
+
+impl Task {
+    /// `activity` on `project`, with the project's default description.
+    fn new(config: &config::Config, project: Project, activity: Activity) -> Task {
+        let description = config
+            .project_defaults(&project)
+            .and_then(|defaults| defaults.description.clone())
+            .unwrap_or_default();
+        Task {
+            project,
+            activity,
+            description,
+            tags: Vec::new(),
+        }
+    }
+}
+
+const OTHER_TASK: &str = "Other…";
+
+/// What was picked last, for the pickers to start on next time.
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct Picked {
+    customer: Option<i32>,
+    project: Option<i32>,
+    activity: Option<i32>,
+}
+
+const PICKED: &str = "last-picked";
+
+fn picked(config: &config::Config) -> Picked {
+    state::load(config, PICKED).unwrap_or_default()
+}
+
+/// Remember a pick. Nothing depends on it, so failing is only worth a
+/// warning.
+fn remember(config: &config::Config, update: impl FnOnce(&mut Picked)) {
+    let mut picked = picked(config);
+    update(&mut picked);
+    if let Err(e) = state::save(config, PICKED, &picked) {
+        eprintln!("Could not remember the picked project: {}", e);
+    }
+}
+
+/// Whether to bill the entry, from `--billable` or `--not-billable` or
+/// asked for, defaulting to what the project and activity say. Without a
+/// terminal Kimai decides.
+pub fn billable(task: &Task, billable: bool, not_billable: bool) -> Result<Option<bool>> {
+    if billable || not_billable {
+        return Ok(Some(billable));
+    }
+    if !std::io::stdin().is_terminal() {
+        return Ok(None);
+    }
+    let default = task.project.billable.unwrap_or(true) && task.activity.billable.unwrap_or(true);
+    Ok(Some(
+        Confirm::new("Billable?").with_default(default).prompt()?,
+    ))
+}
+
+/// Resolve project and activity from the command line, or let the user pick
+/// one of their recent combinations before falling back to the full pickers.
+pub fn task(
+    config: &config::Config,
+    project_needle: Option<&str>,
+    activity_needle: Option<&str>,
+) -> Result<Task> {
+    if project_needle.is_none() && activity_needle.is_none() {
+        let recent = std::thread::scope(|scope| {
+            scope.spawn(|| api::prefetch(config));
+            api::get_recent_timesheets(config, 10)
+        })?;
+        if !recent.is_empty() {
+            let mut labels: Vec<String> = recent
+                .iter()
+                .map(
+                    |t| match t.description.as_deref().filter(|d| !d.is_empty()) {
+                        Some(description) => {
+                            format!("{} / {} – {}", t.project, t.activity.name, description)
+                        }
+                        None => format!("{} / {}", t.project, t.activity.name),
+                    },
+                )
+                .collect();
+            labels.push(OTHER_TASK.to_string());
+
+            let picked = select("Recent:", labels).raw_prompt()?;
+            if let Some(entry) = recent.into_iter().nth(picked.index) {
+                remember(config, |picked| {
+                    picked.project = Some(entry.project.id);
+                    picked.activity = Some(entry.activity.id);
+                });
+                return Ok(Task {
+                    project: entry.project,
+                    activity: entry.activity,
+                    description: entry.description.unwrap_or_default(),
+                    tags: entry.tags,
+                });
+            }
+        }
+    }
+
+    let project = project(config, project_needle)?;
+    let activity = activity(config, &project, activity_needle)?;
+    Ok(Task::new(config, project, activity))
+}
+
+/// Ask for the project and activity again, with the cursors starting on
+/// `current`'s. The description and tags stay if the same task is picked.
+pub fn task_with_default(config: &config::Config, current: Task) -> Result<Task> {
+    let project = project_with_default(config, current.project.id)?;
+    let activity = activity_with_default(config, &project, current.activity.id)?;
+    if project.id == current.project.id && activity.id == current.activity.id {
+        return Ok(Task {
+            project,
+            activity,
+            ..current
+        });
+    }
+    Ok(Task::new(config, project, activity))
+}
+
+/// A project and several of its activities to split time between, found by
+/// the needles or asked for. The task is on the first of them.
+pub fn split_task(
+    config: &config::Config,
+    project_needle: Option<&str>,
+    activity_needles: &[String],
+) -> Result<(Task, Vec<Activity>)> {
+    let project = project(config, project_needle)?;
+    let activities = activities(config, &project, activity_needles)?;
+    Ok((
+        Task::new(config, project, activities[0].clone()),
+        activities,
+    ))
+}
+
+/// Resolve the customer given on the command line, or ask for one.
+pub fn customer(config: &config::Config, needle: Option<&str>) -> Result<Customer> {
+    let customers = api::get_customers(config)?;
+    match needle {
+        Some(needle) => resolve::customer(customers, needle),
+        None => Ok(select("Customer:", customers).prompt()?),
+    }
+}
+
+/// Resolve the project given on the command line, or ask for one.
+pub fn project(config: &config::Config, needle: Option<&str>) -> Result<Project> {
+    match needle {
+        Some(needle) => resolve::project(api::get_projects(config)?, needle),
+        None => choose_project(config, None),
+    }
+}
+
+/// Ask for a project, with the cursor starting on `current`.
+pub fn project_with_default(config: &config::Config, current: i32) -> Result<Project> {
+    choose_project(config, Some(current))
+}
+
+/// Ask for a project, with the cursor starting on `current`, or else on the
+/// one picked last.
+fn choose_project(config: &config::Config, current: Option<i32>) -> Result<Project> {
+    let last = picked(config);
+    let (customer, projects) = if config.select_customer {
+        let customers = api::get_customers(config)?;
+        let cursor = last
+            .customer
+            .and_then(|last| customers.iter().position(|c| c.id == last))
+            .unwrap_or(0);
+        let customer = select("Customer:", customers)
+            .with_starting_cursor(cursor)
+            .prompt()?;
+        (
+            Some(customer.id),
+            api::get_projects_by_customer(config, customer.id)?,
+        )
+    } else {
+        (last.customer, api::get_projects(config)?)
+    };
+    let projects = favorites::sort_first(projects, &favorites::load(config)?.projects, |p| p.id);
+
+    let cursor = current
+        .or(last.project)
+        .and_then(|current| projects.iter().position(|p| p.id == current))
+        .unwrap_or(0);
+    let project = select("Project:", projects)
+        .with_starting_cursor(cursor)
+        .prompt()?;
+    remember(config, |picked| {
+        picked.customer = customer;
+        picked.project = Some(project.id);
+    });
+    Ok(project)
+}
+
+/// Resolve the activity given on the command line, or ask for one.
+pub fn activity(
+    config: &config::Config,
+    project: &Project,
+    needle: Option<&str>,
+) -> Result<Activity> {
+    let activities = api::get_activities_by_project(config, project.id)?;
+    match needle {
+        Some(needle) => resolve::activity(activities, needle),
+        None => {
+            let activities =
+                favorites::sort_first(activities, &favorites::load(config)?.activities, |a| a.id);
+            let preselected = config
+                .project_defaults(project)
+                .and_then(|defaults| defaults.activity.as_deref());
+            let cursor = preselected
+                .and_then(|default| {
+                    activities.iter().position(|a| {
+                        a.id.to_string() == default || a.name.eq_ignore_ascii_case(default)
+                    })
+                })
+                .or_else(|| {
+                    let last = picked(config).activity?;
+                    activities.iter().position(|a| a.id == last)
+                })
+                .unwrap_or(0);
+            let activity = select("Activity:", activities)
+                .with_starting_cursor(cursor)
+                .prompt()?;
+            remember(config, |picked| picked.activity = Some(activity.id));
+            Ok(activity)
+        }
+    }
+}
+
+/// Find the activities of `project` given by `needles`, or let the user pick
+/// at least two.
+pub fn activities(
+    config: &config::Config,
+    project: &Project,
+    needles: &[String],
+) -> Result<Vec<Activity>> {
+    let activities = api::get_activities_by_project(config, project.id)?;
+    if !needles.is_empty() {
+        return needles
+            .iter()
+            .map(|needle| resolve::activity(activities.clone(), needle))
+            .collect();
+    }
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!("Pass the activities to split the time between, e.g. --activities a,b");
+    }
+    let activities =
+        favorites::sort_first(activities, &favorites::load(config)?.activities, |a| a.id);
+    Ok(MultiSelect::new("Activities:", activities)
+        .with_scorer(&substring_scorer)
+        .with_validator(|picked: &[inquire::list_option::ListOption<&Activity>]| {
+            Ok(if picked.len() < 2 {
+                Validation::Invalid("Pick at least two activities".into())
+            } else {
+                Validation::Valid
+            })
+        })
+        .prompt()?)
+}
+
+/// Ask for an activity, with the cursor starting on `current`.
+pub fn activity_with_default(
+    config: &config::Config,
+    project: &Project,
+    current: i32,
+) -> Result<Activity> {
+    let activities = api::get_activities_by_project(config, project.id)?;
+    let activities =
+        favorites::sort_first(activities, &favorites::load(config)?.activities, |a| a.id);
+    let cursor = activities.iter().position(|a| a.id == current).unwrap_or(0);
+    let activity = select("Activity:", activities)
+        .with_starting_cursor(cursor)
+        .prompt()?;
+    remember(config, |picked| picked.activity = Some(activity.id));
+    Ok(activity)
+}
+
+pub fn duration(default: Duration) -> Result<Duration> {
+    duration_with_message("Duration:", default)
+}
+
+/// [`duration`] asked with `message`, e.g. for one part of a longer time.
+pub fn duration_with_message(message: &str, default: Duration) -> Result<Duration> {
+    let duration = Text::new(message)
+        .with_validator(|input: &str| {
+            Ok(match parse::duration(input) {
+                Some(_) => Validation::Valid,
+                None => Validation::Invalid("Please enter a valid duration".into()),
+            })
+        })
+        .with_help_message("E.g. 1.5, 2:30, 90m or 1h30m")
+        .with_default(&format::duration(default))
+        .prompt()?;
+    Ok(parse::duration(&duration).unwrap())
+}
+
+/// Ask for a duration, or a time range to take the start time from as well.
+pub fn length(default: Duration) -> Result<parse::Length> {
+    let length = Text::new("Duration:")
+        .with_validator(|input: &str| {
+            Ok(match parse::length(input) {
+                Some(parse::Length::Range(begin, end)) if begin == end => {
+                    Validation::Invalid("The end time must be after the start time".into())
+                }
+                Some(_) => Validation::Valid,
+                None => Validation::Invalid("Please enter a valid duration or time range".into()),
+            })
+        })
+        .with_help_message("E.g. 1.5, 2:30, 90m, 1h30m or a time range like 9:15-11:40")
+        .with_default(&format::duration(default))
+        .prompt()?;
+    Ok(parse::length(&length).unwrap())
+}
+
+const CALENDAR: &str = "cal";
+
+/// Ask for a date as text, e.g. `yesterday`, or pick it from a calendar.
+pub fn date(default: NaiveDate) -> Result<NaiveDate> {
+    let today = clock::today();
+    let default_text = if default == today {
+        "today".to_string()
+    } else {
+        format::date(default)
+    };
+
+    let input = Text::new("Date:")
+        .with_default(&default_text)
+        .with_help_message(
+            "E.g. today, yesterday, mon, last fri, -2, 2024-05-01 or cal for a calendar",
+        )
+        .with_validator(move |input: &str| {
+            Ok(
+                if input.trim() == CALENDAR || parse::date(input, today).is_some() {
+                    Validation::Valid
+                } else {
+                    Validation::Invalid("Please enter a valid date".into())
+                },
+            )
+        })
+        .prompt()?;
+
+    if input.trim() == CALENDAR {
+        return Ok(DateSelect::new("Date:")
+            .with_week_start(clock::week_start())
+            .with_default(default)
+            .prompt()?);
+    }
+    Ok(parse::date(&input, today).unwrap())
+}
+
+pub fn start_time(default: NaiveTime) -> Result<NaiveTime> {
+    time("Enter start time:", default)
+}
+
+/// Ask for a time of day, shown in the configured `time_format` but taken in
+/// either format.
+pub fn time(message: &str, default: NaiveTime) -> Result<NaiveTime> {
+    Ok(CustomType::<NaiveTime>::new(message)
+        .with_parser(&|input| parse::time(input).ok_or(()))
+        .with_formatter(&|t| format::time(t))
+        .with_default_value_formatter(&|t| format::time(t))
+        .with_error_message("Please enter a valid time, e.g. 14:30 or 2:30pm")
+        .with_default(default)
+        .with_help_message("E.g. 14:30, 9:05 or 2:30pm")
+        .prompt()?)
+}
+
+/// Use the description given on the command line, or ask for one pre-filled
+/// with the one of `task`.
+pub fn description(config: &config::Config, given: Option<String>, task: &Task) -> Result<String> {
+    match given {
+        Some(description) => Ok(description),
+        None => description_for(config, task.project.id, task.activity.id, &task.description),
+    }
+}
+
+const OTHER_DESCRIPTION: &str = "Other…";
+
+/// Let the user pick one of `candidates` as a starting point for the
+/// description, e.g. from git, to finish as in [`description_for`].
+pub fn description_from(
+    config: &config::Config,
+    project: i32,
+    activity: i32,
+    candidates: &[String],
+    initial: &str,
+) -> Result<String> {
+    if candidates.is_empty() {
+        return description_for(config, project, activity, initial);
+    }
+    let mut options: Vec<&str> = Vec::new();
+    if !initial.is_empty() {
+        options.push(initial);
+    }
+    options.extend(candidates.iter().map(String::as_str));
+    options.push(OTHER_DESCRIPTION);
+
+    let picked = select("Description from:", options).prompt()?;
+    if picked == OTHER_DESCRIPTION {
+        description_for(config, project, activity, "")
+    } else {
+        description_for(config, project, activity, picked)
+    }
+}
+
+/// Ask for a description, pre-filled with the current one.
+pub fn description_with_default(current: &str) -> Result<String> {
+    Ok(Text::new("Description:")
+        .with_help_message("optional")
+        .with_initial_value(current)
+        .prompt()?)
+}
+
+/// Suggests earlier descriptions containing every word typed so far, like
+/// the pickers filter.
+#[derive(Clone)]
+struct Descriptions(Vec<String>);
+
+impl Autocomplete for Descriptions {
+    fn get_suggestions(&mut self, input: &str) -> Result<Vec<String>, CustomUserError> {
+        let input_lower = input.to_lowercase();
+        let words: Vec<&str> = input_lower.split_whitespace().collect();
+        Ok(self
+            .0
+            .iter()
+            .filter(|d| *d != input)
+            .filter(|d| {
+                let d = d.to_lowercase();
+                words.iter().all(|word| d.contains(word))
+            })
+            .take(8)
+            .cloned()
+            .collect())
+    }
+
+    fn get_completion(
+        &mut self,
+        input: &str,
+        highlighted: Option<String>,
+    ) -> Result<Replacement, CustomUserError> {
+        match highlighted {
+            Some(highlighted) => Ok(Some(highlighted)),
+            None => Ok(self.get_suggestions(input)?.into_iter().next()),
+        }
+    }
+}
+
+/// [`description_with_default`], offering the descriptions of recent entries
+/// on `project` and `activity` while typing, e.g. none offline, and only
+/// accepting those matching the project's pattern.
+pub fn description_for(
+    config: &config::Config,
+    project: i32,
+    activity: i32,
+    current: &str,
+) -> Result<String> {
+    let pattern = descriptions::pattern(config, project)?;
+    let earlier = api::get_descriptions(config, project, activity).unwrap_or_default();
+    let mut help = vec![match pattern {
+        Some(_) => "must match the project's pattern",
+        None => "optional",
+    }];
+    if !earlier.is_empty() {
+        help.push("Tab completes an earlier one");
+    }
+    let help = help.join(", ");
+
+    let mut text = Text::new("Description:")
+        .with_help_message(&help)
+        .with_initial_value(current);
+    if let Some(pattern) = pattern {
+        text = text.with_validator(move |input: &str| {
+            Ok(if pattern.is_match(input) {
+                Validation::Valid
+            } else {
+                Validation::Invalid(format!("Must match {}", pattern).into())
+            })
+        });
+    }
+    if !earlier.is_empty() {
+        text = text.with_autocomplete(Descriptions(earlier));
+    }
+    Ok(text.prompt()?)
+}
+
+/// A new file only the user can read in the temp dir, holding `initial`.
+/// Its name cannot be guessed ahead, and an existing file or a symlink put in
+/// its place is never written through.
+fn editor_file(initial: &str) -> Result<std::path::PathBuf> {
+    use std::io::Write;
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos())
+        .unwrap_or_default();
+    let mut attempt: u32 = 0;
+    loop {
+        let path = std::env::temp_dir().join(format!(
+            "kimai-description-{}-{:x}.txt",
+            std::process::id(),
+            nanos
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(attempt.into())
+        ));
+        match options.open(&path) {
+            Ok(mut file) => {
+                file.write_all(initial.as_bytes())
+                    .with_context(|| format!("Could not write {}", path.display()))?;
+                return Ok(path);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists && attempt < 100 => {
+                attempt += 1
+            }
+            Err(e) => {
+                return Err(e).with_context(|| format!("Could not create {}", path.display()))
+            }
+        }
+    }
+}
+
+/// Let the user write the description in `$VISUAL` or `$EDITOR`, starting
+/// from `initial`, and again while it doesn't match the project's pattern.
+pub fn description_in_editor(
+    config: &config::Config,
+    project: i32,
+    initial: &str,
+) -> Result<String> {
+    let pattern = descriptions::pattern(config, project)?;
+    let mut description = edited(initial)?;
+    while let Some(pattern) = pattern.as_ref().filter(|p| !p.is_match(&description)) {
+        eprintln!("Descriptions on this project must match '{}'", pattern);
+        if !Confirm::new("Edit it again?").with_default(true).prompt()? {
+            return Err(invalid!(
+                "Descriptions on this project must match '{}', '{}' doesn't",
+                pattern,
+                description
+            ));
+        }
+        description = edited(&description)?;
+    }
+    Ok(description)
+}
+
+/// `initial` as changed in `$VISUAL` or `$EDITOR`.
+fn edited(initial: &str) -> Result<String> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.to_string());
+    let path = editor_file(initial)?;
+
+    // The editor may come with arguments, e.g. `code --wait`
+    let mut words = editor.split_whitespace();
+    let status = Command::new(words.next().unwrap_or_default())
+        .args(words)
+        .arg(&path)
+        .status();
+    let description = std::fs::read_to_string(&path);
+    let _ = std::fs::remove_file(&path);
+    match status {
+        Ok(status) if status.success() => {}
+        Ok(status) => anyhow::bail!("{} exited with {}", editor, status),
+        Err(e) => return Err(e).with_context(|| format!("Could not start the editor {}", editor)),
+    }
+    Ok(description
+        .with_context(|| format!("Could not read {}", path.display()))?
+        .trim_end()
+        .to_string())
+}
+
+/// Ask for tags: any of the existing ones plus new ones typed in by hand.
+/// Tags are optional, so scripts without a terminal are not prompted.
+pub fn tags(
+    config: &config::Config,
+    given: Vec<String>,
+    current: &[String],
+) -> Result<Vec<String>> {
+    if !given.is_empty() || !std::io::stdin().is_terminal() {
+        return Ok(given);
+    }
+
+    let existing: Vec<String> = api::get_tags(config)?
+        .into_iter()
+        .map(|tag| tag.name)
+        .collect();
+
+    // Current tags the server doesn't list can't be deselected, so keep them
+    let mut tags: Vec<String> = current
+        .iter()
+        .filter(|tag| !existing.contains(tag))
+        .cloned()
+        .collect();
+
+    if !existing.is_empty() {
+        let selected: Vec<usize> = existing
+            .iter()
+            .enumerate()
+            .filter(|(_, tag)| current.contains(tag))
+            .map(|(index, _)| index)
+            .collect();
+        tags.extend(
+            MultiSelect::new("Tags:", existing)
+                .with_default(&selected)
+                .prompt()?,
+        );
+    }
+
+    let new_tags = Text::new("New tags:")
+        .with_help_message("optional, comma separated")
+        .prompt()?;
+    for tag in new_tags.split(',').map(str::trim) {
+        if !tag.is_empty() && !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    Ok(tags)
+}
+
+/// Values for the server's custom fields: the ones given as `--meta`, and
+/// any other required field asked for. Optional fields are left to their
+/// defaults.
+pub fn meta_fields(
+    config: &config::Config,
+    given: Vec<(String, String)>,
+) -> Result<Vec<MetaValue>> {
+    let fields = api::get_meta_fields(config)?;
+    let mut values = Vec::new();
+    for (name, value) in given {
+        let Some(field) = fields.iter().find(|f| f.name.eq_ignore_ascii_case(&name)) else {
+            let known: Vec<&str> = fields.iter().map(|f| f.name.as_str()).collect();
+            if known.is_empty() {
+                anyhow::bail!("Unknown field '{}', Kimai has no custom fields", name);
+            }
+            anyhow::bail!(
+                "Unknown field '{}', expected one of {}",
+                name,
+                known.join(", ")
+            );
+        };
+        values.push(MetaValue {
+            name: field.name.clone(),
+            value: Some(value),
+        });
+    }
+
+    for field in fields.iter().filter(|f| f.required) {
+        if values.iter().any(|v| v.name == field.name) {
+            continue;
+        }
+        if !std::io::stdin().is_terminal() {
+            anyhow::bail!(
+                "The field '{}' is required, pass it with --meta {}=<value>",
+                field.name,
+                field.name
+            );
+        }
+        let label = format!("{}:", field.label.as_deref().unwrap_or(&field.name));
+        let default = match &field.value {
+            Some(serde_json::Value::String(value)) => value.clone(),
+            Some(serde_json::Value::Null) | None => String::new(),
+            Some(value) => value.to_string(),
+        };
+        let value = if field.kind.as_deref() == Some("boolean") {
+            let yes = Confirm::new(&label)
+                .with_default(matches!(default.as_str(), "1" | "true"))
+                .prompt()?;
+            u8::from(yes).to_string()
+        } else {
+            let mut text = Text::new(&label);
+            if !default.is_empty() {
+                text = text.with_default(&default);
+            }
+            text.with_validator(|input: &str| {
+                Ok(if input.trim().is_empty() {
+                    Validation::Invalid("This field is required".into())
+                } else {
+                    Validation::Valid
+                })
+            })
+            .prompt()?
+        };
+        values.push(MetaValue {
+            name: field.name.clone(),
+            value: Some(value),
+        });
+    }
+    Ok(values)
+}
@@ -0,0 +1,74 @@
+use anyhow::Result;
+
+use crate::api::{self, TimesheetEditForm};
+use crate::commands::undo;
+use crate::{config, state};
+
+/// Entries that could not be submitted while offline, oldest first.
+const QUEUE: &str = "queue";
+
+pub fn load(config: &config::Config) -> Result<Vec<TimesheetEditForm>> {
+    state::load(config, QUEUE)
+}
+
+fn save(config: &config::Config, forms: &[TimesheetEditForm]) -> Result<()> {
+    if forms.is_empty() {
+        let path = state::dir(config)?.join(format!("{}.json", QUEUE));
+        if path.is_file() {
+            std::fs::remove_file(path)?;
+        }
+        return Ok(());
+    }
+    state::save(config, QUEUE, &forms)
+}
+
+pub fn push(config: &config::Config, form: TimesheetEditForm) -> Result<()> {
+    let mut forms = load(config)?;
+    forms.push(form);
+    save(config, &forms)
+}
+
+pub fn clear(config: &config::Config) -> Result<()> {
+    save(config, &[])
+}
+
+/// What happened when flushing the queue.
+#[derive(Debug, Default)]
+pub struct Flushed {
+    pub submitted: usize,
+    /// Entries the server rejected; they stay queued.
+    pub rejected: Vec<anyhow::Error>,
+    /// Whether flushing stopped early because the server is unreachable.
+    pub offline: bool,
+}
+
+/// Submit queued entries in order. Entries that fail stay in the queue.
+pub fn flush(config: &config::Config) -> Result<Flushed> {
+    let forms = load(config)?;
+    let mut flushed = Flushed::default();
+    let mut remaining = Vec::new();
+
+    for form in forms {
+        if flushed.offline {
+            remaining.push(form);
+            continue;
+        }
+        match api::insert_timesheet_entry(config, &form) {
+            Ok(timesheet) => {
+                undo::remember(config, timesheet.id);
+                flushed.submitted += 1;
+            }
+            Err(e) if api::is_dry_run(&e) => remaining.push(form),
+            Err(e) => {
+                flushed.offline = api::is_unreachable(&e);
+                if !flushed.offline {
+                    flushed.rejected.push(e);
+                }
+                remaining.push(form);
+            }
+        }
+    }
+
+    save(config, &remaining)?;
+    Ok(flushed)
+}
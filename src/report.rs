@@ -0,0 +1,219 @@
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Days, Duration, FixedOffset, NaiveDate, NaiveTime, Weekday};
+use std::collections::BTreeMap;
+
+use crate::api::{self, TimesheetExpanded, TimesheetQuery};
+use crate::clock;
+use crate::config;
+
+/// First and last day of the week containing `date`, starting on the
+/// configured `week_start`.
+pub fn week(date: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let week_start = clock::week_start().num_days_from_monday();
+    let back = (7 + date.weekday().num_days_from_monday() - week_start) % 7;
+    let start = date - Days::new(back.into());
+    (start, start + Days::new(6))
+}
+
+/// First and last day of the month containing `date`.
+pub fn month(date: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let start = date.with_day(1).unwrap();
+    let next = start.checked_add_months(chrono::Months::new(1)).unwrap();
+    (start, next.pred_opt().unwrap())
+}
+
+/// Every day from `from` to `to`, inclusive.
+pub fn days(from: NaiveDate, to: NaiveDate) -> Vec<NaiveDate> {
+    from.iter_days().take_while(|day| *day <= to).collect()
+}
+
+/// All entries that begin between `from` and `to`, inclusive.
+pub fn fetch(
+    config: &config::Config,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<TimesheetExpanded>> {
+    fetch_for(config, from, to, None)
+}
+
+/// [`fetch`] for another user than the token's own.
+pub fn fetch_for(
+    config: &config::Config,
+    from: NaiveDate,
+    to: NaiveDate,
+    user: Option<i32>,
+) -> Result<Vec<TimesheetExpanded>> {
+    api::get_timesheets(
+        config,
+        &TimesheetQuery {
+            begin: Some(from.and_time(NaiveTime::MIN)),
+            end: Some(to.and_hms_opt(23, 59, 59).unwrap()),
+            user,
+            ..Default::default()
+        },
+    )
+}
+
+/// How long an entry has lasted so far; running timers count up to now.
+pub fn entry_duration(entry: &TimesheetExpanded) -> Duration {
+    match entry.end {
+        Some(_) => Duration::seconds(entry.duration),
+        None => clock::now().fixed_offset() - entry.begin,
+    }
+}
+
+/// Time not covered by any of `entries` between the first begin and the last
+/// end, in stretches of at least `min`. Running timers last until now.
+pub fn gaps(
+    entries: &[TimesheetExpanded],
+    min: Duration,
+) -> Vec<(DateTime<FixedOffset>, DateTime<FixedOffset>)> {
+    let mut sorted: Vec<&TimesheetExpanded> = entries.iter().collect();
+    sorted.sort_by_key(|e| e.begin);
+    let mut gaps = Vec::new();
+    // The end of the entries so far, which may overlap
+    let mut covered: Option<DateTime<FixedOffset>> = None;
+    for entry in sorted {
+        if let Some(covered) = covered {
+            if entry.begin - covered >= min {
+                gaps.push((covered, entry.begin));
+            }
+        }
+        let end = entry.end.unwrap_or_else(|| clock::now().fixed_offset());
+        covered = Some(covered.map_or(end, |covered| covered.max(end)));
+    }
+    gaps
+}
+
+/// Billable time on each of `days`.
+pub fn billable_totals(entries: &[TimesheetExpanded], days: &[NaiveDate]) -> Vec<Duration> {
+    let mut totals = vec![Duration::zero(); days.len()];
+    for entry in entries.iter().filter(|e| e.billable) {
+        if let Some(index) = days.iter().position(|d| *d == entry.begin.date_naive()) {
+            totals[index] += entry_duration(entry);
+        }
+    }
+    totals
+}
+
+/// A project as a row of a report: told apart by id, as projects of
+/// different customers may share a name, and labelled with the customer.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ProjectRow {
+    /// E.g. `Acme | Webshop`; rows are sorted by it.
+    pub label: String,
+    pub id: i32,
+}
+
+impl ProjectRow {
+    pub fn of(entry: &TimesheetExpanded) -> ProjectRow {
+        ProjectRow {
+            label: entry.project.to_string(),
+            id: entry.project.id,
+        }
+    }
+}
+
+/// Money earned per project. Entries without a visible rate count as zero.
+pub fn amounts(entries: &[TimesheetExpanded]) -> BTreeMap<ProjectRow, f64> {
+    let mut amounts = BTreeMap::new();
+    for entry in entries {
+        *amounts.entry(ProjectRow::of(entry)).or_default() += entry.rate.unwrap_or_default();
+    }
+    amounts
+}
+
+/// Days without expected hours besides weekends, with the reason, e.g.
+/// `holiday` or `sick`.
+pub type DaysOff = BTreeMap<NaiveDate, String>;
+
+/// What Kimai's absence types are shown as.
+fn absence_label(kind: &str) -> String {
+    match kind {
+        "holiday" => "vacation".to_string(),
+        "sickness" | "sickness_child" => "sick".to_string(),
+        "time_off" => "time off".to_string(),
+        other => other.replace('_', " "),
+    }
+}
+
+/// Holidays from the config and, if enabled, absences and public holidays
+/// from Kimai between `from` and `to`.
+pub fn days_off(config: &config::Config, from: NaiveDate, to: NaiveDate) -> Result<DaysOff> {
+    let mut days_off: DaysOff = config
+        .holidays
+        .iter()
+        .filter(|day| (from..=to).contains(*day))
+        .map(|day| (*day, "holiday".to_string()))
+        .collect();
+
+    if config.absences {
+        match api::get_public_holidays(config, from, to) {
+            Ok(holidays) => {
+                for holiday in holidays {
+                    days_off.insert(holiday.date, "holiday".to_string());
+                }
+            }
+            Err(e) if api::is_not_found(&e) => {}
+            Err(e) => return Err(e),
+        }
+        match api::get_absences(config, from, to) {
+            Ok(absences) => {
+                for absence in absences {
+                    days_off.insert(absence.date, absence_label(&absence.kind));
+                }
+            }
+            Err(e) if api::is_not_found(&e) => {
+                anyhow::bail!("This Kimai has no absences, turn off `absences` in the config")
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(days_off)
+}
+
+/// The hours someone is expected to work on `day`.
+pub fn expected(config: &config::Config, day: NaiveDate, days_off: &DaysOff) -> Duration {
+    match day.weekday() {
+        Weekday::Sat | Weekday::Sun => Duration::zero(),
+        _ if days_off.contains_key(&day) => Duration::zero(),
+        _ => Duration::seconds((config.expected_hours_per_day * 3600.0) as i64),
+    }
+}
+
+/// Logged time per project and day.
+#[derive(Debug)]
+pub struct Grid {
+    pub days: Vec<NaiveDate>,
+    /// Time logged on each of `days`, per project.
+    pub rows: BTreeMap<ProjectRow, Vec<Duration>>,
+}
+
+impl Grid {
+    pub fn new(entries: &[TimesheetExpanded], from: NaiveDate, to: NaiveDate) -> Grid {
+        let days = days(from, to);
+        let mut rows: BTreeMap<ProjectRow, Vec<Duration>> = BTreeMap::new();
+        for entry in entries {
+            let day = entry.begin.date_naive();
+            let Some(index) = days.iter().position(|d| *d == day) else {
+                continue;
+            };
+            let row = rows
+                .entry(ProjectRow::of(entry))
+                .or_insert_with(|| vec![Duration::zero(); days.len()]);
+            row[index] += entry_duration(entry);
+        }
+        Grid { days, rows }
+    }
+
+    /// Total logged time per day.
+    pub fn day_totals(&self) -> Vec<Duration> {
+        let mut totals = vec![Duration::zero(); self.days.len()];
+        for row in self.rows.values() {
+            for (total, duration) in totals.iter_mut().zip(row) {
+                *total += *duration;
+            }
+        }
+        totals
+    }
+}
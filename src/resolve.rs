@@ -0,0 +1,198 @@
+//! Projects, activities, customers and users named on the command line, by
+//! id or by name, exactly or by a part of it.
+use anyhow::Result;
+use std::fmt::Display;
+
+use crate::api::{Activity, Customer, Project, User};
+use crate::invalid::invalid;
+
+/// Edits needed to turn `a` into `b`, for suggesting names like a mistyped
+/// one.
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+/// `items` as a list for an error message, with ids to tell them apart.
+fn listed<T: Display>(items: &[&T], id: impl Fn(&T) -> i32) -> String {
+    items
+        .iter()
+        .map(|item| format!("{} (#{})", item, id(item)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The one of `items` that `needle` names: by id, by one of its `names`
+/// ignoring case, or else the only one with a name containing every word of
+/// `needle`. Fails listing the candidates when several match, or the names
+/// most like `needle` when none does.
+fn find<T: Display>(
+    items: Vec<T>,
+    needle: &str,
+    kind: &str,
+    id: impl Fn(&T) -> i32,
+    names: impl Fn(&T) -> Vec<String>,
+) -> Result<T> {
+    let lower = needle.trim().to_lowercase();
+    let exact: Vec<usize> = (0..items.len())
+        .filter(|&i| {
+            id(&items[i]).to_string() == needle.trim()
+                || names(&items[i]).iter().any(|n| n.to_lowercase() == lower)
+        })
+        .collect();
+    let matches = if exact.is_empty() {
+        (0..items.len())
+            .filter(|&i| {
+                names(&items[i]).iter().any(|name| {
+                    let name = name.to_lowercase();
+                    lower.split_whitespace().all(|word| name.contains(word))
+                })
+            })
+            .collect()
+    } else {
+        exact
+    };
+
+    match matches[..] {
+        [index] => Ok(items.into_iter().nth(index).unwrap()),
+        [] => {
+            let mut close: Vec<(usize, &T)> = items
+                .iter()
+                .filter_map(|item| {
+                    // A mistyped word of a longer name counts as well
+                    let best = names(item)
+                        .iter()
+                        .flat_map(|name| {
+                            let name = name.to_lowercase();
+                            let words: Vec<String> =
+                                name.split_whitespace().map(str::to_string).collect();
+                            std::iter::once(name).chain(words)
+                        })
+                        .map(|name| distance(&lower, &name))
+                        .min()?;
+                    // A third of the letters may be off, but not everything
+                    // short is like everything else
+                    (best <= (lower.chars().count() / 3).max(1)).then_some((best, item))
+                })
+                .collect();
+            close.sort_by_key(|(best, _)| *best);
+            let close: Vec<&T> = close.into_iter().take(3).map(|(_, item)| item).collect();
+            if close.is_empty() {
+                return Err(invalid!("No {} found matching '{}'", kind, needle));
+            }
+            Err(invalid!(
+                "No {} found matching '{}', did you mean {}?",
+                kind,
+                needle,
+                listed(&close, id)
+            ))
+        }
+        _ => {
+            let several: Vec<&T> = matches.iter().map(|&i| &items[i]).collect();
+            Err(invalid!(
+                "'{}' matches several {}s, give more of the name or the id: {}",
+                needle,
+                kind,
+                listed(&several, id)
+            ))
+        }
+    }
+}
+
+/// Find a project by id, by its name, or by its name below the customer's as
+/// shown in lists.
+pub fn project(projects: Vec<Project>, needle: &str) -> Result<Project> {
+    find(
+        projects,
+        needle,
+        "project",
+        |p| p.id,
+        |p| vec![p.name.clone(), p.to_string()],
+    )
+}
+
+pub fn customer(customers: Vec<Customer>, needle: &str) -> Result<Customer> {
+    find(
+        customers,
+        needle,
+        "customer",
+        |c| c.id,
+        |c| vec![c.name.clone()],
+    )
+}
+
+pub fn activity(activities: Vec<Activity>, needle: &str) -> Result<Activity> {
+    find(
+        activities,
+        needle,
+        "activity",
+        |a| a.id,
+        |a| vec![a.name.clone(), a.to_string()],
+    )
+}
+
+/// Find a user by id, username or alias.
+pub fn user(users: Vec<User>, needle: &str) -> Result<User> {
+    find(
+        users,
+        needle,
+        "user",
+        |u| u.id,
+        |u| {
+            u.alias
+                .iter()
+                .cloned()
+                .chain([u.username.clone()])
+                .collect()
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn projects() -> Vec<Project> {
+        [
+            (1, "Acme", "Webshop"),
+            (2, "Acme", "Intranet"),
+            (3, "Globex", "Webshop"),
+        ]
+        .into_iter()
+        .map(|(id, customer, name)| Project {
+            id,
+            parent_title: Some(customer.to_string()),
+            name: name.to_string(),
+            billable: None,
+        })
+        .collect()
+    }
+
+    #[test]
+    fn exact_then_by_words() {
+        assert_eq!(project(projects(), "2").unwrap().id, 2);
+        assert_eq!(project(projects(), "intranet").unwrap().id, 2);
+        assert_eq!(project(projects(), "Globex | Webshop").unwrap().id, 3);
+        assert_eq!(project(projects(), "acme web").unwrap().id, 1);
+        assert_eq!(project(projects(), "intra").unwrap().id, 2);
+
+        let several = project(projects(), "Webshop").unwrap_err().to_string();
+        assert!(several.contains("Acme | Webshop (#1), Globex | Webshop (#3)"));
+        let typo = project(projects(), "Intarnet").unwrap_err().to_string();
+        assert!(typo.ends_with("did you mean Acme | Intranet (#2)?"));
+    }
+}
@@ -0,0 +1,182 @@
+use chrono::{DateTime, Duration, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::api::{Timesheet, TimesheetEditForm};
+use crate::clock::{self, Zone};
+use crate::{config, format};
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Down,
+    Up,
+    #[default]
+    Closest,
+}
+
+/// Round to a multiple of some minutes, e.g. `{ minutes = 15, direction = "up" }`.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy)]
+pub struct Rule {
+    pub minutes: u32,
+    #[serde(default)]
+    pub direction: Direction,
+}
+
+impl std::fmt::Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let direction = match self.direction {
+            Direction::Down => "down",
+            Direction::Up => "up",
+            Direction::Closest => "closest",
+        };
+        write!(f, "{} minutes, {}", self.minutes, direction)
+    }
+}
+
+impl Rule {
+    fn seconds(&self, seconds: i64) -> i64 {
+        let step = i64::from(self.minutes) * 60;
+        if step == 0 {
+            return seconds;
+        }
+        let down = seconds - seconds.rem_euclid(step);
+        let up = if down == seconds { down } else { down + step };
+        match self.direction {
+            Direction::Down => down,
+            Direction::Up => up,
+            Direction::Closest if (seconds - down) * 2 >= step => up,
+            Direction::Closest => down,
+        }
+    }
+
+    /// Round a point in time on the local clock, so hourly steps line up
+    /// with full hours in zones with a half hour offset.
+    pub fn time(&self, time: DateTime<clock::Zone>) -> DateTime<clock::Zone> {
+        let time = time.with_nanosecond(0).unwrap_or(time);
+        let seconds = i64::from(time.num_seconds_from_midnight());
+        time + Duration::seconds(self.seconds(seconds) - seconds)
+    }
+
+    pub fn duration(&self, duration: Duration) -> Duration {
+        Duration::seconds(self.seconds(duration.num_seconds()))
+    }
+}
+
+/// Round the begin and end of `form` by the configured rules, the way the
+/// server would. `round_duration` applies last and moves the end. Returns
+/// whether anything changed.
+pub fn apply(config: &config::Config, form: &mut TimesheetEditForm) -> bool {
+    let (begin, end) = (form.begin, form.end);
+    if let Some(rule) = config.round_begin {
+        form.begin = rule.time(form.begin.with_timezone(&clock::zone())).to_utc();
+    }
+    if let Some(end) = form.end.as_mut() {
+        if let Some(rule) = config.round_end {
+            *end = rule.time(end.with_timezone(&clock::zone())).to_utc();
+        }
+        if let Some(rule) = config.round_duration {
+            *end = form.begin + rule.duration(*end - form.begin);
+        }
+    }
+    (form.begin, form.end) != (begin, end)
+}
+
+/// The end of a timer stopped at `end`.
+pub fn stop(
+    config: &config::Config,
+    begin: DateTime<Utc>,
+    mut end: DateTime<Zone>,
+) -> DateTime<Utc> {
+    if let Some(rule) = config.round_end {
+        end = rule.time(end);
+    }
+    match config.round_duration {
+        Some(rule) => begin + rule.duration(end.to_utc() - begin),
+        None => end.to_utc(),
+    }
+}
+
+/// Where `stored` differs from the `form` it was created from, e.g. because
+/// the server rounds times itself, as `(what, stored, sent)`. Only what shows
+/// is compared, so seconds the server drops don't count.
+pub fn differences(
+    form: &TimesheetEditForm,
+    stored: &Timesheet,
+) -> Vec<(&'static str, String, String)> {
+    let time = |time: DateTime<Utc>| format::time(time.with_timezone(&clock::zone()).time());
+    let mut differences = vec![("start", time(stored.begin.to_utc()), time(form.begin))];
+    if let (Some(end), Some(stored_end)) = (form.end, stored.end) {
+        differences.push(("end", time(stored_end.to_utc()), time(end)));
+        if let Some(seconds) = stored.duration {
+            differences.push((
+                "duration",
+                format::duration(Duration::seconds(seconds)),
+                format::duration(end - form.begin),
+            ));
+        }
+    }
+    differences.retain(|(_, stored, sent)| stored != sent);
+    differences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(minutes: u32, direction: Direction) -> Rule {
+        Rule { minutes, direction }
+    }
+
+    #[test]
+    fn rounding() {
+        let minutes = |m: i64| m * 60;
+        assert_eq!(rule(15, Direction::Down).seconds(minutes(22)), minutes(15));
+        assert_eq!(rule(15, Direction::Up).seconds(minutes(16)), minutes(30));
+        assert_eq!(rule(15, Direction::Up).seconds(minutes(30)), minutes(30));
+        assert_eq!(
+            rule(15, Direction::Closest).seconds(minutes(22)),
+            minutes(15)
+        );
+        assert_eq!(
+            rule(15, Direction::Closest).seconds(minutes(22) + 30),
+            minutes(30)
+        );
+        assert_eq!(rule(0, Direction::Up).seconds(minutes(7)), minutes(7));
+        assert_eq!(
+            rule(5, Direction::Up).duration(Duration::minutes(61)),
+            Duration::minutes(65)
+        );
+    }
+
+    #[test]
+    fn differences_in_what_was_stored() {
+        let at = |time: &str| -> DateTime<Utc> { format!("2024-05-01T{}Z", time).parse().unwrap() };
+        let form = TimesheetEditForm {
+            begin: at("09:07:30"),
+            project: 1,
+            activity: 1,
+            end: Some(at("10:07:00")),
+            description: None,
+            tags: None,
+            billable: None,
+            meta_fields: Vec::new(),
+            user: None,
+        };
+        let stored = Timesheet {
+            id: 1,
+            begin: at("09:07:00").fixed_offset(),
+            end: Some(at("10:15:00").fixed_offset()),
+            duration: Some(68 * 60),
+            description: None,
+            project: 1,
+            activity: 1,
+            tags: Vec::new(),
+            exported: false,
+        };
+        let what: Vec<&str> = differences(&form, &stored)
+            .into_iter()
+            .map(|(what, _, _)| what)
+            .collect();
+        assert_eq!(what, ["end", "duration"]);
+    }
+}
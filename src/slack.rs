@@ -0,0 +1,73 @@
+//! The Slack status showing the running timer, from `[integrations.slack]`.
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::api::{self, TimesheetExpanded};
+use crate::integrations::Slack;
+use crate::{config, hooks};
+
+#[derive(Deserialize)]
+struct Response {
+    ok: bool,
+    error: Option<String>,
+}
+
+fn set_profile(config: &config::Config, slack: &Slack, text: &str, emoji: &str) -> Result<()> {
+    let response: Response = api::agent(config)?
+        .post("https://slack.com/api/users.profile.set")
+        .set("Authorization", &format!("Bearer {}", slack.token))
+        .send_json(json!({
+            "profile": {
+                "status_text": text,
+                "status_emoji": emoji,
+                "status_expiration": 0,
+            }
+        }))?
+        .into_json()?;
+    // Slack answers errors with 200 as well
+    if !response.ok {
+        anyhow::bail!(response
+            .error
+            .unwrap_or_else(|| "unknown error".to_string()));
+    }
+    Ok(())
+}
+
+/// The status for `running`, within Slack's 100 characters.
+fn text(slack: &Slack, running: &TimesheetExpanded) -> String {
+    let values = [
+        ("project", running.project.name.clone()),
+        ("activity", running.activity.name.clone()),
+        (
+            "customer",
+            running.project.parent_title.clone().unwrap_or_default(),
+        ),
+        (
+            "description",
+            running.description.clone().unwrap_or_default(),
+        ),
+    ];
+    hooks::fill(&slack.status, &values)
+        .chars()
+        .take(100)
+        .collect()
+}
+
+/// Show the most recently started of `active` as the Slack status, or clear
+/// the status when no timer is left running.
+pub fn update(config: &config::Config, active: &[TimesheetExpanded]) {
+    let Some(slack) = &config.integrations.slack else {
+        return;
+    };
+    if config.dry_run {
+        return;
+    }
+    let updated = match active.iter().max_by_key(|e| e.begin) {
+        Some(running) => set_profile(config, slack, &text(slack, running), &slack.emoji),
+        None => set_profile(config, slack, "", ""),
+    };
+    if let Err(e) = updated {
+        eprintln!("Could not update the Slack status: {:#}", e);
+    }
+}
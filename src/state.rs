@@ -0,0 +1,32 @@
+use anyhow::Result;
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::PathBuf;
+
+use crate::config;
+
+/// Directory for local state of the configured Kimai instance, like queued
+/// entries and favorites. Unlike the cache, this is not safe to delete.
+pub fn dir(config: &config::Config) -> Result<PathBuf> {
+    let dir = dirs::data_local_dir()
+        .ok_or_else(|| anyhow::anyhow!("Could not determine the local data directory"))?;
+    Ok(dir.join("kimai").join(config.instance_id()))
+}
+
+/// Read `<name>.json`, or the default if it doesn't exist yet.
+pub fn load<T: DeserializeOwned + Default>(config: &config::Config, name: &str) -> Result<T> {
+    let path = dir(config)?.join(format!("{}.json", name));
+    if !path.is_file() {
+        return Ok(T::default());
+    }
+    Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+pub fn save<T: Serialize>(config: &config::Config, name: &str, value: &T) -> Result<()> {
+    let dir = dir(config)?;
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(
+        dir.join(format!("{}.json", name)),
+        serde_json::to_string_pretty(value)?,
+    )?;
+    Ok(())
+}
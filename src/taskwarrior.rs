@@ -0,0 +1,175 @@
+//! Tasks from Taskwarrior, read with `task export`, and where their time goes
+//! in Kimai.
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Deserializer};
+use std::process::Command;
+
+use crate::config;
+
+/// Annotations `journal.time = on` adds when a task is started and stopped.
+const STARTED: &str = "Started task";
+const STOPPED: &str = "Stopped task";
+
+/// Taskwarrior writes times like `20240501T090000Z`.
+fn deserialize_time<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DateTime<Utc>, D::Error> {
+    let text = String::deserialize(deserializer)?;
+    NaiveDateTime::parse_from_str(&text, "%Y%m%dT%H%M%SZ")
+        .map(|time| time.and_utc())
+        .map_err(serde::de::Error::custom)
+}
+
+fn deserialize_optional_time<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<DateTime<Utc>>, D::Error> {
+    deserialize_time(deserializer).map(Some)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Annotation {
+    #[serde(deserialize_with = "deserialize_time")]
+    pub entry: DateTime<Utc>,
+    pub description: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Task {
+    pub description: String,
+    pub project: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// When the task was started, while it is active.
+    #[serde(default, deserialize_with = "deserialize_optional_time")]
+    pub start: Option<DateTime<Utc>>,
+    /// When the task was completed.
+    #[serde(default, deserialize_with = "deserialize_optional_time")]
+    pub end: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub annotations: Vec<Annotation>,
+}
+
+impl Task {
+    /// The times the task was worked on, as far as Taskwarrior knows: from
+    /// the journal annotations, and since it was started if it's active.
+    pub fn intervals(&self, now: DateTime<Utc>) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+        let mut annotations: Vec<&Annotation> = self.annotations.iter().collect();
+        annotations.sort_by_key(|a| a.entry);
+        let mut intervals = Vec::new();
+        let mut started = None;
+        for annotation in annotations {
+            match annotation.description.as_str() {
+                STARTED => started = Some(annotation.entry),
+                STOPPED => {
+                    if let Some(begin) = started.take() {
+                        intervals.push((begin, annotation.entry));
+                    }
+                }
+                _ => {}
+            }
+        }
+        // Completing an active task stops it without a stop annotation
+        match (self.start, started, self.end) {
+            (Some(begin), _, _) => intervals.push((begin, now)),
+            (None, Some(begin), Some(end)) => intervals.push((begin, end)),
+            _ => {}
+        }
+        intervals.retain(|(begin, end)| end > begin);
+        intervals
+    }
+
+    /// Kimai project and activity to look for, from `[taskwarrior]`.
+    pub fn needles<'a>(&self, config: &'a config::Config) -> (Option<&'a str>, Option<&'a str>) {
+        let Some(mapping) = &config.taskwarrior else {
+            return (None, None);
+        };
+        let project = self.project.as_deref().and_then(|project| {
+            mapping
+                .projects
+                .iter()
+                .filter(|(key, _)| {
+                    project == key.as_str()
+                        || project
+                            .strip_prefix(key.as_str())
+                            .is_some_and(|rest| rest.starts_with('.'))
+                })
+                .max_by_key(|(key, _)| key.len())
+                .map(|(_, project)| project.as_str())
+        });
+        let activity = self
+            .tags
+            .iter()
+            .find_map(|tag| mapping.tags.get(tag))
+            .map(String::as_str);
+        (project, activity)
+    }
+
+    /// Tags for Kimai: those that didn't pick the activity.
+    pub fn kimai_tags(&self, config: &config::Config) -> Vec<String> {
+        let mapped = config.taskwarrior.as_ref().map(|t| &t.tags);
+        self.tags
+            .iter()
+            .filter(|tag| !mapped.is_some_and(|mapped| mapped.contains_key(*tag)))
+            .cloned()
+            .collect()
+    }
+}
+
+/// The tasks matching `filter`, e.g. `["+ACTIVE"]`.
+pub fn export(filter: &[&str]) -> Result<Vec<Task>> {
+    let output = Command::new("task")
+        .args(["rc.hooks=off", "rc.verbose=nothing", "rc.json.array=on"])
+        .args(filter)
+        .arg("export")
+        .output()
+        .context("Could not run Taskwarrior's `task`")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "`task export` failed with {}\n{}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    serde_json::from_slice(&output.stdout).context("Could not read the tasks from `task export`")
+}
+
+/// The task with `uuid`, or a short id.
+pub fn task(uuid: &str) -> Result<Task> {
+    export(&[uuid])?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("No task {} in Taskwarrior", uuid))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(text: &str) -> DateTime<Utc> {
+        NaiveDateTime::parse_from_str(text, "%Y%m%dT%H%M%SZ")
+            .unwrap()
+            .and_utc()
+    }
+
+    #[test]
+    fn intervals_from_the_journal() {
+        let task: Task = serde_json::from_str(
+            r#"{
+                "description": "Checkout", "end": "20240501T120000Z",
+                "annotations": [
+                    {"entry": "20240501T090000Z", "description": "Started task"},
+                    {"entry": "20240501T093000Z", "description": "Stopped task"},
+                    {"entry": "20240501T094500Z", "description": "Ask Bob"},
+                    {"entry": "20240501T110000Z", "description": "Started task"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            task.intervals(at("20240501T150000Z")),
+            vec![
+                (at("20240501T090000Z"), at("20240501T093000Z")),
+                (at("20240501T110000Z"), at("20240501T120000Z")),
+            ]
+        );
+    }
+}
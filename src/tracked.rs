@@ -0,0 +1,341 @@
+//! Activity recorded by ActivityWatch or WakaTime, and the blocks of time on
+//! one project it adds up to, for `kimai suggest`.
+use anyhow::{Context, Result};
+use base64::Engine;
+use chrono::{DateTime, Duration, FixedOffset, Utc};
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{api, config};
+
+/// Something worked on for a while: a window, a file in an editor, or a
+/// project in WakaTime.
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub begin: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// What the block is described as, e.g. the window title.
+    pub title: String,
+    /// What the title belongs to, e.g. the app or the editor's project.
+    pub source: String,
+}
+
+/// A stretch of time the events of one rule make up the most of.
+#[derive(Debug, PartialEq)]
+pub struct Block {
+    /// Index of the rule.
+    pub rule: usize,
+    pub begin: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// The title seen for the longest within the block.
+    pub title: String,
+}
+
+/// Activity is weighed per slot of this length, so a quick look at another
+/// window doesn't split a block.
+const SLOT: i64 = 5 * 60;
+
+/// The parts of `begin` to `end` not covered by any of `cuts`.
+fn subtract(
+    begin: DateTime<Utc>,
+    end: DateTime<Utc>,
+    cuts: &[(DateTime<Utc>, DateTime<Utc>)],
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut cuts: Vec<_> = cuts
+        .iter()
+        .filter(|(b, e)| *b < end && *e > begin)
+        .collect();
+    cuts.sort();
+    let mut parts = Vec::new();
+    let mut cursor = begin;
+    for (cut_begin, cut_end) in cuts {
+        if *cut_begin > cursor {
+            parts.push((cursor, *cut_begin));
+        }
+        cursor = cursor.max(*cut_end);
+    }
+    if cursor < end {
+        parts.push((cursor, end));
+    }
+    parts
+}
+
+/// How much of a slot a rule's events cover, and where they begin and end.
+#[derive(Clone, Copy)]
+struct Share {
+    seconds: i64,
+    begin: DateTime<Utc>,
+    end: DateTime<Utc>,
+}
+
+/// Blocks of at least `min` in which events matching one of `rules` were
+/// the most, leaving out the time in `skip`, e.g. what's logged already.
+pub fn blocks(
+    events: &[Event],
+    rules: &[Regex],
+    skip: &[(DateTime<Utc>, DateTime<Utc>)],
+    min: Duration,
+) -> Vec<Block> {
+    let mut pieces = Vec::new();
+    for event in events {
+        let Some(rule) = rules
+            .iter()
+            .position(|r| r.is_match(&event.title) || r.is_match(&event.source))
+        else {
+            continue;
+        };
+        for (begin, end) in subtract(event.begin, event.end, skip) {
+            pieces.push((rule, begin, end, event.title.as_str()));
+        }
+    }
+
+    let mut slots: BTreeMap<i64, BTreeMap<usize, Share>> = BTreeMap::new();
+    for (rule, begin, end, _) in &pieces {
+        let mut from = *begin;
+        while from < *end {
+            let slot = from.timestamp().div_euclid(SLOT);
+            let slot_end = DateTime::from_timestamp((slot + 1) * SLOT, 0).unwrap();
+            let to = slot_end.min(*end);
+            let share = slots
+                .entry(slot)
+                .or_default()
+                .entry(*rule)
+                .or_insert(Share {
+                    seconds: 0,
+                    begin: from,
+                    end: to,
+                });
+            share.seconds += (to - from).num_seconds();
+            share.begin = share.begin.min(from);
+            share.end = share.end.max(to);
+            from = to;
+        }
+    }
+
+    // Neighbouring slots won by the same rule make one block
+    let mut blocks: Vec<Block> = Vec::new();
+    let mut last_slot = None;
+    for (slot, shares) in &slots {
+        let Some((rule, share)) = shares
+            .iter()
+            .max_by_key(|(rule, share)| (share.seconds, std::cmp::Reverse(**rule)))
+        else {
+            continue;
+        };
+        match blocks.last_mut() {
+            Some(block) if block.rule == *rule && last_slot == Some(slot - 1) => {
+                block.end = share.end;
+            }
+            _ => blocks.push(Block {
+                rule: *rule,
+                begin: share.begin,
+                end: share.end,
+                title: String::new(),
+            }),
+        }
+        last_slot = Some(*slot);
+    }
+
+    blocks.retain(|block| block.end - block.begin >= min);
+    for block in &mut blocks {
+        let mut titles: HashMap<&str, i64> = HashMap::new();
+        for (rule, begin, end, title) in &pieces {
+            let overlap = (*end).min(block.end) - (*begin).max(block.begin);
+            if *rule == block.rule && overlap > Duration::zero() {
+                *titles.entry(title).or_default() += overlap.num_seconds();
+            }
+        }
+        block.title = titles
+            .into_iter()
+            .max_by_key(|(title, seconds)| (*seconds, std::cmp::Reverse(*title)))
+            .map(|(title, _)| title.to_string())
+            .unwrap_or_default();
+    }
+    blocks
+}
+
+#[derive(Deserialize)]
+struct Bucket {
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+#[derive(Deserialize)]
+struct WatcherEvent {
+    timestamp: DateTime<FixedOffset>,
+    /// Seconds.
+    duration: f64,
+    data: HashMap<String, serde_json::Value>,
+}
+
+impl WatcherEvent {
+    fn field(&self, name: &str) -> String {
+        self.data
+            .get(name)
+            .and_then(|value| value.as_str())
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    fn span(&self) -> (DateTime<Utc>, DateTime<Utc>) {
+        let begin = self.timestamp.to_utc();
+        (
+            begin,
+            begin + Duration::milliseconds((self.duration * 1000.0) as i64),
+        )
+    }
+}
+
+/// Windows and editor files from the ActivityWatch server at `url`, without
+/// the time the computer was left alone.
+pub fn activitywatch(
+    config: &config::Config,
+    url: &str,
+    begin: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<Event>> {
+    let url = url.trim_end_matches('/');
+    let agent = api::agent(config)?;
+    let buckets: BTreeMap<String, Bucket> = agent
+        .get(&format!("{}/api/0/buckets/", url))
+        .call()
+        .with_context(|| format!("Could not reach ActivityWatch at {}", url))?
+        .into_json()?;
+
+    let mut events = Vec::new();
+    let mut away = Vec::new();
+    for (id, bucket) in buckets {
+        let watched: Vec<WatcherEvent> = agent
+            .get(&format!("{}/api/0/buckets/{}/events", url, id))
+            .query("start", &begin.to_rfc3339())
+            .query("end", &end.to_rfc3339())
+            .query("limit", "-1")
+            .call()
+            .with_context(|| format!("Could not read {} from ActivityWatch", id))?
+            .into_json()?;
+        for event in watched {
+            let (begin, end) = event.span();
+            let (title, source) = match bucket.kind.as_str() {
+                "currentwindow" => (event.field("title"), event.field("app")),
+                "app.editor.activity" => (event.field("file"), event.field("project")),
+                "afkstatus" => {
+                    if event.field("status") == "afk" {
+                        away.push((begin, end));
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+            events.push(Event {
+                begin,
+                end,
+                title,
+                source,
+            });
+        }
+    }
+
+    Ok(events
+        .into_iter()
+        .flat_map(|event| {
+            subtract(event.begin, event.end, &away)
+                .into_iter()
+                .map(move |(begin, end)| Event {
+                    begin,
+                    end,
+                    ..event.clone()
+                })
+        })
+        .collect())
+}
+
+#[derive(Deserialize)]
+struct Durations {
+    data: Vec<Coded>,
+}
+
+#[derive(Deserialize)]
+struct Coded {
+    project: String,
+    /// Unix time.
+    time: f64,
+    /// Seconds.
+    duration: f64,
+}
+
+/// The projects coded on `day` according to WakaTime.
+pub fn wakatime(config: &config::Config, key: &str, day: chrono::NaiveDate) -> Result<Vec<Event>> {
+    let key = base64::engine::general_purpose::STANDARD.encode(key);
+    let durations: Durations = api::agent(config)?
+        .get("https://wakatime.com/api/v1/users/current/durations")
+        .query("date", &day.format("%Y-%m-%d").to_string())
+        .set("Authorization", &format!("Basic {}", key))
+        .call()
+        .context("Could not read the durations from WakaTime")?
+        .into_json()?;
+    Ok(durations
+        .data
+        .into_iter()
+        .filter_map(|coded| {
+            let begin = DateTime::from_timestamp_millis((coded.time * 1000.0) as i64)?;
+            Some(Event {
+                begin,
+                end: begin + Duration::milliseconds((coded.duration * 1000.0) as i64),
+                title: coded.project.clone(),
+                source: coded.project,
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(time: &str) -> DateTime<Utc> {
+        format!("2024-05-01T{}:00Z", time).parse().unwrap()
+    }
+
+    fn event(begin: &str, end: &str, title: &str) -> Event {
+        Event {
+            begin: at(begin),
+            end: at(end),
+            title: title.to_string(),
+            source: String::new(),
+        }
+    }
+
+    #[test]
+    fn glances_elsewhere_dont_split_blocks() {
+        let events = [
+            event("09:00", "09:40", "kimai-cli – main.rs"),
+            event("09:40", "09:42", "Inbox – Mail"),
+            event("09:42", "10:30", "kimai-cli – log.rs"),
+            event("10:30", "11:00", "Acme standup"),
+            event("11:00", "11:05", "Acme notes"),
+        ];
+        let rules = [
+            Regex::new("kimai-cli").unwrap(),
+            Regex::new("Acme").unwrap(),
+        ];
+        let skip = [(at("10:50"), at("11:05"))];
+        assert_eq!(
+            blocks(&events, &rules, &skip, Duration::minutes(10)),
+            vec![
+                Block {
+                    rule: 0,
+                    begin: at("09:00"),
+                    end: at("10:30"),
+                    title: "kimai-cli – log.rs".to_string(),
+                },
+                Block {
+                    rule: 1,
+                    begin: at("10:30"),
+                    end: at("10:50"),
+                    title: "Acme standup".to_string(),
+                },
+            ]
+        );
+    }
+}